@@ -2,6 +2,27 @@ pub use sea_orm_migration::prelude::*;
 
 
 mod m20241206_041125_create_tables;
+mod m20241220_093000_create_wishlist_tables;
+mod m20241222_101500_add_product_recommendations;
+mod m20241226_140000_add_product_publication;
+mod m20241228_090000_create_product_translation;
+mod m20241230_110000_unique_cart_item_product;
+mod m20250103_120000_allow_guest_carts;
+mod m20250106_100000_single_active_cart;
+mod m20250108_090000_create_cart_abandonment;
+mod m20250110_090000_add_product_max_quantity;
+mod m20250112_090000_add_cart_item_saved_for_later;
+mod m20250114_090000_add_cart_item_unit_price;
+mod m20250116_090000_order_status_enums;
+mod m20250118_090000_create_idempotency_key;
+mod m20250120_090000_add_stock_and_order_cancellation;
+mod m20250122_090000_create_refund;
+mod m20250124_090000_create_shipping_method;
+mod m20250126_090000_create_tax_class;
+mod m20250128_090000_create_coupon;
+mod m20250130_090000_create_promotion;
+mod m20250201_090000_create_invoice;
+mod m20250203_090000_add_refund_status;
 
 pub struct Migrator;
 
@@ -10,6 +31,27 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(m20241206_041125_create_tables::Migration),
+            Box::new(m20241220_093000_create_wishlist_tables::Migration),
+            Box::new(m20241222_101500_add_product_recommendations::Migration),
+            Box::new(m20241226_140000_add_product_publication::Migration),
+            Box::new(m20241228_090000_create_product_translation::Migration),
+            Box::new(m20241230_110000_unique_cart_item_product::Migration),
+            Box::new(m20250103_120000_allow_guest_carts::Migration),
+            Box::new(m20250106_100000_single_active_cart::Migration),
+            Box::new(m20250108_090000_create_cart_abandonment::Migration),
+            Box::new(m20250110_090000_add_product_max_quantity::Migration),
+            Box::new(m20250112_090000_add_cart_item_saved_for_later::Migration),
+            Box::new(m20250114_090000_add_cart_item_unit_price::Migration),
+            Box::new(m20250116_090000_order_status_enums::Migration),
+            Box::new(m20250118_090000_create_idempotency_key::Migration),
+            Box::new(m20250120_090000_add_stock_and_order_cancellation::Migration),
+            Box::new(m20250122_090000_create_refund::Migration),
+            Box::new(m20250124_090000_create_shipping_method::Migration),
+            Box::new(m20250126_090000_create_tax_class::Migration),
+            Box::new(m20250128_090000_create_coupon::Migration),
+            Box::new(m20250130_090000_create_promotion::Migration),
+            Box::new(m20250201_090000_create_invoice::Migration),
+            Box::new(m20250203_090000_add_refund_status::Migration),
         ]
     }
 }
@@ -0,0 +1,169 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create the `wishlist` table
+        manager
+            .create_table(
+                Table::create()
+                    .table(Wishlist::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Wishlist::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Wishlist::UserId).integer().not_null())
+                    .col(ColumnDef::new(Wishlist::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Wishlist::ShareToken)
+                            .string()
+                            .null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Wishlist::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Wishlist::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()".to_owned()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Wishlist::Table, Wishlist::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A user cannot have two lists with the same name
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_wishlist_user_id_name")
+                    .table(Wishlist::Table)
+                    .col(Wishlist::UserId)
+                    .col(Wishlist::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create the `wishlist_item` table
+        manager
+            .create_table(
+                Table::create()
+                    .table(WishlistItem::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WishlistItem::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WishlistItem::WishlistId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WishlistItem::ProductId).integer().not_null())
+                    .col(
+                        ColumnDef::new(WishlistItem::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(WishlistItem::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()".to_owned()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WishlistItem::Table, WishlistItem::WishlistId)
+                            .to(Wishlist::Table, Wishlist::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WishlistItem::Table, WishlistItem::ProductId)
+                            .to(Product::Table, Product::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A product appears at most once per list
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_wishlist_item_wishlist_id_product_id")
+                    .table(WishlistItem::Table)
+                    .col(WishlistItem::WishlistId)
+                    .col(WishlistItem::ProductId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WishlistItem::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Wishlist::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Wishlist {
+    Table,
+    Id,
+    UserId,
+    Name,
+    ShareToken,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum WishlistItem {
+    Table,
+    Id,
+    WishlistId,
+    ProductId,
+    CreatedAt,
+    UpdatedAt,
+}
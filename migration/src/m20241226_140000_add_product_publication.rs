@@ -0,0 +1,162 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Roles decide who may use the staff endpoints
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(UserRole::Enum)
+                    .values([UserRole::Customer, UserRole::Staff, UserRole::Admin])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::Role)
+                            .enumeration(
+                                UserRole::Enum,
+                                [UserRole::Customer, UserRole::Staff, UserRole::Admin],
+                            )
+                            .not_null()
+                            .default("customer"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ProductStatus::Enum)
+                    .values([
+                        ProductStatus::Draft,
+                        ProductStatus::Published,
+                        ProductStatus::Unlisted,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing products stay visible, so they are backfilled as published
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .add_column(
+                        ColumnDef::new(Product::Status)
+                            .enumeration(
+                                ProductStatus::Enum,
+                                [
+                                    ProductStatus::Draft,
+                                    ProductStatus::Published,
+                                    ProductStatus::Unlisted,
+                                ],
+                            )
+                            .not_null()
+                            .default("published"),
+                    )
+                    .add_column(
+                        ColumnDef::new(Product::AvailableFrom)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(Product::AvailableUntil)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_status")
+                    .table(Product::Table)
+                    .col(Product::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_product_status")
+                    .table(Product::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .drop_column(Product::Status)
+                    .drop_column(Product::AvailableFrom)
+                    .drop_column(Product::AvailableUntil)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(ProductStatus::Enum).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Role)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(UserRole::Enum).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum UserRole {
+    #[sea_orm(iden = "user_role")]
+    Enum,
+    Customer,
+    Staff,
+    Admin,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Status,
+    AvailableFrom,
+    AvailableUntil,
+}
+
+#[derive(DeriveIden)]
+enum ProductStatus {
+    #[sea_orm(iden = "product_status")]
+    Enum,
+    Draft,
+    Published,
+    Unlisted,
+}
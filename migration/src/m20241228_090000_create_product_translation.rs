@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create the `product_translation` table
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductTranslation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProductTranslation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductTranslation::ProductId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductTranslation::Locale)
+                            .string_len(8)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProductTranslation::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(ProductTranslation::Description)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductTranslation::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(ProductTranslation::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()".to_owned()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProductTranslation::Table, ProductTranslation::ProductId)
+                            .to(Product::Table, Product::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One translation per product and locale
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_translation_product_id_locale")
+                    .table(ProductTranslation::Table)
+                    .col(ProductTranslation::ProductId)
+                    .col(ProductTranslation::Locale)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProductTranslation::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ProductTranslation {
+    Table,
+    Id,
+    ProductId,
+    Locale,
+    Name,
+    Description,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Merge duplicate product lines into the oldest line of each cart
+        db.execute_unprepared(
+            r#"
+            UPDATE cart_item AS ci
+            SET quantity = merged.total_quantity
+            FROM (
+                SELECT MIN(id) AS id, SUM(quantity) AS total_quantity
+                FROM cart_item
+                GROUP BY cart_id, product_id
+                HAVING COUNT(*) > 1
+            ) AS merged
+            WHERE ci.id = merged.id
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            DELETE FROM cart_item AS duplicate
+            USING cart_item AS kept
+            WHERE duplicate.cart_id = kept.cart_id
+              AND duplicate.product_id = kept.product_id
+              AND duplicate.id > kept.id
+            "#,
+        )
+        .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_cart_item_cart_id_product_id")
+                    .table(CartItem::Table)
+                    .col(CartItem::CartId)
+                    .col(CartItem::ProductId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_cart_item_cart_id_product_id")
+                    .table(CartItem::Table)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CartItem {
+    Table,
+    CartId,
+    ProductId,
+}
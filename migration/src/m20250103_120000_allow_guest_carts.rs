@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Guest carts have no owner until the visitor logs in or registers
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Cart::Table)
+                    .modify_column(ColumnDef::new(Cart::UserId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Idle carts are looked up by their last update
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_cart_updated_at")
+                    .table(Cart::Table)
+                    .col(Cart::UpdatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_cart_updated_at")
+                    .table(Cart::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Guest carts cannot be represented once the column is required again
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "DELETE FROM cart_item WHERE cart_id IN (SELECT id FROM cart WHERE user_id IS NULL)",
+        )
+        .await?;
+        db.execute_unprepared("DELETE FROM cart WHERE user_id IS NULL")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Cart::Table)
+                    .modify_column(ColumnDef::new(Cart::UserId).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Cart {
+    Table,
+    UserId,
+    UpdatedAt,
+}
@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(CartKind::Enum)
+                    .values([CartKind::Active, CartKind::Saved])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Cart::Table)
+                    .add_column(
+                        ColumnDef::new(Cart::Kind)
+                            .enumeration(CartKind::Enum, [CartKind::Active, CartKind::Saved])
+                            .not_null()
+                            .default("active"),
+                    )
+                    .add_column(ColumnDef::new(Cart::Name).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Users with several carts keep the most recently used one as their
+        // active cart; the others are preserved as saved carts
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            UPDATE cart AS c
+            SET kind = 'saved', name = 'Cart ' || c.id
+            WHERE c.user_id IS NOT NULL
+              AND EXISTS (
+                  SELECT 1 FROM cart AS newer
+                  WHERE newer.user_id = c.user_id
+                    AND (newer.updated_at, newer.id) > (c.updated_at, c.id)
+              )
+            "#,
+        )
+        .await?;
+
+        // At most one active cart per user
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX idx_cart_active_user_id ON cart (user_id) WHERE kind = 'active'",
+        )
+        .await?;
+
+        // Saved cart names are unique per user
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX idx_cart_saved_user_id_name ON cart (user_id, name) WHERE kind = 'saved'",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_cart_saved_user_id_name")
+            .await?;
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_cart_active_user_id")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Cart::Table)
+                    .drop_column(Cart::Kind)
+                    .drop_column(Cart::Name)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(CartKind::Enum).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Cart {
+    Table,
+    Kind,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum CartKind {
+    #[sea_orm(iden = "cart_kind")]
+    Enum,
+    Active,
+    Saved,
+}
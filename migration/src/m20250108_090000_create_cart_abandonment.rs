@@ -0,0 +1,98 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per time a cart went idle; kept after the cart itself is purged
+        // so abandonment can still be measured
+        manager
+            .create_table(
+                Table::create()
+                    .table(CartAbandonment::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CartAbandonment::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CartAbandonment::CartId).integer().not_null())
+                    .col(ColumnDef::new(CartAbandonment::UserId).integer().null())
+                    .col(
+                        ColumnDef::new(CartAbandonment::ItemCount)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CartAbandonment::CartTotal)
+                            .decimal()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CartAbandonment::AbandonedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CartAbandonment::ReminderSentAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(CartAbandonment::RecoveredAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Open abandonments are looked up by cart on every cart update
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_cart_abandonment_cart_id")
+                    .table(CartAbandonment::Table)
+                    .col(CartAbandonment::CartId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Metrics are computed over a window of abandonment times
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_cart_abandonment_abandoned_at")
+                    .table(CartAbandonment::Table)
+                    .col(CartAbandonment::AbandonedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CartAbandonment::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CartAbandonment {
+    Table,
+    Id,
+    CartId,
+    UserId,
+    ItemCount,
+    CartTotal,
+    AbandonedAt,
+    ReminderSentAt,
+    RecoveredAt,
+}
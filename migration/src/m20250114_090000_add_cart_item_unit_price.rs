@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Price the customer saw when the line was added
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CartItem::Table)
+                    .add_column(ColumnDef::new(CartItem::UnitPrice).decimal().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing lines are snapshotted at today's price
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            UPDATE cart_item AS ci
+            SET unit_price = p.price
+            FROM product AS p
+            WHERE p.id = ci.product_id
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CartItem::Table)
+                    .modify_column(ColumnDef::new(CartItem::UnitPrice).decimal().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CartItem::Table)
+                    .drop_column(CartItem::UnitPrice)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CartItem {
+    Table,
+    UnitPrice,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "CREATE TYPE order_status AS ENUM \
+             ('pending_payment', 'paid', 'processing', 'shipped', 'delivered', 'cancelled', 'refunded')",
+        )
+        .await?;
+        db.execute_unprepared("CREATE TYPE payment_status AS ENUM ('pending', 'paid', 'refunded')")
+            .await?;
+        db.execute_unprepared(
+            "CREATE TYPE fulfilment_status AS ENUM \
+             ('unfulfilled', 'processing', 'shipped', 'delivered', 'cancelled')",
+        )
+        .await?;
+
+        // Free-form payment statuses are folded into the typed values;
+        // anything unrecognised is treated as still awaiting payment
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE "order"
+            ALTER COLUMN payment_status TYPE payment_status USING (
+                CASE lower(trim(payment_status))
+                    WHEN 'paid' THEN 'paid'
+                    WHEN 'completed' THEN 'paid'
+                    WHEN 'refunded' THEN 'refunded'
+                    ELSE 'pending'
+                END
+            )::payment_status,
+            ALTER COLUMN payment_status SET DEFAULT 'pending'
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE "order"
+            ADD COLUMN status order_status NOT NULL DEFAULT 'pending_payment',
+            ADD COLUMN fulfilment_status fulfilment_status NOT NULL DEFAULT 'unfulfilled'
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            UPDATE "order"
+            SET status = CASE payment_status
+                WHEN 'paid' THEN 'paid'::order_status
+                WHEN 'refunded' THEN 'refunded'::order_status
+                ELSE 'pending_payment'::order_status
+            END
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(r#"CREATE INDEX idx_order_status ON "order" (status)"#)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_order_status")
+            .await?;
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE "order"
+            DROP COLUMN status,
+            DROP COLUMN fulfilment_status,
+            ALTER COLUMN payment_status DROP DEFAULT,
+            ALTER COLUMN payment_status TYPE varchar USING (
+                CASE payment_status
+                    WHEN 'paid' THEN 'Paid'
+                    WHEN 'refunded' THEN 'Refunded'
+                    ELSE 'Pending'
+                END
+            )
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared("DROP TYPE fulfilment_status").await?;
+        db.execute_unprepared("DROP TYPE payment_status").await?;
+        db.execute_unprepared("DROP TYPE order_status").await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Orders that got part of their payment back
+        db.execute_unprepared(
+            "ALTER TYPE payment_status ADD VALUE IF NOT EXISTS 'partially_refunded'",
+        )
+        .await?;
+
+        // Money returned to a customer for an order
+        manager
+            .create_table(
+                Table::create()
+                    .table(Refund::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Refund::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Refund::OrderId).integer().not_null())
+                    .col(
+                        ColumnDef::new(Refund::Amount)
+                            .decimal()
+                            .not_null()
+                            .check(Expr::col(Refund::Amount).gt(0)),
+                    )
+                    .col(ColumnDef::new(Refund::Reason).text().null())
+                    .col(ColumnDef::new(Refund::ProviderReference).string().null())
+                    .col(ColumnDef::new(Refund::CreatedBy).integer().not_null())
+                    .col(
+                        ColumnDef::new(Refund::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Refund::Table, Refund::OrderId)
+                            .to(Order::Table, Order::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Refund::Table, Refund::CreatedBy)
+                            .to(User::Table, User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Order lines (and how many of their units) a refund covers
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefundItem::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RefundItem::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RefundItem::RefundId).integer().not_null())
+                    .col(ColumnDef::new(RefundItem::OrderItemId).integer().not_null())
+                    .col(
+                        ColumnDef::new(RefundItem::Quantity)
+                            .integer()
+                            .not_null()
+                            .check(Expr::col(RefundItem::Quantity).gt(0)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RefundItem::Table, RefundItem::RefundId)
+                            .to(Refund::Table, Refund::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RefundItem::Table, RefundItem::OrderItemId)
+                            .to(OrderItem::Table, OrderItem::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refund_order_id")
+                    .table(Refund::Table)
+                    .col(Refund::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refund_item_order_item_id")
+                    .table(RefundItem::Table)
+                    .col(RefundItem::OrderItemId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RefundItem::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Refund::Table).to_owned())
+            .await?;
+
+        // Postgres cannot drop an enum value; fold it back into `paid`
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE \"order\" SET payment_status = 'paid' \
+                 WHERE payment_status = 'partially_refunded'",
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Refund {
+    Table,
+    Id,
+    OrderId,
+    Amount,
+    Reason,
+    ProviderReference,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum RefundItem {
+    Table,
+    Id,
+    RefundId,
+    OrderItemId,
+    Quantity,
+}
+
+#[derive(DeriveIden)]
+enum Order {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum OrderItem {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
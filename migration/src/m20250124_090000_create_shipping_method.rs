@@ -0,0 +1,221 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ShippingMethodKind::Enum)
+                    .values([
+                        ShippingMethodKind::FlatRate,
+                        ShippingMethodKind::WeightBased,
+                        ShippingMethodKind::FreeOverThreshold,
+                        ShippingMethodKind::StorePickup,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Ways an order can reach the customer, configured by staff
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShippingMethod::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ShippingMethod::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::Code)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(ShippingMethod::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(ShippingMethod::Kind)
+                            .enumeration(
+                                ShippingMethodKind::Enum,
+                                [
+                                    ShippingMethodKind::FlatRate,
+                                    ShippingMethodKind::WeightBased,
+                                    ShippingMethodKind::FreeOverThreshold,
+                                    ShippingMethodKind::StorePickup,
+                                ],
+                            )
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::Fee)
+                            .decimal()
+                            .not_null()
+                            .default(0)
+                            .check(Expr::col(ShippingMethod::Fee).gte(0)),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::PerKgFee)
+                            .decimal()
+                            .null()
+                            .check(Expr::col(ShippingMethod::PerKgFee).gte(0)),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::FreeOver)
+                            .decimal()
+                            .null()
+                            .check(Expr::col(ShippingMethod::FreeOver).gte(0)),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::MaxWeightGrams)
+                            .integer()
+                            .null()
+                            .check(Expr::col(ShippingMethod::MaxWeightGrams).gt(0)),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::CountryCode)
+                            .string_len(2)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingMethod::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Shipping weight of one unit; products without a weight count as weightless
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .add_column(
+                        ColumnDef::new(Product::WeightGrams)
+                            .integer()
+                            .null()
+                            .check(Expr::col(Product::WeightGrams).gte(0)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The shipping chosen at checkout, charged separately from the items
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Order::Table)
+                    .add_column(ColumnDef::new(Order::ShippingMethodId).integer().null())
+                    .add_column(
+                        ColumnDef::new(Order::ShippingCost)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(Order::ShippingAddress).json_binary().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_order_shipping_method_id")
+                            .from_tbl(Order::Table)
+                            .from_col(Order::ShippingMethodId)
+                            .to_tbl(ShippingMethod::Table)
+                            .to_col(ShippingMethod::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Order::Table)
+                    .drop_foreign_key(Alias::new("fk_order_shipping_method_id"))
+                    .drop_column(Order::ShippingMethodId)
+                    .drop_column(Order::ShippingCost)
+                    .drop_column(Order::ShippingAddress)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .drop_column(Product::WeightGrams)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ShippingMethod::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(ShippingMethodKind::Enum).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ShippingMethod {
+    Table,
+    Id,
+    Code,
+    Name,
+    Kind,
+    Fee,
+    PerKgFee,
+    FreeOver,
+    MaxWeightGrams,
+    CountryCode,
+    Active,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ShippingMethodKind {
+    #[sea_orm(iden = "shipping_method_kind")]
+    Enum,
+    FlatRate,
+    WeightBased,
+    FreeOverThreshold,
+    StorePickup,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    WeightGrams,
+}
+
+#[derive(DeriveIden)]
+enum Order {
+    Table,
+    ShippingMethodId,
+    ShippingCost,
+    ShippingAddress,
+}
@@ -0,0 +1,217 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // VAT treatment of a group of products
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaxClass::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaxClass::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TaxClass::Code)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(TaxClass::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(TaxClass::RatePercent)
+                            .decimal_len(5, 2)
+                            .not_null()
+                            .check(Expr::col(TaxClass::RatePercent).gte(0)),
+                    )
+                    .col(
+                        ColumnDef::new(TaxClass::Exempt)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(TaxClass::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(TaxClass::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Thai VAT at 7% for everything by default, plus VAT-exempt goods
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "INSERT INTO tax_class (code, name, rate_percent, exempt) VALUES \
+             ('standard', 'VAT 7%', 7.00, FALSE), \
+             ('exempt', 'VAT exempt', 0.00, TRUE)",
+        )
+        .await?;
+
+        // Products without a tax class use the standard class
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .add_column(ColumnDef::new(Product::TaxClassId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_product_tax_class_id")
+                            .from_tbl(Product::Table)
+                            .from_col(Product::TaxClassId)
+                            .to_tbl(TaxClass::Table)
+                            .to_col(TaxClass::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Tax breakdown of every order line and order, as charged
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItem::Table)
+                    .add_column(
+                        ColumnDef::new(OrderItem::TaxRate)
+                            .decimal_len(5, 2)
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(OrderItem::NetAmount)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(OrderItem::TaxAmount)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(OrderItem::GrossAmount)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Order::Table)
+                    .add_column(
+                        ColumnDef::new(Order::NetAmount)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Order::TaxAmount)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Orders placed before VAT was tracked carry no tax
+        db.execute_unprepared(
+            "UPDATE order_item SET gross_amount = price * quantity, net_amount = price * quantity",
+        )
+        .await?;
+        db.execute_unprepared(r#"UPDATE "order" SET net_amount = total_amount"#)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Order::Table)
+                    .drop_column(Order::NetAmount)
+                    .drop_column(Order::TaxAmount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItem::Table)
+                    .drop_column(OrderItem::TaxRate)
+                    .drop_column(OrderItem::NetAmount)
+                    .drop_column(OrderItem::TaxAmount)
+                    .drop_column(OrderItem::GrossAmount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .drop_foreign_key(Alias::new("fk_product_tax_class_id"))
+                    .drop_column(Product::TaxClassId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TaxClass::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum TaxClass {
+    Table,
+    Id,
+    Code,
+    Name,
+    RatePercent,
+    Exempt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    TaxClassId,
+}
+
+#[derive(DeriveIden)]
+enum OrderItem {
+    Table,
+    TaxRate,
+    NetAmount,
+    TaxAmount,
+    GrossAmount,
+}
+
+#[derive(DeriveIden)]
+enum Order {
+    Table,
+    NetAmount,
+    TaxAmount,
+}
@@ -0,0 +1,445 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(CouponKind::Enum)
+                    .values([CouponKind::Percentage, CouponKind::FixedAmount])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Discount codes customers can apply to a cart
+        manager
+            .create_table(
+                Table::create()
+                    .table(Coupon::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Coupon::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Coupon::Code)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Coupon::Description).text().null())
+                    .col(
+                        ColumnDef::new(Coupon::Kind)
+                            .enumeration(
+                                CouponKind::Enum,
+                                [CouponKind::Percentage, CouponKind::FixedAmount],
+                            )
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Coupon::Value)
+                            .decimal()
+                            .not_null()
+                            .check(Expr::col(Coupon::Value).gt(0)),
+                    )
+                    .col(ColumnDef::new(Coupon::MinSpend).decimal().null())
+                    .col(
+                        ColumnDef::new(Coupon::StartsAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Coupon::EndsAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(Coupon::UsageLimit).integer().null())
+                    .col(ColumnDef::new(Coupon::PerUserLimit).integer().null())
+                    .col(
+                        ColumnDef::new(Coupon::TimesRedeemed)
+                            .integer()
+                            .not_null()
+                            .default(0)
+                            .check(Expr::col(Coupon::TimesRedeemed).gte(0)),
+                    )
+                    .col(
+                        ColumnDef::new(Coupon::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(Coupon::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Coupon::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Products a coupon is limited to; no rows means no product restriction
+        manager
+            .create_table(
+                Table::create()
+                    .table(CouponProduct::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CouponProduct::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CouponProduct::CouponId).integer().not_null())
+                    .col(
+                        ColumnDef::new(CouponProduct::ProductId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CouponProduct::Table, CouponProduct::CouponId)
+                            .to(Coupon::Table, Coupon::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CouponProduct::Table, CouponProduct::ProductId)
+                            .to(Product::Table, Product::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_coupon_product_unique")
+                    .table(CouponProduct::Table)
+                    .col(CouponProduct::CouponId)
+                    .col(CouponProduct::ProductId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Categories a coupon is limited to; no rows means no category restriction
+        manager
+            .create_table(
+                Table::create()
+                    .table(CouponCategory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CouponCategory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponCategory::CouponId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CouponCategory::Category).string().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CouponCategory::Table, CouponCategory::CouponId)
+                            .to(Coupon::Table, Coupon::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_coupon_category_unique")
+                    .table(CouponCategory::Table)
+                    .col(CouponCategory::CouponId)
+                    .col(CouponCategory::Category)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Code applied to a cart, checked again at checkout
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Cart::Table)
+                    .add_column(ColumnDef::new(Cart::CouponId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_cart_coupon_id")
+                            .from_tbl(Cart::Table)
+                            .from_col(Cart::CouponId)
+                            .to_tbl(Coupon::Table)
+                            .to_col(Coupon::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Discount given on each order and order line
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Order::Table)
+                    .add_column(ColumnDef::new(Order::CouponId).integer().null())
+                    .add_column(
+                        ColumnDef::new(Order::DiscountAmount)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_order_coupon_id")
+                            .from_tbl(Order::Table)
+                            .from_col(Order::CouponId)
+                            .to_tbl(Coupon::Table)
+                            .to_col(Coupon::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItem::Table)
+                    .add_column(
+                        ColumnDef::new(OrderItem::DiscountAmount)
+                            .decimal()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One row per order that used a coupon, for the per-user limits
+        manager
+            .create_table(
+                Table::create()
+                    .table(CouponRedemption::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CouponRedemption::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemption::CouponId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemption::OrderId)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemption::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemption::Amount)
+                            .decimal()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemption::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CouponRedemption::Table, CouponRedemption::CouponId)
+                            .to(Coupon::Table, Coupon::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CouponRedemption::Table, CouponRedemption::OrderId)
+                            .to(Order::Table, Order::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CouponRedemption::Table, CouponRedemption::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_coupon_redemption_coupon_user")
+                    .table(CouponRedemption::Table)
+                    .col(CouponRedemption::CouponId)
+                    .col(CouponRedemption::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CouponRedemption::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItem::Table)
+                    .drop_column(OrderItem::DiscountAmount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Order::Table)
+                    .drop_foreign_key(Alias::new("fk_order_coupon_id"))
+                    .drop_column(Order::CouponId)
+                    .drop_column(Order::DiscountAmount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Cart::Table)
+                    .drop_foreign_key(Alias::new("fk_cart_coupon_id"))
+                    .drop_column(Cart::CouponId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CouponCategory::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CouponProduct::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Coupon::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(CouponKind::Enum).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Coupon {
+    Table,
+    Id,
+    Code,
+    Description,
+    Kind,
+    Value,
+    MinSpend,
+    StartsAt,
+    EndsAt,
+    UsageLimit,
+    PerUserLimit,
+    TimesRedeemed,
+    Active,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum CouponKind {
+    #[sea_orm(iden = "coupon_kind")]
+    Enum,
+    Percentage,
+    FixedAmount,
+}
+
+#[derive(DeriveIden)]
+enum CouponProduct {
+    Table,
+    Id,
+    CouponId,
+    ProductId,
+}
+
+#[derive(DeriveIden)]
+enum CouponCategory {
+    Table,
+    Id,
+    CouponId,
+    Category,
+}
+
+#[derive(DeriveIden)]
+enum CouponRedemption {
+    Table,
+    Id,
+    CouponId,
+    OrderId,
+    UserId,
+    Amount,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Cart {
+    Table,
+    CouponId,
+}
+
+#[derive(DeriveIden)]
+enum Order {
+    Table,
+    Id,
+    CouponId,
+    DiscountAmount,
+}
+
+#[derive(DeriveIden)]
+enum OrderItem {
+    Table,
+    DiscountAmount,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,322 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(PromotionKind::Enum)
+                    .values([
+                        PromotionKind::BuyXGetY,
+                        PromotionKind::TieredPercentage,
+                        PromotionKind::BundlePrice,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Discounts applied automatically while a cart is priced
+        manager
+            .create_table(
+                Table::create()
+                    .table(Promotion::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Promotion::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Promotion::Name).string().not_null())
+                    .col(ColumnDef::new(Promotion::Description).text().null())
+                    .col(
+                        ColumnDef::new(Promotion::Kind)
+                            .enumeration(
+                                PromotionKind::Enum,
+                                [
+                                    PromotionKind::BuyXGetY,
+                                    PromotionKind::TieredPercentage,
+                                    PromotionKind::BundlePrice,
+                                ],
+                            )
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::Priority)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::Stackable)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::CombinableWithCoupons)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::BuyQuantity)
+                            .integer()
+                            .null()
+                            .check(Expr::col(Promotion::BuyQuantity).gt(0)),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::GetQuantity)
+                            .integer()
+                            .null()
+                            .check(Expr::col(Promotion::GetQuantity).gt(0)),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::BundleQuantity)
+                            .integer()
+                            .null()
+                            .check(Expr::col(Promotion::BundleQuantity).gt(1)),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::BundlePrice)
+                            .decimal()
+                            .null()
+                            .check(Expr::col(Promotion::BundlePrice).gte(0)),
+                    )
+                    .col(ColumnDef::new(Promotion::Tiers).json_binary().null())
+                    .col(
+                        ColumnDef::new(Promotion::StartsAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::EndsAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Promotion::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Products a promotion is limited to; no rows means no product restriction
+        manager
+            .create_table(
+                Table::create()
+                    .table(PromotionProduct::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PromotionProduct::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PromotionProduct::PromotionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PromotionProduct::ProductId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PromotionProduct::Table, PromotionProduct::PromotionId)
+                            .to(Promotion::Table, Promotion::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PromotionProduct::Table, PromotionProduct::ProductId)
+                            .to(Product::Table, Product::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_promotion_product_unique")
+                    .table(PromotionProduct::Table)
+                    .col(PromotionProduct::PromotionId)
+                    .col(PromotionProduct::ProductId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Categories a promotion is limited to; no rows means no category restriction
+        manager
+            .create_table(
+                Table::create()
+                    .table(PromotionCategory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PromotionCategory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PromotionCategory::PromotionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PromotionCategory::Category)
+                            .string()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PromotionCategory::Table, PromotionCategory::PromotionId)
+                            .to(Promotion::Table, Promotion::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_promotion_category_unique")
+                    .table(PromotionCategory::Table)
+                    .col(PromotionCategory::PromotionId)
+                    .col(PromotionCategory::Category)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Which promotions and codes changed the price of each order line
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItem::Table)
+                    .add_column(
+                        ColumnDef::new(OrderItem::Adjustments)
+                            .json_binary()
+                            .not_null()
+                            .default(Expr::cust("'[]'::jsonb")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderItem::Table)
+                    .drop_column(OrderItem::Adjustments)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PromotionCategory::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PromotionProduct::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Promotion::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(PromotionKind::Enum).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Promotion {
+    Table,
+    Id,
+    Name,
+    Description,
+    Kind,
+    Priority,
+    Stackable,
+    CombinableWithCoupons,
+    BuyQuantity,
+    GetQuantity,
+    BundleQuantity,
+    BundlePrice,
+    Tiers,
+    StartsAt,
+    EndsAt,
+    Active,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PromotionKind {
+    #[sea_orm(iden = "promotion_kind")]
+    Enum,
+    #[sea_orm(iden = "buy_x_get_y")]
+    BuyXGetY,
+    TieredPercentage,
+    BundlePrice,
+}
+
+#[derive(DeriveIden)]
+enum PromotionProduct {
+    Table,
+    Id,
+    PromotionId,
+    ProductId,
+}
+
+#[derive(DeriveIden)]
+enum PromotionCategory {
+    Table,
+    Id,
+    PromotionId,
+    Category,
+}
+
+#[derive(DeriveIden)]
+enum OrderItem {
+    Table,
+    Adjustments,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Id,
+}
@@ -0,0 +1,125 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Last invoice number handed out in each fiscal year; the row is
+        // locked while a number is taken so the sequence has no gaps
+        manager
+            .create_table(
+                Table::create()
+                    .table(InvoiceSequence::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(InvoiceSequence::FiscalYear)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(InvoiceSequence::LastNumber)
+                            .integer()
+                            .not_null()
+                            .check(Expr::col(InvoiceSequence::LastNumber).gt(0)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Invoice issued for an order; the number never changes once issued,
+        // and an invoiced order cannot be deleted
+        manager
+            .create_table(
+                Table::create()
+                    .table(Invoice::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Invoice::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Invoice::OrderId)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Invoice::FiscalYear).integer().not_null())
+                    .col(ColumnDef::new(Invoice::SequenceNumber).integer().not_null())
+                    .col(
+                        ColumnDef::new(Invoice::Number)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Invoice::IssuedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Invoice::Table, Invoice::OrderId)
+                            .to(Order::Table, Order::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_invoice_fiscal_year_sequence")
+                    .table(Invoice::Table)
+                    .col(Invoice::FiscalYear)
+                    .col(Invoice::SequenceNumber)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Invoice::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(InvoiceSequence::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum InvoiceSequence {
+    Table,
+    FiscalYear,
+    LastNumber,
+}
+
+#[derive(DeriveIden)]
+enum Invoice {
+    Table,
+    Id,
+    OrderId,
+    FiscalYear,
+    SequenceNumber,
+    #[sea_orm(iden = "invoice_number")]
+    Number,
+    IssuedAt,
+}
+
+#[derive(DeriveIden)]
+enum Order {
+    Table,
+    Id,
+}
@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(RefundStatus::Enum)
+                    .values([
+                        RefundStatus::Pending,
+                        RefundStatus::Completed,
+                        RefundStatus::Failed,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // A refund is recorded as pending before the payment provider is asked
+        // to send the money, and settled once it answers; refunds recorded
+        // before this migration were all sent
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Refund::Table)
+                    .add_column(
+                        ColumnDef::new(Refund::Status)
+                            .enumeration(
+                                RefundStatus::Enum,
+                                [
+                                    RefundStatus::Pending,
+                                    RefundStatus::Completed,
+                                    RefundStatus::Failed,
+                                ],
+                            )
+                            .not_null()
+                            .default("completed"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Refund::Table)
+                    .drop_column(Refund::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(RefundStatus::Enum).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Refund {
+    Table,
+    Status,
+}
+
+#[derive(DeriveIden)]
+enum RefundStatus {
+    #[sea_orm(iden = "refund_status")]
+    Enum,
+    Pending,
+    Completed,
+    Failed,
+}
@@ -12,6 +12,8 @@ pub struct Model {
     pub quantity: i32,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub saved_for_later: bool,
+    pub unit_price: Decimal,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
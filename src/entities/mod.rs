@@ -3,8 +3,28 @@
 pub mod prelude;
 
 pub mod cart;
+pub mod cart_abandonment;
 pub mod cart_item;
+pub mod coupon;
+pub mod coupon_category;
+pub mod coupon_product;
+pub mod coupon_redemption;
+pub mod idempotency_key;
+pub mod invoice;
+pub mod invoice_sequence;
 pub mod order;
 pub mod order_item;
 pub mod product;
+pub mod product_recommendation;
+pub mod product_translation;
+pub mod promotion;
+pub mod promotion_category;
+pub mod promotion_product;
+pub mod refund;
+pub mod refund_item;
+pub mod sea_orm_active_enums;
+pub mod shipping_method;
+pub mod tax_class;
 pub mod user;
+pub mod wishlist;
+pub mod wishlist_item;
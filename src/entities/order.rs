@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+use super::sea_orm_active_enums::{FulfilmentStatus, OrderStatus, PaymentStatus};
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -9,15 +10,50 @@ pub struct Model {
     pub id: i32,
     pub user_id: i32,
     pub total_amount: Decimal,
-    pub payment_status: String,
+    pub payment_status: PaymentStatus,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub status: OrderStatus,
+    pub fulfilment_status: FulfilmentStatus,
+    pub cancelled_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub cancellation_reason: Option<String>,
+    pub shipping_method_id: Option<i32>,
+    pub shipping_cost: Decimal,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub shipping_address: Option<Json>,
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub coupon_id: Option<i32>,
+    pub discount_amount: Decimal,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::coupon::Entity",
+        from = "Column::CouponId",
+        to = "super::coupon::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Coupon,
+    #[sea_orm(has_many = "super::coupon_redemption::Entity")]
+    CouponRedemption,
+    #[sea_orm(has_one = "super::invoice::Entity")]
+    Invoice,
     #[sea_orm(has_many = "super::order_item::Entity")]
     OrderItem,
+    #[sea_orm(has_many = "super::refund::Entity")]
+    Refund,
+    #[sea_orm(
+        belongs_to = "super::shipping_method::Entity",
+        from = "Column::ShippingMethodId",
+        to = "super::shipping_method::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    ShippingMethod,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -28,12 +64,42 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::coupon::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Coupon.def()
+    }
+}
+
+impl Related<super::coupon_redemption::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CouponRedemption.def()
+    }
+}
+
+impl Related<super::invoice::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Invoice.def()
+    }
+}
+
 impl Related<super::order_item::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::OrderItem.def()
     }
 }
 
+impl Related<super::refund::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Refund.def()
+    }
+}
+
+impl Related<super::shipping_method::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShippingMethod.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
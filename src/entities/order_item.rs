@@ -13,6 +13,14 @@ pub struct Model {
     pub price: Decimal,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(column_type = "Decimal(Some((5, 2)))")]
+    pub tax_rate: Decimal,
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub gross_amount: Decimal,
+    pub discount_amount: Decimal,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub adjustments: Json,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -33,6 +41,8 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Product,
+    #[sea_orm(has_many = "super::refund_item::Entity")]
+    RefundItem,
 }
 
 impl Related<super::order::Entity> for Entity {
@@ -47,4 +57,10 @@ impl Related<super::product::Entity> for Entity {
     }
 }
 
+impl Related<super::refund_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RefundItem.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
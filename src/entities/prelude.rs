@@ -1,8 +1,27 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
 pub use super::cart::Entity as Cart;
+pub use super::cart_abandonment::Entity as CartAbandonment;
 pub use super::cart_item::Entity as CartItem;
+pub use super::coupon::Entity as Coupon;
+pub use super::coupon_category::Entity as CouponCategory;
+pub use super::coupon_product::Entity as CouponProduct;
+pub use super::coupon_redemption::Entity as CouponRedemption;
+pub use super::idempotency_key::Entity as IdempotencyKey;
+pub use super::invoice::Entity as Invoice;
+pub use super::invoice_sequence::Entity as InvoiceSequence;
 pub use super::order::Entity as Order;
 pub use super::order_item::Entity as OrderItem;
 pub use super::product::Entity as Product;
+pub use super::product_recommendation::Entity as ProductRecommendation;
+pub use super::product_translation::Entity as ProductTranslation;
+pub use super::promotion::Entity as Promotion;
+pub use super::promotion_category::Entity as PromotionCategory;
+pub use super::promotion_product::Entity as PromotionProduct;
+pub use super::refund::Entity as Refund;
+pub use super::refund_item::Entity as RefundItem;
+pub use super::shipping_method::Entity as ShippingMethod;
+pub use super::tax_class::Entity as TaxClass;
 pub use super::user::Entity as User;
+pub use super::wishlist::Entity as Wishlist;
+pub use super::wishlist_item::Entity as WishlistItem;
@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+use super::sea_orm_active_enums::ProductStatus;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -13,14 +14,38 @@ pub struct Model {
     pub price: Decimal,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub category: Option<String>,
+    pub status: ProductStatus,
+    pub available_from: Option<DateTimeWithTimeZone>,
+    pub available_until: Option<DateTimeWithTimeZone>,
+    pub max_quantity: Option<i32>,
+    pub stock: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub tax_class_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::cart_item::Entity")]
     CartItem,
+    #[sea_orm(has_many = "super::coupon_product::Entity")]
+    CouponProduct,
     #[sea_orm(has_many = "super::order_item::Entity")]
     OrderItem,
+    #[sea_orm(has_many = "super::product_translation::Entity")]
+    ProductTranslation,
+    #[sea_orm(has_many = "super::promotion_product::Entity")]
+    PromotionProduct,
+    #[sea_orm(
+        belongs_to = "super::tax_class::Entity",
+        from = "Column::TaxClassId",
+        to = "super::tax_class::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    TaxClass,
+    #[sea_orm(has_many = "super::wishlist_item::Entity")]
+    WishlistItem,
 }
 
 impl Related<super::cart_item::Entity> for Entity {
@@ -29,10 +54,40 @@ impl Related<super::cart_item::Entity> for Entity {
     }
 }
 
+impl Related<super::coupon_product::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CouponProduct.def()
+    }
+}
+
 impl Related<super::order_item::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::OrderItem.def()
     }
 }
 
+impl Related<super::product_translation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductTranslation.def()
+    }
+}
+
+impl Related<super::promotion_product::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PromotionProduct.def()
+    }
+}
+
+impl Related<super::tax_class::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaxClass.def()
+    }
+}
+
+impl Related<super::wishlist_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WishlistItem.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,21 +1,27 @@
+use crate::services::cart_abandonment_service::{self, AbandonmentMetricsQuery};
 use crate::services::cart_service;
-use crate::services::cart_service::{CartItemRequest, CartItemResponse, CartRequest, CartResponse};
+use crate::services::cart_service::{
+    CartDetailResponse, CartItemRequest, CartItemResponse, CartResponse, UpdateCartItemRequest,
+};
+use crate::services::recommendation_service::{self, RelatedProductsQuery};
+use crate::services::saved_cart_service::{self, SaveCartRequest};
+use crate::services::shared_cart_service::{self, ImportSharedCartRequest, ShareCartRequest};
+use crate::services::translation_service;
 use crate::utils::actix_error::ApiError;
+use crate::utils::auth::{AuthenticatedUser, CartCaller};
+use crate::utils::locale::RequestLocale;
 use crate::ApiResponse;
-use actix_web::{delete, post, web, HttpResponse};
+use actix_web::{delete, get, http::header, patch, post, web, HttpResponse};
 use sea_orm::DatabaseConnection;
 use validator::Validate;
 
 #[post("/carts")]
 async fn create_cart_handler(
     db: web::Data<DatabaseConnection>,
-    request: web::Json<CartRequest>,
+    user: AuthenticatedUser,
 ) -> Result<HttpResponse, ApiError> {
-    // Validate input
-    request.validate()?;
-
-    // Create cart using the service
-    let cart_response = cart_service::create_cart(db.get_ref(), request.into_inner()).await?;
+    // Create the active cart of the signed-in user, or return the existing one
+    let cart_response = cart_service::create_cart(db.get_ref(), user.id).await?;
 
     // Return success response
     Ok(HttpResponse::Created().json(ApiResponse::<CartResponse> {
@@ -25,9 +31,143 @@ async fn create_cart_handler(
     }))
 }
 
+#[get("/carts/{cart_id}")]
+async fn get_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
+    cart_id: web::Path<i32>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    let cart = cart_service::get_cart_details(db.get_ref(), &caller, *cart_id, &locale.0).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse::<CartDetailResponse> {
+            status: "success".to_string(),
+            message: "Cart fetched successfully".to_string(),
+            data: Some(cart),
+        }))
+}
+
+#[get("/me/cart")]
+async fn get_my_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    let cart = cart_service::get_cart_details_for_user(db.get_ref(), user.id, &locale.0).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse::<CartDetailResponse> {
+            status: "success".to_string(),
+            message: "Cart fetched successfully".to_string(),
+            data: Some(cart),
+        }))
+}
+
+#[post("/me/cart/items")]
+async fn add_item_to_my_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<CartItemRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    // The active cart is created on the first add
+    let cart_item_response =
+        cart_service::add_item_to_active_cart(db.get_ref(), user.id, request.into_inner()).await?;
+
+    Ok(
+        HttpResponse::Created().json(ApiResponse::<CartItemResponse> {
+            status: "success".to_string(),
+            message: "Item added to cart successfully".to_string(),
+            data: Some(cart_item_response),
+        }),
+    )
+}
+
+#[post("/me/carts/saved")]
+async fn save_my_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<SaveCartRequest>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let cart = saved_cart_service::save_active_cart(
+        db.get_ref(),
+        user.id,
+        request.into_inner(),
+        &locale.0,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse::<CartDetailResponse> {
+            status: "success".to_string(),
+            message: "Cart saved successfully".to_string(),
+            data: Some(cart),
+        }))
+}
+
+#[get("/me/carts/saved")]
+async fn get_my_saved_carts_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    let carts = saved_cart_service::get_saved_carts(db.get_ref(), user.id, &locale.0).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse::<Vec<CartDetailResponse>> {
+            status: "success".to_string(),
+            message: "Saved carts fetched successfully".to_string(),
+            data: Some(carts),
+        }))
+}
+
+#[post("/me/carts/saved/{cart_id}/restore")]
+async fn restore_my_saved_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    cart_id: web::Path<i32>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    let cart =
+        saved_cart_service::restore_saved_cart(db.get_ref(), user.id, *cart_id, &locale.0).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse::<CartDetailResponse> {
+            status: "success".to_string(),
+            message: "Saved cart restored successfully".to_string(),
+            data: Some(cart),
+        }))
+}
+
+#[delete("/me/carts/saved/{cart_id}")]
+async fn delete_my_saved_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    cart_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    saved_cart_service::delete_saved_cart(db.get_ref(), user.id, *cart_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<String> {
+        status: "success".to_string(),
+        message: "Saved cart deleted successfully".to_string(),
+        data: None,
+    }))
+}
+
 #[post("/carts/{cart_id}/items")]
 async fn add_item_to_cart_handler(
     db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
     cart_id: web::Path<i32>,
     request: web::Json<CartItemRequest>,
 ) -> Result<HttpResponse, ApiError> {
@@ -36,7 +176,8 @@ async fn add_item_to_cart_handler(
 
     // Add item to cart using the service
     let cart_item_response =
-        cart_service::add_item_to_cart(db.get_ref(), *cart_id, request.into_inner()).await?;
+        cart_service::add_item_to_owned_cart(db.get_ref(), &caller, *cart_id, request.into_inner())
+            .await?;
 
     // Return success response
     Ok(
@@ -48,13 +189,78 @@ async fn add_item_to_cart_handler(
     )
 }
 
+#[patch("/carts/items/{cart_item_id}")]
+async fn update_cart_item_handler(
+    db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
+    cart_item_id: web::Path<i32>,
+    request: web::Json<UpdateCartItemRequest>,
+) -> Result<HttpResponse, ApiError> {
+    // Validate input
+    request.validate()?;
+
+    // Update (or remove) the cart item using the service
+    let cart_item_response = cart_service::update_cart_item_quantity(
+        db.get_ref(),
+        &caller,
+        *cart_item_id,
+        request.into_inner(),
+    )
+    .await?;
+
+    let message = match cart_item_response {
+        Some(_) => "Cart item updated successfully",
+        None => "Item removed from cart successfully",
+    };
+
+    // Return success response
+    Ok(HttpResponse::Ok().json(ApiResponse::<CartItemResponse> {
+        status: "success".to_string(),
+        message: message.to_string(),
+        data: cart_item_response,
+    }))
+}
+
+#[post("/carts/items/{cart_item_id}/save-for-later")]
+async fn save_cart_item_for_later_handler(
+    db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
+    cart_item_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let cart_item_response =
+        cart_service::set_saved_for_later(db.get_ref(), &caller, *cart_item_id, true).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<CartItemResponse> {
+        status: "success".to_string(),
+        message: "Item saved for later".to_string(),
+        data: Some(cart_item_response),
+    }))
+}
+
+#[post("/carts/items/{cart_item_id}/move-to-cart")]
+async fn move_cart_item_to_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
+    cart_item_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let cart_item_response =
+        cart_service::set_saved_for_later(db.get_ref(), &caller, *cart_item_id, false).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<CartItemResponse> {
+        status: "success".to_string(),
+        message: "Item moved back to cart".to_string(),
+        data: Some(cart_item_response),
+    }))
+}
+
 #[delete("/carts/items/{cart_item_id}")]
 async fn remove_item_from_cart_handler(
     db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
     cart_item_id: web::Path<i32>,
 ) -> Result<HttpResponse, ApiError> {
     // Remove item from cart using the service
-    cart_service::remove_item_from_cart(db.get_ref(), *cart_item_id).await?;
+    cart_service::remove_item_from_cart(db.get_ref(), &caller, *cart_item_id).await?;
 
     // Return success response
     Ok(HttpResponse::Ok().json(ApiResponse::<String> {
@@ -67,10 +273,11 @@ async fn remove_item_from_cart_handler(
 #[delete("/carts/{cart_id}/clear")]
 async fn clear_cart_handler(
     db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
     cart_id: web::Path<i32>,
 ) -> Result<HttpResponse, ApiError> {
     // Clear all items from cart using the service
-    cart_service::clear_cart(db.get_ref(), *cart_id).await?;
+    cart_service::clear_cart(db.get_ref(), &caller, *cart_id).await?;
 
     // Return success response
     Ok(HttpResponse::Ok().json(ApiResponse::<String> {
@@ -79,3 +286,106 @@ async fn clear_cart_handler(
         data: None,
     }))
 }
+
+#[get("/carts/{cart_id}/related")]
+async fn get_cart_recommendations_handler(
+    db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
+    cart_id: web::Path<i32>,
+    query: web::Query<RelatedProductsQuery>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+    cart_service::find_visible_cart(db.get_ref(), &caller, *cart_id).await?;
+
+    let mut related_products =
+        recommendation_service::get_cart_recommendations(db.get_ref(), *cart_id, query.limit())
+            .await?;
+    translation_service::localize_products(
+        db.get_ref(),
+        related_products
+            .iter_mut()
+            .map(|related| &mut related.product),
+        &locale.0,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse {
+            status: "success".to_string(),
+            message: "Cart recommendations fetched successfully".to_string(),
+            data: Some(related_products),
+        }))
+}
+
+#[post("/carts/{cart_id}/share")]
+async fn share_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    cart_id: web::Path<i32>,
+    request: web::Json<ShareCartRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let shared_cart =
+        shared_cart_service::share_cart(db.get_ref(), &user, *cart_id, request.into_inner())
+            .await?;
+
+    Ok(HttpResponse::Created().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Cart share link created successfully".to_string(),
+        data: Some(shared_cart),
+    }))
+}
+
+#[post("/me/cart/import")]
+async fn import_shared_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<ImportSharedCartRequest>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let imported = shared_cart_service::import_shared_cart(
+        db.get_ref(),
+        user.id,
+        request.into_inner(),
+        &locale.0,
+    )
+    .await?;
+
+    let message = if imported.unavailable_items.is_empty() {
+        "Shared cart imported successfully"
+    } else {
+        "Shared cart imported; some items are no longer available"
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse {
+            status: "success".to_string(),
+            message: message.to_string(),
+            data: Some(imported),
+        }))
+}
+
+#[get("/admin/metrics/cart-abandonment")]
+async fn get_cart_abandonment_metrics_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    query: web::Query<AbandonmentMetricsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    query.validate()?;
+
+    let metrics =
+        cart_abandonment_service::get_abandonment_metrics(db.get_ref(), query.days()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Cart abandonment metrics fetched successfully".to_string(),
+        data: Some(metrics),
+    }))
+}
@@ -0,0 +1,100 @@
+use crate::services::coupon_service::{self, ApplyCouponRequest, CouponRequest};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::{AuthenticatedUser, CartCaller};
+use crate::ApiResponse;
+use actix_web::{delete, get, post, put, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use validator::Validate;
+
+/// Handler to apply a discount code to a cart
+#[post("/carts/{cart_id}/coupon")]
+async fn apply_coupon_handler(
+    db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
+    cart_id: web::Path<i32>,
+    request: web::Json<ApplyCouponRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let applied =
+        coupon_service::apply_coupon(db.get_ref(), &caller, *cart_id, request.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Discount code applied successfully".to_string(),
+        data: Some(applied),
+    }))
+}
+
+/// Handler to take the discount code off a cart
+#[delete("/carts/{cart_id}/coupon")]
+async fn remove_coupon_handler(
+    db: web::Data<DatabaseConnection>,
+    caller: CartCaller,
+    cart_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    coupon_service::remove_coupon(db.get_ref(), &caller, *cart_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<String> {
+        status: "success".to_string(),
+        message: "Discount code removed successfully".to_string(),
+        data: None,
+    }))
+}
+
+/// Handler to list every coupon (staff only)
+#[get("/admin/coupons")]
+async fn get_all_coupons_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let coupons = coupon_service::get_all_coupons(db.get_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Coupons fetched successfully".to_string(),
+        data: Some(coupons),
+    }))
+}
+
+/// Handler to add a coupon (staff only)
+#[post("/coupons")]
+async fn create_coupon_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<CouponRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let coupon = coupon_service::create_coupon(db.get_ref(), request.into_inner()).await?;
+
+    Ok(HttpResponse::Created().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Coupon created successfully".to_string(),
+        data: Some(coupon),
+    }))
+}
+
+/// Handler to change a coupon (staff only)
+#[put("/coupons/{coupon_id}")]
+async fn update_coupon_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    coupon_id: web::Path<i32>,
+    request: web::Json<CouponRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let coupon =
+        coupon_service::update_coupon(db.get_ref(), *coupon_id, request.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Coupon updated successfully".to_string(),
+        data: Some(coupon),
+    }))
+}
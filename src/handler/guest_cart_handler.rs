@@ -0,0 +1,96 @@
+use crate::services::cart_service::{
+    CartDetailResponse, CartItemRequest, CartItemResponse, UpdateCartItemRequest,
+};
+use crate::services::guest_cart_service;
+use crate::services::guest_cart_service::GuestCartResponse;
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::GuestCartToken;
+use crate::utils::locale::RequestLocale;
+use crate::ApiResponse;
+use actix_web::{get, patch, post, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use validator::Validate;
+
+#[post("/guest/carts")]
+async fn create_guest_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    let guest_cart = guest_cart_service::create_guest_cart(db.get_ref(), &locale.0).await?;
+
+    Ok(
+        HttpResponse::Created().json(ApiResponse::<GuestCartResponse> {
+            status: "success".to_string(),
+            message: "Guest cart created successfully".to_string(),
+            data: Some(guest_cart),
+        }),
+    )
+}
+
+#[get("/guest/cart")]
+async fn get_guest_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    token: GuestCartToken,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    let cart = guest_cart_service::get_guest_cart(db.get_ref(), token.cart_id, &locale.0).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<CartDetailResponse> {
+        status: "success".to_string(),
+        message: "Cart fetched successfully".to_string(),
+        data: Some(cart),
+    }))
+}
+
+#[post("/guest/cart/items")]
+async fn add_item_to_guest_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    token: GuestCartToken,
+    request: web::Json<CartItemRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let cart_item_response = guest_cart_service::add_item_to_guest_cart(
+        db.get_ref(),
+        token.cart_id,
+        request.into_inner(),
+    )
+    .await?;
+
+    Ok(
+        HttpResponse::Created().json(ApiResponse::<CartItemResponse> {
+            status: "success".to_string(),
+            message: "Item added to cart successfully".to_string(),
+            data: Some(cart_item_response),
+        }),
+    )
+}
+
+#[patch("/guest/cart/items/{cart_item_id}")]
+async fn update_guest_cart_item_handler(
+    db: web::Data<DatabaseConnection>,
+    token: GuestCartToken,
+    cart_item_id: web::Path<i32>,
+    request: web::Json<UpdateCartItemRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let cart_item_response = guest_cart_service::update_guest_cart_item(
+        db.get_ref(),
+        token.cart_id,
+        *cart_item_id,
+        request.into_inner(),
+    )
+    .await?;
+
+    let message = match cart_item_response {
+        Some(_) => "Cart item updated successfully",
+        None => "Item removed from cart successfully",
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<CartItemResponse> {
+        status: "success".to_string(),
+        message: message.to_string(),
+        data: cart_item_response,
+    }))
+}
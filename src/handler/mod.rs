@@ -1,4 +1,10 @@
 pub mod cart_handler;
+pub mod coupon_handler;
+pub mod guest_cart_handler;
 pub mod order_handler;
 pub mod product_handler;
+pub mod promotion_handler;
+pub mod shipping_handler;
+pub mod tax_handler;
 pub mod user_handler;
+pub mod wishlist_handler;
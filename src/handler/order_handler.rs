@@ -1,86 +1,139 @@
+use crate::services::idempotency_service;
+use crate::services::invoice_service;
 use crate::services::order_service::{
-    CreateOrderRequest, OrderItemRequest, OrderService, UpdatePaymentStatusRequest,
+    CancelOrderRequest, CheckoutOutcome, OrderListQuery, OrderSearchQuery, OrderService,
+    UpdateOrderStatusRequest, UpdatePaymentStatusRequest,
 };
+use crate::services::pricing_service::{self, CheckoutRequest};
+use crate::services::refund_service::{self, CreateRefundRequest};
 use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::idempotency::{run_idempotent, IdempotencyKey};
+use crate::utils::payment_provider::PaymentProvider;
 use crate::ApiResponse;
+use actix_web::http::{header, StatusCode};
 use actix_web::{delete, get, post, put, web, HttpResponse};
 use sea_orm::DatabaseConnection;
+use serde::Serialize;
 use validator::Validate;
 
+/// Handler to place an order from a cart.
+///
+/// Retries carrying the same `Idempotency-Key` header replay the first response.
 #[post("/orders")]
 async fn create_order_handler(
     db: web::Data<DatabaseConnection>,
-    payload: web::Json<CreateOrderPayload>, // Expect JSON object
+    payment_provider: web::Data<dyn PaymentProvider>,
+    user: AuthenticatedUser,
+    idempotency_key: IdempotencyKey,
+    payload: web::Json<CheckoutRequest>, // Expect JSON object
 ) -> Result<HttpResponse, ApiError> {
-    let user_id = payload.user_id;
-
-    // Fetch all carts for the user
-    let carts = OrderService::get_all_carts_for_user(db.get_ref(), user_id).await?;
-
-    if carts.is_empty() {
-        return Err(ApiError::ValidationError(
-            "No carts found for the user".to_string(),
-        ));
-    }
-
-    let mut all_cart_items = Vec::new();
-
-    // Collect items from all carts
-    for cart in carts {
-        let cart_items = OrderService::get_cart_items_for_user(db.get_ref(), cart.id).await?;
-
-        all_cart_items.extend(cart_items);
-    }
-
-    if all_cart_items.is_empty() {
-        return Err(ApiError::ValidationError(
-            "No items in carts to create an order".to_string(),
-        ));
-    }
-
-    // Map cart items to order items with prices
-    let mut order_items = Vec::new();
-    for item in all_cart_items {
-        let price = OrderService::get_product_price(db.get_ref(), item.product_id).await?;
-
-        order_items.push(OrderItemRequest {
-            product_id: item.product_id,
-            quantity: item.quantity,
-            price,
-        });
-    }
-
-    // Prepare the order creation request
-    let create_order_request = CreateOrderRequest {
-        user_id,
-        items: order_items,
-    };
-
-    // Validate the order creation request
-    create_order_request
-        .validate()
-        .map_err(|e| ApiError::ValidationError(e.to_string()))?;
+    payload.validate()?;
+    let fingerprint = idempotency_service::fingerprint(&*payload);
 
-    // Create the order using the service
-    let order = OrderService::create_order(db.get_ref(), create_order_request).await?;
+    run_idempotent(
+        db.get_ref(),
+        idempotency_key,
+        "create_order",
+        user.id,
+        fingerprint,
+        || async {
+            // Price, place and clear the cart in one transaction
+            match OrderService::checkout(
+                db.get_ref(),
+                payment_provider.get_ref(),
+                user.id,
+                &payload,
+            )
+            .await?
+            {
+                CheckoutOutcome::Placed(order) => Ok((
+                    StatusCode::CREATED,
+                    to_json(ApiResponse {
+                        status: "success".to_string(),
+                        message: "Order created successfully".to_string(),
+                        data: Some(order),
+                    })?,
+                )),
+                CheckoutOutcome::PricesChanged(price_changes) => Ok((
+                    StatusCode::CONFLICT,
+                    to_json(ApiResponse {
+                        status: "error".to_string(),
+                        message: "Prices changed since the items were added to the cart; \
+                                  resend their current prices in confirmed_prices to accept them"
+                            .to_string(),
+                        data: Some(price_changes),
+                    })?,
+                )),
+            }
+        },
+    )
+    .await
+}
 
-    // Clear all carts after the order is created
-    OrderService::clear_all_carts(db.get_ref(), user_id).await?;
+/// Handler to preview the totals of a checkout without placing the order
+#[post("/checkout/preview")]
+async fn checkout_preview_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    payload: web::Json<CheckoutRequest>,
+) -> Result<HttpResponse, ApiError> {
+    payload.validate()?;
+    let quote = pricing_service::quote_checkout(db.get_ref(), user.id, &payload).await?;
 
-    Ok(HttpResponse::Created().json(ApiResponse {
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Checkout preview computed successfully".to_string(),
+        data: Some(quote),
+    }))
+}
+
+/// Handler to list the caller's orders
+#[get("/me/orders")]
+async fn get_my_orders_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    query: web::Query<OrderListQuery>,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let orders =
+        OrderService::list_orders_for_user(db.get_ref(), user.id, query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
         status: "success".to_string(),
-        message: "Order created successfully from all carts".to_string(),
-        data: Some(order),
+        message: "Orders fetched successfully".to_string(),
+        data: Some(orders),
     }))
 }
 
-/// Handler to retrieve an order with its items
+/// Handler to search the orders of all customers (staff only)
+#[get("/admin/orders")]
+async fn search_orders_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    query: web::Query<OrderSearchQuery>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    query.validate()?;
+
+    let orders = OrderService::search_orders(db.get_ref(), query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Orders fetched successfully".to_string(),
+        data: Some(orders),
+    }))
+}
+
+/// Handler to retrieve an order with its items (staff or the customer who placed it)
 #[get("/orders/{order_id}")]
 async fn get_order_with_items_handler(
     db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
     order_id: web::Path<i32>,
 ) -> Result<HttpResponse, ApiError> {
-    let (order, items) = OrderService::get_order_with_items(db.get_ref(), *order_id).await?;
+    let (order, items) = OrderService::get_order_with_items(db.get_ref(), &user, *order_id).await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse {
         status: "success".to_string(),
@@ -89,46 +142,214 @@ async fn get_order_with_items_handler(
     }))
 }
 
-/// Handler to update the payment status of an order
+/// Handler to update the payment status of an order (staff only; PromptPay
+/// transfers are confirmed by hand).
+///
+/// Retries carrying the same `Idempotency-Key` header replay the first response.
 #[put("/orders/{order_id}/payment-status")]
 async fn update_payment_status_handler(
     db: web::Data<DatabaseConnection>,
+    payment_provider: web::Data<dyn PaymentProvider>,
+    user: AuthenticatedUser,
+    idempotency_key: IdempotencyKey,
     order_id: web::Path<i32>,
     request: web::Json<UpdatePaymentStatusRequest>,
 ) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
     // Validate input
     request
         .validate()
         .map_err(|e| ApiError::ValidationError(e.to_string()))?;
 
-    let updated_order =
-        OrderService::update_payment_status(db.get_ref(), *order_id, request.into_inner()).await?;
+    let order_id = order_id.into_inner();
+    let request = request.into_inner();
+    let fingerprint = idempotency_service::fingerprint(&(order_id, &request));
+
+    run_idempotent(
+        db.get_ref(),
+        idempotency_key,
+        "update_payment_status",
+        user.id,
+        fingerprint,
+        || async {
+            let updated_order = OrderService::update_payment_status(
+                db.get_ref(),
+                payment_provider.get_ref(),
+                order_id,
+                request,
+            )
+            .await?;
+
+            Ok((
+                StatusCode::OK,
+                to_json(ApiResponse {
+                    status: "success".to_string(),
+                    message: "Payment status updated successfully".to_string(),
+                    data: Some(updated_order),
+                })?,
+            ))
+        },
+    )
+    .await
+}
+
+/// Handler to move an order through its lifecycle (staff only)
+#[put("/orders/{order_id}/status")]
+async fn update_order_status_handler(
+    db: web::Data<DatabaseConnection>,
+    payment_provider: web::Data<dyn PaymentProvider>,
+    user: AuthenticatedUser,
+    order_id: web::Path<i32>,
+    request: web::Json<UpdateOrderStatusRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let updated_order = OrderService::transition_order_status(
+        db.get_ref(),
+        payment_provider.get_ref(),
+        *order_id,
+        request.status,
+    )
+    .await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse {
         status: "success".to_string(),
-        message: "Payment status updated successfully".to_string(),
+        message: "Order status updated successfully".to_string(),
         data: Some(updated_order),
     }))
 }
 
-/// Handler to delete an order and its items
-#[delete("/orders/{order_id}")]
-async fn delete_order_handler(
+/// Handler to cancel an order; customers while it is unpaid, staff with a reason
+#[post("/orders/{order_id}/cancel")]
+async fn cancel_order_handler(
+    db: web::Data<DatabaseConnection>,
+    payment_provider: web::Data<dyn PaymentProvider>,
+    user: AuthenticatedUser,
+    order_id: web::Path<i32>,
+    request: web::Json<CancelOrderRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let cancelled_order = OrderService::cancel_order(
+        db.get_ref(),
+        payment_provider.get_ref(),
+        &user,
+        *order_id,
+        request.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Order cancelled successfully".to_string(),
+        data: Some(cancelled_order),
+    }))
+}
+
+/// Handler to permanently delete an order and its items (admin only, for test data)
+#[delete("/admin/orders/{order_id}")]
+async fn purge_order_handler(
     db: web::Data<DatabaseConnection>,
+    payment_provider: web::Data<dyn PaymentProvider>,
+    user: AuthenticatedUser,
     order_id: web::Path<i32>,
 ) -> Result<HttpResponse, ApiError> {
-    OrderService::delete_order(db.get_ref(), *order_id).await?;
+    user.require_admin()?;
+
+    OrderService::purge_order(db.get_ref(), payment_provider.get_ref(), *order_id).await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse::<String> {
         status: "success".to_string(),
-        message: "Order deleted successfully".to_string(),
+        message: "Order purged successfully".to_string(),
         data: None,
     }))
 }
 
-use serde::Deserialize;
+/// Handler to return money to the customer of an order (staff only)
+#[post("/orders/{order_id}/refunds")]
+async fn create_refund_handler(
+    db: web::Data<DatabaseConnection>,
+    payment_provider: web::Data<dyn PaymentProvider>,
+    user: AuthenticatedUser,
+    order_id: web::Path<i32>,
+    request: web::Json<CreateRefundRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let refunds = refund_service::create_refund(
+        db.get_ref(),
+        payment_provider.get_ref(),
+        &user,
+        *order_id,
+        request.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Refund recorded successfully".to_string(),
+        data: Some(refunds),
+    }))
+}
+
+/// Handler to list the refunds of an order (staff or the customer who placed it)
+#[get("/orders/{order_id}/refunds")]
+async fn get_order_refunds_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    order_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let refunds = refund_service::get_order_refunds(db.get_ref(), &user, *order_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Refunds retrieved successfully".to_string(),
+        data: Some(refunds),
+    }))
+}
+
+/// Handler to issue the invoice of an unpaid order (staff or the customer who placed it).
+///
+/// Paid orders are invoiced when the payment is recorded; issuing twice
+/// returns the invoice already issued.
+#[post("/orders/{order_id}/invoice")]
+async fn issue_order_invoice_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    order_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let invoice = invoice_service::issue_order_invoice(db.get_ref(), &user, *order_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Invoice issued successfully".to_string(),
+        data: Some(invoice),
+    }))
+}
+
+/// Handler to download the issued invoice of an order as a PDF (staff or the
+/// customer who placed it); it is a receipt once the order is paid.
+#[get("/orders/{order_id}/invoice.pdf")]
+async fn get_order_invoice_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    order_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let invoice = invoice_service::get_order_invoice(db.get_ref(), &user, *order_id).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/pdf")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}.pdf\"", invoice.invoice_number),
+        ))
+        .body(invoice.bytes))
+}
 
-#[derive(Deserialize)]
-struct CreateOrderPayload {
-    user_id: i32,
+/// Serialize a response body so that it can be stored for idempotent replays
+fn to_json(response: impl Serialize) -> Result<serde_json::Value, ApiError> {
+    serde_json::to_value(response)
+        .map_err(|_| ApiError::InternalServerError("Failed to serialize response".to_string()))
 }
@@ -1,7 +1,11 @@
-use crate::models::product::ProductRequest;
+use crate::models::product::{ProductRequest, ProductTranslationRequest};
 use crate::services::product_service; // Import the service where product logic resides
+use crate::services::recommendation_service::{self, RelatedProductsQuery};
+use crate::services::translation_service;
 use crate::utils::actix_error::ApiError;
-use actix_web::{delete, get, post, put, web, HttpResponse};
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::locale::RequestLocale;
+use actix_web::{delete, get, http::header, post, put, web, HttpResponse};
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -13,19 +17,13 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
 }
 
-/// Standard error response format
-#[derive(Serialize, Deserialize)]
-pub struct ErrorResponse {
-    pub status: String,
-    pub message: String,
-    pub error: Option<String>,
-}
-
 #[post("/products")]
 pub async fn create_product(
     db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
     request: web::Json<ProductRequest>,
 ) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
     request.validate()?;
 
     let product_response =
@@ -39,36 +37,80 @@ pub async fn create_product(
 }
 
 #[get("/products")]
-pub async fn get_all_products(db: web::Data<DatabaseConnection>) -> Result<HttpResponse, ApiError> {
-    let product_responses = product_service::get_all_products(db.get_ref()).await?;
+pub async fn get_all_products(
+    db: web::Data<DatabaseConnection>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    let mut product_responses = product_service::get_all_products(db.get_ref()).await?;
+    translation_service::localize_products(db.get_ref(), &mut product_responses, &locale.0).await?;
 
-    Ok(HttpResponse::Ok().json(ApiResponse {
-        status: "success".to_string(),
-        message: "Products fetched successfully".to_string(),
-        data: Some(product_responses),
-    }))
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse {
+            status: "success".to_string(),
+            message: "Products fetched successfully".to_string(),
+            data: Some(product_responses),
+        }))
 }
 
 #[get("/products/{id}")]
 pub async fn get_product(
     db: web::Data<DatabaseConnection>,
     product_id: web::Path<i32>,
+    locale: RequestLocale,
 ) -> Result<HttpResponse, ApiError> {
-    let product_response = product_service::get_product_by_id(db.get_ref(), *product_id).await?;
+    let mut product_response =
+        product_service::get_product_by_id(db.get_ref(), *product_id).await?;
+    translation_service::localize_products(db.get_ref(), [&mut product_response], &locale.0)
+        .await?;
 
-    Ok(HttpResponse::Ok().json(ApiResponse {
-        status: "success".to_string(),
-        message: "Product fetched successfully".to_string(),
-        data: Some(product_response),
-    }))
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse {
+            status: "success".to_string(),
+            message: "Product fetched successfully".to_string(),
+            data: Some(product_response),
+        }))
+}
+
+#[get("/products/{id}/related")]
+pub async fn get_related_products(
+    db: web::Data<DatabaseConnection>,
+    product_id: web::Path<i32>,
+    query: web::Query<RelatedProductsQuery>,
+    locale: RequestLocale,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let mut related_products =
+        recommendation_service::get_related_products(db.get_ref(), *product_id, query.limit())
+            .await?;
+    translation_service::localize_products(
+        db.get_ref(),
+        related_products
+            .iter_mut()
+            .map(|related| &mut related.product),
+        &locale.0,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, locale.0))
+        .json(ApiResponse {
+            status: "success".to_string(),
+            message: "Related products fetched successfully".to_string(),
+            data: Some(related_products),
+        }))
 }
 
 #[put("/products/{id}")]
 pub async fn update_product(
     db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
     product_id: web::Path<i32>,
     request: web::Json<ProductRequest>,
 ) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
     request.validate()?;
 
     let product_response =
@@ -84,8 +126,11 @@ pub async fn update_product(
 #[delete("/products/{id}")]
 pub async fn delete_product(
     db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
     product_id: web::Path<i32>,
 ) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
     product_service::delete_product(db.get_ref(), *product_id).await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse::<()> {
@@ -94,3 +139,99 @@ pub async fn delete_product(
         data: None,
     }))
 }
+
+/// Staff listing that includes drafts, unlisted and scheduled products
+#[get("/admin/products")]
+pub async fn get_all_products_admin(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let product_responses = product_service::get_all_products_for_staff(db.get_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Products fetched successfully".to_string(),
+        data: Some(product_responses),
+    }))
+}
+
+#[get("/admin/products/{id}")]
+pub async fn get_product_admin(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    product_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let product_response =
+        product_service::get_product_by_id_for_staff(db.get_ref(), *product_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Product fetched successfully".to_string(),
+        data: Some(product_response),
+    }))
+}
+
+#[get("/admin/products/{id}/translations")]
+pub async fn get_product_translations(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    product_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let translations = translation_service::get_translations(db.get_ref(), *product_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Translations fetched successfully".to_string(),
+        data: Some(translations),
+    }))
+}
+
+#[put("/admin/products/{id}/translations/{locale}")]
+pub async fn upsert_product_translation(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    path: web::Path<(i32, String)>,
+    request: web::Json<ProductTranslationRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+    let (product_id, locale) = path.into_inner();
+
+    let translation = translation_service::upsert_translation(
+        db.get_ref(),
+        product_id,
+        &locale,
+        request.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Translation saved successfully".to_string(),
+        data: Some(translation),
+    }))
+}
+
+#[delete("/admin/products/{id}/translations/{locale}")]
+pub async fn delete_product_translation(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    path: web::Path<(i32, String)>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    let (product_id, locale) = path.into_inner();
+
+    translation_service::delete_translation(db.get_ref(), product_id, &locale).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<()> {
+        status: "success".to_string(),
+        message: "Translation deleted successfully".to_string(),
+        data: None,
+    }))
+}
@@ -0,0 +1,65 @@
+use crate::services::promotion_service::{self, PromotionRequest};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::ApiResponse;
+use actix_web::{get, post, put, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use validator::Validate;
+
+/// Handler to list every promotion, highest priority first (staff only)
+#[get("/admin/promotions")]
+async fn get_all_promotions_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let promotions = promotion_service::get_all_promotions(db.get_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Promotions fetched successfully".to_string(),
+        data: Some(promotions),
+    }))
+}
+
+/// Handler to add a promotion (staff only)
+#[post("/promotions")]
+async fn create_promotion_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<PromotionRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let promotion = promotion_service::create_promotion(db.get_ref(), request.into_inner()).await?;
+
+    Ok(HttpResponse::Created().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Promotion created successfully".to_string(),
+        data: Some(promotion),
+    }))
+}
+
+/// Handler to change a promotion (staff only)
+#[put("/promotions/{promotion_id}")]
+async fn update_promotion_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    promotion_id: web::Path<i32>,
+    request: web::Json<PromotionRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let promotion =
+        promotion_service::update_promotion(db.get_ref(), *promotion_id, request.into_inner())
+            .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Promotion updated successfully".to_string(),
+        data: Some(promotion),
+    }))
+}
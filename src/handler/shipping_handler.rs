@@ -0,0 +1,92 @@
+use crate::services::shipping_service::{
+    self, AvailableShippingMethodsQuery, ShippingMethodRequest,
+};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::ApiResponse;
+use actix_web::{get, post, put, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use validator::Validate;
+
+/// Handler to list the shipping methods available for an address, priced for
+/// the signed-in user's cart
+#[get("/shipping-methods")]
+async fn get_available_shipping_methods_handler(
+    db: web::Data<DatabaseConnection>,
+    user: Option<AuthenticatedUser>,
+    query: web::Query<AvailableShippingMethodsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let methods = shipping_service::get_available_shipping_methods(
+        db.get_ref(),
+        user.map(|user| user.id),
+        query.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Shipping methods fetched successfully".to_string(),
+        data: Some(methods),
+    }))
+}
+
+/// Handler to list every shipping method, including inactive ones (staff only)
+#[get("/admin/shipping-methods")]
+async fn get_all_shipping_methods_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let methods = shipping_service::get_all_shipping_methods(db.get_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Shipping methods fetched successfully".to_string(),
+        data: Some(methods),
+    }))
+}
+
+/// Handler to add a shipping method (staff only)
+#[post("/shipping-methods")]
+async fn create_shipping_method_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<ShippingMethodRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let method =
+        shipping_service::create_shipping_method(db.get_ref(), request.into_inner()).await?;
+
+    Ok(HttpResponse::Created().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Shipping method created successfully".to_string(),
+        data: Some(method),
+    }))
+}
+
+/// Handler to change or deactivate a shipping method (staff only)
+#[put("/shipping-methods/{method_id}")]
+async fn update_shipping_method_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    method_id: web::Path<i32>,
+    request: web::Json<ShippingMethodRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let method =
+        shipping_service::update_shipping_method(db.get_ref(), *method_id, request.into_inner())
+            .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Shipping method updated successfully".to_string(),
+        data: Some(method),
+    }))
+}
@@ -0,0 +1,64 @@
+use crate::services::tax_service::{self, TaxClassRequest};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::ApiResponse;
+use actix_web::{get, post, put, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use validator::Validate;
+
+/// Handler to list the tax classes (staff only)
+#[get("/admin/tax-classes")]
+async fn get_all_tax_classes_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+
+    let classes = tax_service::get_all_tax_classes(db.get_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Tax classes fetched successfully".to_string(),
+        data: Some(classes),
+    }))
+}
+
+/// Handler to add a tax class (staff only)
+#[post("/tax-classes")]
+async fn create_tax_class_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<TaxClassRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let class = tax_service::create_tax_class(db.get_ref(), request.into_inner()).await?;
+
+    Ok(HttpResponse::Created().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Tax class created successfully".to_string(),
+        data: Some(class),
+    }))
+}
+
+/// Handler to change the rate of a tax class (staff only)
+#[put("/tax-classes/{tax_class_id}")]
+async fn update_tax_class_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    tax_class_id: web::Path<i32>,
+    request: web::Json<TaxClassRequest>,
+) -> Result<HttpResponse, ApiError> {
+    user.require_staff()?;
+    request.validate()?;
+
+    let class =
+        tax_service::update_tax_class(db.get_ref(), *tax_class_id, request.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Tax class updated successfully".to_string(),
+        data: Some(class),
+    }))
+}
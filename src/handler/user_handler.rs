@@ -1,6 +1,7 @@
 use crate::models::user::{UserLoginRequest, UserRegisterRequest};
-use crate::services::user_service;
+use crate::services::{guest_cart_service, user_service};
 use crate::utils::actix_error::ApiError;
+use crate::utils::auth::GuestCartToken;
 use crate::ApiResponse;
 use actix_web::{get, post, web, HttpResponse};
 use sea_orm::DatabaseConnection;
@@ -9,11 +10,23 @@ use validator::Validate;
 #[post("/register")]
 async fn register(
     db: web::Data<DatabaseConnection>,
+    guest_cart: Option<GuestCartToken>,
     request: web::Json<UserRegisterRequest>,
 ) -> Result<HttpResponse, ApiError> {
     request.validate()?;
 
-    let user_response = user_service::register_user(db.get_ref(), request.into_inner()).await?;
+    let mut user_response = user_service::register_user(db.get_ref(), request.into_inner()).await?;
+
+    // Carry the visitor's guest cart over to the new account
+    if let Some(guest_cart) = guest_cart {
+        user_response.cart_id = guest_cart_service::merge_guest_cart(
+            db.get_ref(),
+            guest_cart.cart_id,
+            user_response.id,
+        )
+        .await?;
+    }
+
     Ok(HttpResponse::Created().json(ApiResponse {
         status: "success".to_string(),
         message: "registration successfully".to_string(),
@@ -37,14 +50,25 @@ async fn get_user(
 #[post("/login")]
 async fn login(
     db: web::Data<DatabaseConnection>,
+    guest_cart: Option<GuestCartToken>,
     request: web::Json<UserLoginRequest>,
 ) -> Result<HttpResponse, ApiError> {
     // Validate the input
     request.validate()?;
 
-    let user_response =
+    let mut user_response =
         user_service::authenticate_user(db.get_ref(), &request.email, &request.password).await?;
 
+    // Merge the guest cart the visitor filled before logging in
+    if let Some(guest_cart) = guest_cart {
+        user_response.cart_id = guest_cart_service::merge_guest_cart(
+            db.get_ref(),
+            guest_cart.cart_id,
+            user_response.id,
+        )
+        .await?;
+    }
+
     Ok(HttpResponse::Created().json(ApiResponse {
         status: "success".to_string(),
         message: "login successfully".to_string(),
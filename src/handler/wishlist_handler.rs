@@ -0,0 +1,194 @@
+use crate::services::cart_service::CartItemResponse;
+use crate::services::wishlist_service;
+use crate::services::wishlist_service::{
+    MoveToCartRequest, WishlistItemRequest, WishlistRequest, WishlistResponse,
+};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::ApiResponse;
+use actix_web::{delete, get, post, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use validator::Validate;
+
+#[post("/wishlists")]
+async fn create_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    request: web::Json<WishlistRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let wishlist_response =
+        wishlist_service::create_wishlist(db.get_ref(), user.id, request.into_inner()).await?;
+
+    Ok(
+        HttpResponse::Created().json(ApiResponse::<WishlistResponse> {
+            status: "success".to_string(),
+            message: "Wishlist created successfully".to_string(),
+            data: Some(wishlist_response),
+        }),
+    )
+}
+
+#[get("/wishlists")]
+async fn get_wishlists_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let wishlists = wishlist_service::get_wishlists_for_user(db.get_ref(), user.id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Wishlists fetched successfully".to_string(),
+        data: Some(wishlists),
+    }))
+}
+
+#[get("/wishlists/{wishlist_id}")]
+async fn get_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let wishlist_response =
+        wishlist_service::get_wishlist(db.get_ref(), user.id, *wishlist_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Wishlist fetched successfully".to_string(),
+        data: Some(wishlist_response),
+    }))
+}
+
+#[delete("/wishlists/{wishlist_id}")]
+async fn delete_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    wishlist_service::delete_wishlist(db.get_ref(), user.id, *wishlist_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<String> {
+        status: "success".to_string(),
+        message: "Wishlist deleted successfully".to_string(),
+        data: None,
+    }))
+}
+
+#[post("/wishlists/{wishlist_id}/items")]
+async fn add_item_to_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<i32>,
+    request: web::Json<WishlistItemRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+
+    let wishlist_response = wishlist_service::add_item_to_wishlist(
+        db.get_ref(),
+        user.id,
+        *wishlist_id,
+        request.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Item added to wishlist successfully".to_string(),
+        data: Some(wishlist_response),
+    }))
+}
+
+#[delete("/wishlists/{wishlist_id}/items/{product_id}")]
+async fn remove_item_from_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (wishlist_id, product_id) = path.into_inner();
+
+    wishlist_service::remove_item_from_wishlist(db.get_ref(), user.id, wishlist_id, product_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::<String> {
+        status: "success".to_string(),
+        message: "Item removed from wishlist successfully".to_string(),
+        data: None,
+    }))
+}
+
+#[post("/wishlists/{wishlist_id}/items/{product_id}/move-to-cart")]
+async fn move_wishlist_item_to_cart_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    path: web::Path<(i32, i32)>,
+    request: web::Json<MoveToCartRequest>,
+) -> Result<HttpResponse, ApiError> {
+    request.validate()?;
+    let (wishlist_id, product_id) = path.into_inner();
+
+    let cart_item_response = wishlist_service::move_item_to_cart(
+        db.get_ref(),
+        user.id,
+        wishlist_id,
+        product_id,
+        request.into_inner(),
+    )
+    .await?;
+
+    Ok(
+        HttpResponse::Created().json(ApiResponse::<CartItemResponse> {
+            status: "success".to_string(),
+            message: "Item moved to cart successfully".to_string(),
+            data: Some(cart_item_response),
+        }),
+    )
+}
+
+#[post("/wishlists/{wishlist_id}/share")]
+async fn share_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let wishlist_response =
+        wishlist_service::share_wishlist(db.get_ref(), user.id, *wishlist_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Wishlist shared successfully".to_string(),
+        data: Some(wishlist_response),
+    }))
+}
+
+#[delete("/wishlists/{wishlist_id}/share")]
+async fn unshare_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let wishlist_response =
+        wishlist_service::unshare_wishlist(db.get_ref(), user.id, *wishlist_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Wishlist link revoked successfully".to_string(),
+        data: Some(wishlist_response),
+    }))
+}
+
+/// Public, unauthenticated view of a shared wishlist
+#[get("/wishlists/shared/{share_token}")]
+async fn get_shared_wishlist_handler(
+    db: web::Data<DatabaseConnection>,
+    share_token: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let wishlist_response =
+        wishlist_service::get_shared_wishlist(db.get_ref(), &share_token).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_string(),
+        message: "Wishlist fetched successfully".to_string(),
+        data: Some(wishlist_response),
+    }))
+}
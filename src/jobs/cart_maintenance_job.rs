@@ -0,0 +1,60 @@
+use crate::services::cart_abandonment_service;
+use crate::utils::notifier::Notifier;
+use chrono::{DateTime, FixedOffset, Utc};
+use sea_orm::DatabaseConnection;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time between two cart maintenance runs (15 minutes)
+const DEFAULT_MAINTENANCE_INTERVAL_SECS: u64 = 900;
+
+/// Log the outcome of one maintenance step
+fn log_step(step: &str, result: Result<u64, sea_orm::DbErr>) {
+    match result {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Cart maintenance: {} {}", step, count),
+        Err(err) => tracing::error!("Cart maintenance failed to {}: {}", step, err),
+    }
+}
+
+/// Run every cart maintenance step once
+pub async fn run_cart_maintenance(db: &DatabaseConnection, notifier: &dyn Notifier) {
+    let now: DateTime<FixedOffset> = Utc::now().into();
+
+    log_step(
+        "purge expired guest carts",
+        cart_abandonment_service::purge_expired_guest_carts(db, now).await,
+    );
+    log_step(
+        "purge stale carts",
+        cart_abandonment_service::purge_stale_carts(db, now).await,
+    );
+    log_step(
+        "flag abandoned carts",
+        cart_abandonment_service::flag_abandoned_carts(db, now).await,
+    );
+    log_step(
+        "send abandoned cart reminders",
+        cart_abandonment_service::send_abandoned_cart_reminders(db, notifier, now).await,
+    );
+}
+
+/// Spawn the background task that purges old carts, flags abandoned ones
+/// and reminds their owners
+pub fn spawn_cart_maintenance(db: Arc<DatabaseConnection>, notifier: Arc<dyn Notifier>) {
+    let interval_secs = env::var("CART_MAINTENANCE_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_MAINTENANCE_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+        loop {
+            interval.tick().await;
+            run_cart_maintenance(&db, notifier.as_ref()).await;
+        }
+    });
+}
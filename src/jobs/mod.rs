@@ -0,0 +1,2 @@
+pub mod cart_maintenance_job;
+pub mod recommendation_job;
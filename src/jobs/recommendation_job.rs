@@ -0,0 +1,31 @@
+use crate::services::recommendation_service;
+use sea_orm::DatabaseConnection;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time between two recommendation refreshes (1 hour)
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 3600;
+
+/// Spawn the background task that periodically rebuilds the
+/// "frequently bought together" table from the order history
+pub fn spawn_recommendation_refresh(db: Arc<DatabaseConnection>) {
+    let interval_secs = env::var("RECOMMENDATION_REFRESH_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+        loop {
+            // The first tick completes immediately, so the table is filled on startup
+            interval.tick().await;
+
+            match recommendation_service::refresh_recommendations(&db).await {
+                Ok(pairs) => tracing::info!("Refreshed {} product recommendation pairs", pairs),
+                Err(err) => tracing::error!("Failed to refresh product recommendations: {}", err),
+            }
+        }
+    });
+}
@@ -1,14 +1,23 @@
 use actix_web::{web, App, HttpResponse, HttpServer, Responder};
 use db::establish_connection;
 use handler::cart_handler::*;
+use handler::coupon_handler::*;
+use handler::guest_cart_handler::*;
 use handler::order_handler::*;
 use handler::product_handler::*;
+use handler::promotion_handler::*;
+use handler::shipping_handler::*;
+use handler::tax_handler::*;
 use handler::user_handler::*;
+use handler::wishlist_handler::*;
+use jobs::cart_maintenance_job::spawn_cart_maintenance;
+use jobs::recommendation_job::spawn_recommendation_refresh;
 use tracing::Level;
 
 mod db; // Module for database connection
 pub mod entities;
 mod handler;
+mod jobs; // Module for background tasks
 mod models; // Module for SeaORM models
 mod services;
 mod utils;
@@ -20,7 +29,8 @@ async fn default_route() -> impl Responder {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let db = establish_connection().await?;
+    // Shared by the server and the background jobs
+    let db = web::Data::new(establish_connection().await?);
 
     println!("Connected to the database!");
 
@@ -29,10 +39,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_max_level(Level::DEBUG) // Set log level to DEBUG
         .init(); // Initialize the subscriber
 
+    // Keep the "frequently bought together" table up to date
+    spawn_recommendation_refresh(db.clone().into_inner());
+
+    // Purge old carts and chase abandoned ones
+    spawn_cart_maintenance(
+        db.clone().into_inner(),
+        utils::notifier::notifier_from_env(),
+    );
+
+    // Collects and refunds order payments
+    let payment_provider = utils::payment_provider::payment_provider_from_env();
+
     // Start Actix Web server
     HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(db.clone())) // Pass the DB connection to Actix
+            .app_data(db.clone()) // Pass the DB connection to Actix
+            .app_data(web::Data::from(payment_provider.clone()))
             .route("/", web::get().to(default_route)) // Default route handler
             .service(register) // Add your register route to the app
             .service(get_user) // Add the GET route
@@ -42,18 +65,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .service(update_product)
             .service(delete_product)
             .service(get_all_products)
+            .service(get_related_products)
+            .service(get_all_products_admin)
+            .service(get_product_admin)
+            .service(get_product_translations)
+            .service(upsert_product_translation)
+            .service(delete_product_translation)
             .service(create_cart_handler)
+            .service(get_cart_handler)
+            .service(get_my_cart_handler)
+            .service(add_item_to_my_cart_handler)
+            .service(save_my_cart_handler)
+            .service(get_my_saved_carts_handler)
+            .service(restore_my_saved_cart_handler)
+            .service(delete_my_saved_cart_handler)
             .service(add_item_to_cart_handler)
+            .service(update_cart_item_handler)
+            .service(save_cart_item_for_later_handler)
+            .service(move_cart_item_to_cart_handler)
             .service(remove_item_from_cart_handler)
             .service(clear_cart_handler)
+            .service(get_cart_recommendations_handler)
+            .service(share_cart_handler)
+            .service(import_shared_cart_handler)
+            .service(get_cart_abandonment_metrics_handler)
+            .service(create_guest_cart_handler) //guest carts
+            .service(get_guest_cart_handler)
+            .service(add_item_to_guest_cart_handler)
+            .service(update_guest_cart_item_handler)
             .service(create_order_handler)
+            .service(checkout_preview_handler)
+            .service(get_my_orders_handler)
+            .service(search_orders_handler)
             .service(get_order_with_items_handler)
             .service(update_payment_status_handler)
-            .service(delete_order_handler)
+            .service(update_order_status_handler)
+            .service(cancel_order_handler)
+            .service(purge_order_handler)
+            .service(create_refund_handler)
+            .service(get_order_refunds_handler)
+            .service(issue_order_invoice_handler)
+            .service(get_order_invoice_handler)
+            .service(get_available_shipping_methods_handler) //shipping
+            .service(get_all_shipping_methods_handler)
+            .service(create_shipping_method_handler)
+            .service(update_shipping_method_handler)
+            .service(get_all_tax_classes_handler) //tax
+            .service(create_tax_class_handler)
+            .service(update_tax_class_handler)
+            .service(apply_coupon_handler) //coupons
+            .service(remove_coupon_handler)
+            .service(get_all_coupons_handler)
+            .service(create_coupon_handler)
+            .service(update_coupon_handler)
+            .service(get_all_promotions_handler) //promotions
+            .service(create_promotion_handler)
+            .service(update_promotion_handler)
             .service(create_order_handler) //orders
             .service(get_order_with_items_handler)
             .service(update_payment_status_handler)
-            .service(delete_order_handler)
+            .service(create_wishlist_handler) //wishlists
+            .service(get_wishlists_handler)
+            .service(get_shared_wishlist_handler)
+            .service(get_wishlist_handler)
+            .service(delete_wishlist_handler)
+            .service(add_item_to_wishlist_handler)
+            .service(remove_item_from_wishlist_handler)
+            .service(move_wishlist_item_to_cart_handler)
+            .service(share_wishlist_handler)
+            .service(unshare_wishlist_handler)
     })
     .bind("127.0.0.1:8080")? // Bind server to address
     .run()
@@ -1,9 +1,12 @@
+use crate::entities::sea_orm_active_enums::ProductStatus;
+use chrono::{DateTime, FixedOffset};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationError};
 
 /// This struct is used to handle the creation or update of a product.
 #[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_availability_window"))]
 pub struct ProductRequest {
     #[validate(length(min = 1, message = "Product name cannot be empty."))]
     pub name: String,
@@ -13,6 +16,31 @@ pub struct ProductRequest {
 
     #[validate(custom(function = "validate_decimal_range"))]
     pub price: Decimal,
+
+    #[validate(length(min = 1, max = 50, message = "Category must be 1-50 characters."))]
+    pub category: Option<String>,
+
+    /// Defaults to `published` on creation and to the current status on update.
+    pub status: Option<ProductStatus>,
+
+    pub available_from: Option<DateTime<FixedOffset>>,
+
+    pub available_until: Option<DateTime<FixedOffset>>,
+
+    /// Most units of this product a single cart may hold; unlimited when absent.
+    #[validate(range(min = 1, message = "Max quantity must be at least 1."))]
+    pub max_quantity: Option<i32>,
+
+    /// Units on hand; the product is not stock-tracked when absent.
+    #[validate(range(min = 0, message = "Stock cannot be negative."))]
+    pub stock: Option<i32>,
+
+    /// Shipping weight of one unit in grams.
+    #[validate(range(min = 0, message = "Weight cannot be negative."))]
+    pub weight_grams: Option<i32>,
+
+    /// VAT treatment; the standard class applies when absent.
+    pub tax_class_id: Option<i32>,
 }
 
 /// This struct is used to return product details in API responses.
@@ -22,6 +50,14 @@ pub struct ProductResponse {
     pub name: String,
     pub description: Option<String>,
     pub price: Decimal,
+    pub category: Option<String>,
+    pub status: ProductStatus,
+    pub available_from: Option<String>,
+    pub available_until: Option<String>,
+    pub max_quantity: Option<i32>,
+    pub stock: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub tax_class_id: Option<i32>,
 }
 
 impl From<crate::entities::product::Model> for ProductResponse {
@@ -31,6 +67,46 @@ impl From<crate::entities::product::Model> for ProductResponse {
             name: model.name,
             description: model.description,
             price: model.price,
+            category: model.category,
+            status: model.status,
+            available_from: model.available_from.map(|date| date.to_string()),
+            available_until: model.available_until.map(|date| date.to_string()),
+            max_quantity: model.max_quantity,
+            stock: model.stock,
+            weight_grams: model.weight_grams,
+            tax_class_id: model.tax_class_id,
+        }
+    }
+}
+
+/// This struct is used to create or replace a product translation.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ProductTranslationRequest {
+    #[validate(length(min = 1, message = "Product name cannot be empty."))]
+    pub name: String,
+
+    #[validate(length(max = 100, message = "Description cannot exceed 100 characters."))]
+    pub description: Option<String>,
+}
+
+/// This struct is used to return product translations in API responses.
+#[derive(Debug, Serialize)]
+pub struct ProductTranslationResponse {
+    pub product_id: i32,
+    pub locale: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub updated_at: String,
+}
+
+impl From<crate::entities::product_translation::Model> for ProductTranslationResponse {
+    fn from(model: crate::entities::product_translation::Model) -> Self {
+        ProductTranslationResponse {
+            product_id: model.product_id,
+            locale: model.locale,
+            name: model.name,
+            description: model.description,
+            updated_at: model.updated_at.to_string(),
         }
     }
 }
@@ -44,3 +120,15 @@ fn validate_decimal_range(value: &Decimal) -> Result<(), ValidationError> {
     }
     Ok(())
 }
+
+/// Struct-level validator making sure the availability window is not empty.
+fn validate_availability_window(request: &ProductRequest) -> Result<(), ValidationError> {
+    if let (Some(from), Some(until)) = (request.available_from, request.available_until) {
+        if from >= until {
+            let mut error = ValidationError::new("availability_window");
+            error.message = Some("available_from must be before available_until.".into());
+            return Err(error);
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::entities::sea_orm_active_enums::UserRole;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
@@ -16,6 +17,10 @@ pub struct UserRegisterRequest {
 pub struct UserResponse {
     pub id: i32,
     pub email: String,
+    pub role: UserRole,
+    /// Set when a guest cart was merged into the user's cart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cart_id: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -31,5 +36,9 @@ pub struct UserLoginRequest {
 pub struct UserLoginResponse {
     pub id: i32,
     pub email: String,
+    pub role: UserRole,
     pub token: String,
+    /// Set when a guest cart was merged into the user's cart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cart_id: Option<i32>,
 }
@@ -0,0 +1,301 @@
+use super::guest_cart_service::guest_cart_idle_timeout;
+use crate::entities::{cart, cart_abandonment, cart_item, order, user};
+use crate::utils::actix_error::ApiError;
+use crate::utils::notifier::{AbandonedCartReminder, Notifier};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, Statement, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use validator::Validate;
+
+/// Default idle time after which a cart counts as abandoned (1 day)
+const DEFAULT_CART_ABANDONED_AFTER_HOURS: i64 = 24;
+
+/// Default idle time after which a customer's cart is purged (90 days)
+const DEFAULT_CART_RETENTION_DAYS: i64 = 90;
+
+/// Default metrics window
+const DEFAULT_METRICS_DAYS: i64 = 30;
+
+/// Read a positive integer setting from the environment
+fn positive_env(name: &str, default: i64) -> i64 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+/// Idle time after which an active cart is flagged as abandoned
+/// (overridable with the `CART_ABANDONED_AFTER_HOURS` environment variable)
+pub fn cart_abandoned_after() -> Duration {
+    Duration::hours(positive_env(
+        "CART_ABANDONED_AFTER_HOURS",
+        DEFAULT_CART_ABANDONED_AFTER_HOURS,
+    ))
+}
+
+/// Idle time after which a customer's cart is deleted
+/// (overridable with the `CART_RETENTION_DAYS` environment variable)
+pub fn cart_retention() -> Duration {
+    Duration::days(positive_env(
+        "CART_RETENTION_DAYS",
+        DEFAULT_CART_RETENTION_DAYS,
+    ))
+}
+
+/// Query parameters of the abandonment metrics endpoint
+#[derive(Debug, Deserialize, Validate)]
+pub struct AbandonmentMetricsQuery {
+    #[validate(range(min = 1, max = 365, message = "days must be between 1 and 365"))]
+    pub days: Option<i64>,
+}
+
+impl AbandonmentMetricsQuery {
+    pub fn days(&self) -> i64 {
+        self.days.unwrap_or(DEFAULT_METRICS_DAYS)
+    }
+}
+
+/// Cart abandonment figures over a time window
+#[derive(Debug, Serialize)]
+pub struct AbandonmentMetricsResponse {
+    pub since: String,
+    pub until: String,
+    pub abandoned_carts: u64,
+    pub abandoned_value: Decimal,
+    pub reminders_sent: u64,
+    pub recovered_carts: u64,
+    pub orders_placed: u64,
+    /// Share of checkout attempts that ended in an abandoned cart
+    pub abandonment_rate: f64,
+    /// Share of abandoned carts the customer came back to
+    pub recovery_rate: f64,
+}
+
+/// Delete the carts matching `condition` together with their items
+async fn purge_carts(db: &DatabaseConnection, condition: Condition) -> Result<u64, DbErr> {
+    let txn = db.begin().await?;
+
+    let cart_ids: Vec<i32> = cart::Entity::find()
+        .filter(condition)
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|cart| cart.id)
+        .collect();
+
+    if cart_ids.is_empty() {
+        return Ok(0);
+    }
+
+    cart_item::Entity::delete_many()
+        .filter(cart_item::Column::CartId.is_in(cart_ids.clone()))
+        .exec(&txn)
+        .await?;
+    let result = cart::Entity::delete_many()
+        .filter(cart::Column::Id.is_in(cart_ids))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+
+    Ok(result.rows_affected)
+}
+
+/// Delete guest carts that have been idle longer than the guest cart timeout
+pub async fn purge_expired_guest_carts(
+    db: &DatabaseConnection,
+    now: DateTime<FixedOffset>,
+) -> Result<u64, DbErr> {
+    purge_carts(
+        db,
+        Condition::all()
+            .add(cart::Column::UserId.is_null())
+            .add(cart::Column::UpdatedAt.lt(now - guest_cart_idle_timeout())),
+    )
+    .await
+}
+
+/// Delete customer carts (active or saved) that have been idle past the retention period
+pub async fn purge_stale_carts(
+    db: &DatabaseConnection,
+    now: DateTime<FixedOffset>,
+) -> Result<u64, DbErr> {
+    purge_carts(
+        db,
+        Condition::all()
+            .add(cart::Column::UserId.is_not_null())
+            .add(cart::Column::UpdatedAt.lt(now - cart_retention())),
+    )
+    .await
+}
+
+/// Record an abandonment for every non-empty active cart idle past the threshold.
+///
+/// A cart is flagged once per idle period; it can be flagged again after it
+/// has been recovered.
+pub async fn flag_abandoned_carts(
+    db: &DatabaseConnection,
+    now: DateTime<FixedOffset>,
+) -> Result<u64, DbErr> {
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO cart_abandonment (cart_id, user_id, item_count, cart_total, abandoned_at)
+            SELECT c.id, c.user_id, SUM(ci.quantity)::int, SUM(ci.quantity * p.price), $1
+            FROM cart c
+            JOIN cart_item ci ON ci.cart_id = c.id AND NOT ci.saved_for_later
+            JOIN product p ON p.id = ci.product_id
+            WHERE c.kind = 'active'
+              AND c.updated_at < $2
+              AND NOT EXISTS (
+                  SELECT 1 FROM cart_abandonment ca
+                  WHERE ca.cart_id = c.id AND ca.recovered_at IS NULL
+              )
+            GROUP BY c.id, c.user_id
+            "#,
+            [now.into(), (now - cart_abandoned_after()).into()],
+        ))
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Send one reminder per open abandonment of a registered customer.
+///
+/// Failed deliveries are logged and retried on the next run.
+pub async fn send_abandoned_cart_reminders(
+    db: &DatabaseConnection,
+    notifier: &dyn Notifier,
+    now: DateTime<FixedOffset>,
+) -> Result<u64, DbErr> {
+    let pending = cart_abandonment::Entity::find()
+        .filter(cart_abandonment::Column::UserId.is_not_null())
+        .filter(cart_abandonment::Column::ReminderSentAt.is_null())
+        .filter(cart_abandonment::Column::RecoveredAt.is_null())
+        .all(db)
+        .await?;
+
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let user_ids: Vec<i32> = pending.iter().filter_map(|row| row.user_id).collect();
+    let emails: HashMap<i32, String> = user::Entity::find()
+        .filter(user::Column::Id.is_in(user_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.email))
+        .collect();
+
+    let mut sent = 0;
+    for abandonment in pending {
+        let Some((user_id, email)) = abandonment
+            .user_id
+            .and_then(|user_id| Some((user_id, emails.get(&user_id)?.clone())))
+        else {
+            continue;
+        };
+
+        let reminder = AbandonedCartReminder {
+            cart_id: abandonment.cart_id,
+            user_id,
+            email,
+            item_count: abandonment.item_count,
+            cart_total: abandonment.cart_total,
+            abandoned_at: abandonment.abandoned_at.to_string(),
+        };
+
+        match notifier.send_abandoned_cart_reminder(&reminder).await {
+            Ok(()) => {
+                cart_abandonment::Entity::update_many()
+                    .col_expr(cart_abandonment::Column::ReminderSentAt, Expr::value(now))
+                    .filter(cart_abandonment::Column::Id.eq(abandonment.id))
+                    .exec(db)
+                    .await?;
+                sent += 1;
+            }
+            Err(err) => tracing::error!(
+                "Failed to send abandoned cart reminder for cart {}: {}",
+                abandonment.cart_id,
+                err
+            ),
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Close the open abandonment of a cart the customer came back to
+pub async fn mark_cart_recovered<C: ConnectionTrait>(
+    conn: &C,
+    cart_id: i32,
+    now: DateTime<FixedOffset>,
+) -> Result<(), DbErr> {
+    cart_abandonment::Entity::update_many()
+        .col_expr(cart_abandonment::Column::RecoveredAt, Expr::value(now))
+        .filter(cart_abandonment::Column::CartId.eq(cart_id))
+        .filter(cart_abandonment::Column::RecoveredAt.is_null())
+        .exec(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Ratio of two counts, 0 when the denominator is 0
+fn rate(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Service function to compute abandonment metrics over the last `days` days (staff only)
+pub async fn get_abandonment_metrics(
+    db: &DatabaseConnection,
+    days: i64,
+) -> Result<AbandonmentMetricsResponse, ApiError> {
+    let until: DateTime<FixedOffset> = Utc::now().into();
+    let since = until - Duration::days(days);
+
+    let abandonments = cart_abandonment::Entity::find()
+        .filter(cart_abandonment::Column::AbandonedAt.gte(since))
+        .all(db)
+        .await?;
+
+    let orders_placed = order::Entity::find()
+        .filter(order::Column::CreatedAt.gte(since))
+        .count(db)
+        .await?;
+
+    let abandoned_carts = abandonments.len() as u64;
+    let reminders_sent = abandonments
+        .iter()
+        .filter(|row| row.reminder_sent_at.is_some())
+        .count() as u64;
+    let recovered_carts = abandonments
+        .iter()
+        .filter(|row| row.recovered_at.is_some())
+        .count() as u64;
+
+    Ok(AbandonmentMetricsResponse {
+        since: since.to_string(),
+        until: until.to_string(),
+        abandoned_carts,
+        abandoned_value: abandonments.iter().map(|row| row.cart_total).sum(),
+        reminders_sent,
+        recovered_carts,
+        orders_placed,
+        abandonment_rate: rate(abandoned_carts, abandoned_carts + orders_placed),
+        recovery_rate: rate(recovered_carts, abandoned_carts),
+    })
+}
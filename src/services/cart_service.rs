@@ -1,20 +1,47 @@
 use crate::{
-    entities::{cart, cart_item},
-    utils::actix_error::ApiError,
+    entities::{cart, cart_item, product, sea_orm_active_enums::CartKind},
+    services::{
+        cart_abandonment_service, coupon_service,
+        pricing_service::{self, CheckoutRequest},
+        product_service::is_purchasable,
+        promotion_service::AppliedPromotion,
+        translation_service,
+    },
+    utils::{actix_error::ApiError, auth::CartCaller},
 };
-use actix_web::{web, HttpResponse, Responder};
 use chrono::Utc;
+use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DeleteResult, EntityTrait, QueryFilter, Set,
+    sea_query::{Alias, Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, DeleteResult,
+    EntityTrait, ModelTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
-/// Struct for creating or updating a cart
-#[derive(Debug, Deserialize, Validate)]
-pub struct CartRequest {
-    #[validate(range(min = 1))]
-    pub user_id: i32,
+/// Most units of one product a cart line may hold
+pub const MAX_LINE_QUANTITY: i32 = 999;
+
+/// Fail unless `quantity` lies between `min` and [`MAX_LINE_QUANTITY`]
+fn check_line_quantity(quantity: i32, min: i32) -> Result<(), ValidationError> {
+    if (min..=MAX_LINE_QUANTITY).contains(&quantity) {
+        return Ok(());
+    }
+
+    let mut error = ValidationError::new("quantity");
+    error.message =
+        Some(format!("Quantity must be between {} and {}", min, MAX_LINE_QUANTITY).into());
+    Err(error)
+}
+
+/// Custom validator for the quantity of units added to a cart line
+pub fn validate_line_quantity(quantity: i32) -> Result<(), ValidationError> {
+    check_line_quantity(quantity, 1)
+}
+
+/// Custom validator for a new cart line quantity, where 0 removes the line
+fn validate_updated_quantity(quantity: i32) -> Result<(), ValidationError> {
+    check_line_quantity(quantity, 0)
 }
 
 /// Struct for creating or updating a cart item
@@ -23,7 +50,14 @@ pub struct CartItemRequest {
     #[validate(range(min = 1))]
     pub product_id: i32,
 
-    #[validate(range(min = 1, message = "Quantity must be at least 1"))]
+    #[validate(custom(function = "validate_line_quantity"))]
+    pub quantity: i32,
+}
+
+/// Struct for setting the quantity of an existing cart item
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCartItemRequest {
+    #[validate(custom(function = "validate_updated_quantity"))]
     pub quantity: i32,
 }
 
@@ -31,7 +65,9 @@ pub struct CartItemRequest {
 #[derive(Debug, Serialize)]
 pub struct CartResponse {
     pub id: i32,
-    pub user_id: i32,
+    pub user_id: Option<i32>,
+    pub kind: CartKind,
+    pub name: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -43,15 +79,63 @@ pub struct CartItemResponse {
     pub cart_id: i32,
     pub product_id: i32,
     pub quantity: i32,
+    pub saved_for_later: bool,
+    /// Unit price when the product was added to the cart
+    pub unit_price: Decimal,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Struct used to return a cart with its line items and computed totals
+#[derive(Debug, Serialize)]
+pub struct CartDetailResponse {
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub kind: CartKind,
+    pub name: Option<String>,
+    pub items: Vec<CartLineResponse>,
+    /// Lines parked by the customer; not part of the totals or of checkout
+    pub saved_for_later: Vec<CartLineResponse>,
+    /// Total number of units across all lines
+    pub item_count: i32,
+    /// Sum of the line totals, before promotions and the discount code
+    pub total: Decimal,
+    /// Part of the total taken off by promotions and the discount code
+    pub discount_total: Decimal,
+    /// What the items cost after discounts, before shipping
+    pub discounted_total: Decimal,
+    /// Automatic promotions that lower the price of the cart
+    pub promotions: Vec<AppliedPromotion>,
+    /// Discount code applied to the cart
+    pub coupon_code: Option<String>,
+    /// Why the cart's discounts cannot be applied (e.g. an expired code);
+    /// checkout is refused until this is resolved
+    pub discount_error: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Struct used to return one cart line priced at the current product price
+#[derive(Debug, Serialize)]
+pub struct CartLineResponse {
+    pub id: i32,
+    pub product_id: i32,
+    pub product_name: String,
+    pub unit_price: Decimal,
+    /// Unit price when the product was added to the cart
+    pub added_price: Decimal,
+    pub price_changed: bool,
+    pub quantity: i32,
+    pub line_total: Decimal,
+}
+
 impl From<crate::entities::cart::Model> for CartResponse {
     fn from(model: crate::entities::cart::Model) -> Self {
         CartResponse {
             id: model.id,
             user_id: model.user_id,
+            kind: model.kind,
+            name: model.name,
             created_at: model.created_at.to_string(),
             updated_at: model.updated_at.to_string(),
         }
@@ -65,44 +149,327 @@ impl From<crate::entities::cart_item::Model> for CartItemResponse {
             cart_id: model.cart_id,
             product_id: model.product_id,
             quantity: model.quantity,
+            saved_for_later: model.saved_for_later,
+            unit_price: model.unit_price,
             created_at: model.created_at.to_string(),
             updated_at: model.updated_at.to_string(),
         }
     }
 }
 
-/// Service function to create a new cart
-pub async fn create_cart(
-    db: &DatabaseConnection,
-    request: CartRequest,
-) -> Result<CartResponse, ApiError> {
-    // Get the current timestamp
-    let now_utc = Utc::now();
-    let now_fixed: chrono::DateTime<chrono::FixedOffset> = now_utc.into(); // Convert to FixedOffset
+/// Fetch the active cart of a user, if they have one
+pub async fn find_active_cart<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+) -> Result<Option<cart::Model>, DbErr> {
+    cart::Entity::find()
+        .filter(cart::Column::UserId.eq(user_id))
+        .filter(cart::Column::Kind.eq(CartKind::Active))
+        .one(conn)
+        .await
+}
 
-    // Validate the input
-    if let Err(validation_errors) = request.validate() {
-        return Err(ApiError::ValidationError(format!(
-            "Validation failed: {}",
-            validation_errors
-        )));
+/// Fetch the active cart of a user, creating it on first use
+pub async fn get_or_create_active_cart<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    now: chrono::DateTime<chrono::FixedOffset>,
+) -> Result<cart::Model, DbErr> {
+    if let Some(cart) = find_active_cart(conn, user_id).await? {
+        return Ok(cart);
     }
 
-    // Create the cart in the database
     let cart = cart::ActiveModel {
-        user_id: Set(request.user_id),
-        created_at: Set(now_fixed), // Set the created_at timestamp
-        updated_at: Set(now_fixed), // Set the updated_at timestamp
+        user_id: Set(Some(user_id)),
+        kind: Set(CartKind::Active),
+        name: Set(None),
+        created_at: Set(now), // Set the created_at timestamp
+        updated_at: Set(now), // Set the updated_at timestamp
         ..Default::default()
     };
 
-    let inserted_cart = cart
-        .insert(db)
+    // A concurrent request may have created the cart in the meantime; the
+    // partial unique index on active carts turns that into a no-op
+    cart::Entity::insert(cart)
+        .on_conflict(
+            OnConflict::column(cart::Column::UserId)
+                .target_and_where(Expr::col(cart::Column::Kind).eq(CartKind::Active))
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(conn)
+        .await?;
+
+    find_active_cart(conn, user_id)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("Active cart for user {}", user_id)))
+}
+
+/// Service function to get the user's active cart, creating it if needed
+pub async fn create_cart(db: &DatabaseConnection, user_id: i32) -> Result<CartResponse, ApiError> {
+    // Get the current timestamp
+    let now_utc = Utc::now();
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = now_utc.into(); // Convert to FixedOffset
+
+    // Users have a single active cart, so this is idempotent
+    let cart = get_or_create_active_cart(db, user_id, now_fixed)
         .await
         .map_err(|err| ApiError::InternalServerError(format!("Failed to create cart: {}", err)))?;
 
     // Return the response with cart details
-    Ok(CartResponse::from(inserted_cart))
+    Ok(CartResponse::from(cart))
+}
+
+/// Load the items of a cart with their products and compute the totals
+pub async fn build_cart_details(
+    db: &DatabaseConnection,
+    cart: cart::Model,
+    locale: &str,
+) -> Result<CartDetailResponse, ApiError> {
+    let items = cart_item::Entity::find()
+        .filter(cart_item::Column::CartId.eq(cart.id))
+        .find_also_related(product::Entity)
+        .order_by_asc(cart_item::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let product_ids = items.iter().map(|(item, _)| item.product_id).collect();
+    let mut translations = translation_service::find_translations(db, product_ids, locale).await?;
+
+    let (lines, saved_lines): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .filter_map(|(item, product)| {
+            let product = product?;
+            let product_name = translations
+                .remove(&product.id)
+                .map(|translation| translation.name)
+                .unwrap_or(product.name);
+
+            let line = CartLineResponse {
+                id: item.id,
+                product_id: item.product_id,
+                product_name,
+                unit_price: product.price,
+                added_price: item.unit_price,
+                price_changed: item.unit_price != product.price,
+                quantity: item.quantity,
+                line_total: product.price * Decimal::from(item.quantity),
+            };
+            Some((item.saved_for_later, line))
+        })
+        .partition(|(saved_for_later, _)| !saved_for_later);
+    let lines: Vec<CartLineResponse> = lines.into_iter().map(|(_, line)| line).collect();
+    let coupon_code = coupon_service::find_cart_coupon(db, &cart)
+        .await?
+        .map(|coupon| coupon.coupon.code);
+    let total: Decimal = lines.iter().map(|line| line.line_total).sum();
+
+    // Discounts are worked out by the checkout pricing pipeline, so the cart
+    // shows the same total checkout will charge for the items
+    let (discount_total, promotions, discount_error) = if lines.is_empty() {
+        (Decimal::ZERO, Vec::new(), None)
+    } else {
+        let request = CheckoutRequest {
+            cart_id: Some(cart.id),
+            ..Default::default()
+        };
+        match pricing_service::quote_cart(db, cart.user_id, cart.id, &request).await {
+            Ok(quote) => (quote.discount_total, quote.promotions, None),
+            Err(e @ (ApiError::DatabaseError(_) | ApiError::InternalServerError(_))) => {
+                return Err(e)
+            }
+            Err(e) => (Decimal::ZERO, Vec::new(), Some(e.to_string())),
+        }
+    };
+
+    Ok(CartDetailResponse {
+        id: cart.id,
+        user_id: cart.user_id,
+        kind: cart.kind,
+        name: cart.name,
+        item_count: lines.iter().map(|line| line.quantity).sum(),
+        total,
+        discount_total,
+        discounted_total: total - discount_total,
+        promotions,
+        coupon_code,
+        discount_error,
+        items: lines,
+        saved_for_later: saved_lines.into_iter().map(|(_, line)| line).collect(),
+        created_at: cart.created_at.to_string(),
+        updated_at: cart.updated_at.to_string(),
+    })
+}
+
+/// Service function to fetch a cart with its items and totals
+pub async fn get_cart_details(
+    db: &DatabaseConnection,
+    caller: &CartCaller,
+    cart_id: i32,
+    locale: &str,
+) -> Result<CartDetailResponse, ApiError> {
+    let cart = find_visible_cart(db, caller, cart_id).await?;
+
+    build_cart_details(db, cart, locale).await
+}
+
+/// Load a cart the caller may read: their own, or any cart for staff.
+///
+/// Other carts are reported as missing so that IDs cannot be probed.
+pub async fn find_visible_cart<C: ConnectionTrait>(
+    conn: &C,
+    caller: &CartCaller,
+    cart_id: i32,
+) -> Result<cart::Model, ApiError> {
+    cart::Entity::find_by_id(cart_id)
+        .one(conn)
+        .await?
+        .filter(|cart| caller.owns(cart) || caller.is_staff())
+        .ok_or_else(|| ApiError::NotFound(format!("Cart with ID {} not found", cart_id)))
+}
+
+/// Load a cart the caller may change, which must be their own
+pub async fn find_owned_cart<C: ConnectionTrait>(
+    conn: &C,
+    caller: &CartCaller,
+    cart_id: i32,
+) -> Result<cart::Model, ApiError> {
+    cart::Entity::find_by_id(cart_id)
+        .one(conn)
+        .await?
+        .filter(|cart| caller.owns(cart))
+        .ok_or_else(|| ApiError::NotFound(format!("Cart with ID {} not found", cart_id)))
+}
+
+/// Service function to fetch the active cart of a user
+pub async fn get_cart_details_for_user(
+    db: &DatabaseConnection,
+    user_id: i32,
+    locale: &str,
+) -> Result<CartDetailResponse, ApiError> {
+    let cart = find_active_cart(db, user_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("No active cart for user {}", user_id)))?;
+
+    build_cart_details(db, cart, locale).await
+}
+
+/// Insert a cart line, or add `quantity` to the existing line for the same product.
+///
+/// `unit_price` is the price shown to the customer and replaces the line's snapshot.
+pub async fn upsert_cart_line<C: ConnectionTrait>(
+    conn: &C,
+    cart_id: i32,
+    product_id: i32,
+    quantity: i32,
+    unit_price: Decimal,
+    now: chrono::DateTime<chrono::FixedOffset>,
+) -> Result<cart_item::Model, DbErr> {
+    let cart_item = cart_item::ActiveModel {
+        cart_id: Set(cart_id),
+        product_id: Set(product_id),
+        quantity: Set(quantity),
+        saved_for_later: Set(false),
+        unit_price: Set(unit_price),
+        created_at: Set(now), // Set the created_at timestamp
+        updated_at: Set(now), // Set the updated_at timestamp
+        ..Default::default()
+    };
+
+    // Relies on the unique (cart_id, product_id) index
+    cart_item::Entity::insert(cart_item)
+        .on_conflict(
+            OnConflict::columns([cart_item::Column::CartId, cart_item::Column::ProductId])
+                .value(
+                    cart_item::Column::Quantity,
+                    Expr::col((cart_item::Entity, cart_item::Column::Quantity)).add(Expr::col((
+                        Alias::new("excluded"),
+                        cart_item::Column::Quantity,
+                    ))),
+                )
+                .update_columns([
+                    cart_item::Column::UpdatedAt,
+                    // Adding a parked product moves it back into the cart
+                    cart_item::Column::SavedForLater,
+                    // The whole line is now priced as last seen by the customer
+                    cart_item::Column::UnitPrice,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(conn)
+        .await
+}
+
+/// Record activity on a cart so idle carts can be detected
+pub async fn touch_cart<C: ConnectionTrait>(
+    conn: &C,
+    cart_id: i32,
+    now: chrono::DateTime<chrono::FixedOffset>,
+) -> Result<(), DbErr> {
+    cart::Entity::update_many()
+        .col_expr(cart::Column::UpdatedAt, Expr::value(now))
+        .filter(cart::Column::Id.eq(cart_id))
+        .exec(conn)
+        .await?;
+
+    // A customer coming back closes any open abandonment
+    cart_abandonment_service::mark_cart_recovered(conn, cart_id, now).await
+}
+
+/// Make sure a product exists (404) and can currently be bought (422)
+pub fn ensure_purchasable(
+    product: Option<product::Model>,
+    product_id: i32,
+    now: chrono::DateTime<chrono::FixedOffset>,
+) -> Result<product::Model, ApiError> {
+    let product = product
+        .ok_or_else(|| ApiError::NotFound(format!("Product with ID {} not found", product_id)))?;
+
+    // Drafts and products outside their availability window cannot be bought
+    if !is_purchasable(&product, now) {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "Product with ID {} is not available for purchase",
+            product_id
+        )));
+    }
+
+    Ok(product)
+}
+
+/// Most units of a product a cart line may hold: the product's own maximum,
+/// and never more than [`MAX_LINE_QUANTITY`]
+fn line_quantity_limit(product: &product::Model) -> i32 {
+    product
+        .max_quantity
+        .map_or(MAX_LINE_QUANTITY, |max| max.min(MAX_LINE_QUANTITY))
+}
+
+/// Enforce the maximum quantity of a cart line, checked on the merged quantity
+/// when a product is added again
+pub fn check_quantity_limit(product: &product::Model, quantity: i32) -> Result<(), ApiError> {
+    match line_quantity_limit(product) {
+        max_quantity if quantity > max_quantity => Err(ApiError::UnprocessableEntity(format!(
+            "At most {} units of product with ID {} can be added to a cart",
+            max_quantity, product.id
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Bring a merged cart line back within its maximum quantity
+pub async fn cap_cart_line<C: ConnectionTrait>(
+    conn: &C,
+    line: cart_item::Model,
+    product: &product::Model,
+) -> Result<(), DbErr> {
+    let max_quantity = line_quantity_limit(product);
+    if line.quantity > max_quantity {
+        let mut active_model: cart_item::ActiveModel = line.into();
+        active_model.quantity = Set(max_quantity);
+        active_model.update(conn).await?;
+    }
+
+    Ok(())
 }
 
 /// Service function to add an item to the cart
@@ -110,6 +477,20 @@ pub async fn add_item_to_cart(
     db: &DatabaseConnection,
     cart_id: i32,
     request: CartItemRequest,
+) -> Result<CartItemResponse, ApiError> {
+    // The limit applies to the merged line, so it is checked after the upsert
+    let txn = db.begin().await?;
+    let cart_item_response = add_line_to_cart(&txn, cart_id, request).await?;
+    txn.commit().await?;
+
+    Ok(cart_item_response)
+}
+
+/// Add an item to a cart within the caller's transaction
+pub async fn add_line_to_cart<C: ConnectionTrait>(
+    conn: &C,
+    cart_id: i32,
+    request: CartItemRequest,
 ) -> Result<CartItemResponse, ApiError> {
     // Get the current timestamp
     let now_utc = Utc::now();
@@ -123,29 +504,117 @@ pub async fn add_item_to_cart(
         )));
     }
 
-    // Create the cart item in the database
-    let cart_item = cart_item::ActiveModel {
-        cart_id: Set(cart_id),
-        product_id: Set(request.product_id),
-        quantity: Set(request.quantity),
-        created_at: Set(now_fixed), // Set the created_at timestamp
-        updated_at: Set(now_fixed), // Set the updated_at timestamp
-        ..Default::default()
-    };
+    cart::Entity::find_by_id(cart_id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Cart with ID {} not found", cart_id)))?;
+
+    let product = product::Entity::find_by_id(request.product_id)
+        .one(conn)
+        .await?;
+    let product = ensure_purchasable(product, request.product_id, now_fixed)?;
 
-    let inserted_cart_item = cart_item.insert(db).await.map_err(|err| {
-        ApiError::InternalServerError(format!("Failed to add item to cart: {}", err))
-    })?;
+    // Create the cart item in the database
+    let inserted_cart_item = upsert_cart_line(
+        conn,
+        cart_id,
+        request.product_id,
+        request.quantity,
+        product.price,
+        now_fixed,
+    )
+    .await
+    .map_err(|err| ApiError::InternalServerError(format!("Failed to add item to cart: {}", err)))?;
+    check_quantity_limit(&product, inserted_cart_item.quantity)?;
+    touch_cart(conn, cart_id, now_fixed).await?;
 
     // Return the response with cart item details
     Ok(CartItemResponse::from(inserted_cart_item))
 }
 
+/// Load a cart line whose cart belongs to the caller.
+///
+/// Lines of other carts are reported as missing so that IDs cannot be probed.
+pub async fn find_owned_cart_item<C: ConnectionTrait>(
+    conn: &C,
+    caller: &CartCaller,
+    cart_item_id: i32,
+) -> Result<cart_item::Model, ApiError> {
+    cart_item::Entity::find_by_id(cart_item_id)
+        .find_also_related(cart::Entity)
+        .one(conn)
+        .await?
+        .filter(|(_, cart)| cart.as_ref().is_some_and(|cart| caller.owns(cart)))
+        .map(|(cart_item, _)| cart_item)
+        .ok_or_else(|| ApiError::NotFound(format!("Cart item with ID {} not found", cart_item_id)))
+}
+
+/// Service function to add an item to a cart owned by the caller
+pub async fn add_item_to_owned_cart(
+    db: &DatabaseConnection,
+    caller: &CartCaller,
+    cart_id: i32,
+    request: CartItemRequest,
+) -> Result<CartItemResponse, ApiError> {
+    let cart = find_owned_cart(db, caller, cart_id).await?;
+
+    add_item_to_cart(db, cart.id, request).await
+}
+
+/// Service function to add an item to the user's active cart, creating the cart on first add
+pub async fn add_item_to_active_cart(
+    db: &DatabaseConnection,
+    user_id: i32,
+    request: CartItemRequest,
+) -> Result<CartItemResponse, ApiError> {
+    let cart = get_or_create_active_cart(db, user_id, Utc::now().into()).await?;
+
+    add_item_to_cart(db, cart.id, request).await
+}
+
+/// Service function to set the quantity of a cart item.
+///
+/// A quantity of 0 removes the line, in which case `None` is returned.
+pub async fn update_cart_item_quantity(
+    db: &DatabaseConnection,
+    caller: &CartCaller,
+    cart_item_id: i32,
+    request: UpdateCartItemRequest,
+) -> Result<Option<CartItemResponse>, ApiError> {
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let cart_item = find_owned_cart_item(db, caller, cart_item_id).await?;
+
+    if request.quantity == 0 {
+        let cart_id = cart_item.cart_id;
+        cart_item.delete(db).await?;
+        touch_cart(db, cart_id, now_fixed).await?;
+        return Ok(None);
+    }
+
+    let product = product::Entity::find_by_id(cart_item.product_id)
+        .one(db)
+        .await?;
+    let product = ensure_purchasable(product, cart_item.product_id, now_fixed)?;
+    check_quantity_limit(&product, request.quantity)?;
+
+    let mut active_model: cart_item::ActiveModel = cart_item.into();
+    active_model.quantity = Set(request.quantity);
+    active_model.updated_at = Set(now_fixed);
+    let updated_cart_item = active_model.update(db).await?;
+    touch_cart(db, updated_cart_item.cart_id, now_fixed).await?;
+
+    Ok(Some(CartItemResponse::from(updated_cart_item)))
+}
+
 /// Service function to remove an item from the cart
 pub async fn remove_item_from_cart(
     db: &DatabaseConnection,
+    caller: &CartCaller,
     cart_item_id: i32,
 ) -> Result<DeleteResult, ApiError> {
+    find_owned_cart_item(db, caller, cart_item_id).await?;
+
     // Attempt to delete the cart item from the database
     cart_item::Entity::delete_many()
         .filter(cart_item::Column::Id.eq(cart_item_id)) // Apply filter to delete specific cart item
@@ -156,11 +625,47 @@ pub async fn remove_item_from_cart(
         })
 }
 
-/// Service function to clear all items in a cart
-pub async fn clear_cart(db: &DatabaseConnection, cart_id: i32) -> Result<(), ApiError> {
+/// Service function to move a cart line into or out of the "save for later" section
+pub async fn set_saved_for_later(
+    db: &DatabaseConnection,
+    caller: &CartCaller,
+    cart_item_id: i32,
+    saved_for_later: bool,
+) -> Result<CartItemResponse, ApiError> {
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let cart_item = find_owned_cart_item(db, caller, cart_item_id).await?;
+    let product = product::Entity::find_by_id(cart_item.product_id)
+        .one(db)
+        .await?;
+
+    // Only lines that can still be bought may re-enter the purchase
+    if !saved_for_later {
+        let product = ensure_purchasable(product, cart_item.product_id, now_fixed)?;
+        check_quantity_limit(&product, cart_item.quantity)?;
+    }
+
+    let mut active_model: cart_item::ActiveModel = cart_item.into();
+    active_model.saved_for_later = Set(saved_for_later);
+    active_model.updated_at = Set(now_fixed);
+    let updated_cart_item = active_model.update(db).await?;
+    touch_cart(db, updated_cart_item.cart_id, now_fixed).await?;
+
+    Ok(CartItemResponse::from(updated_cart_item))
+}
+
+/// Service function to clear all items in a cart, keeping the ones saved for later
+pub async fn clear_cart(
+    db: &DatabaseConnection,
+    caller: &CartCaller,
+    cart_id: i32,
+) -> Result<(), ApiError> {
+    find_owned_cart(db, caller, cart_id).await?;
+
     // Attempt to delete all items in the cart
     cart_item::Entity::delete_many() // Use delete_many instead of delete
         .filter(cart_item::Column::CartId.eq(cart_id))
+        .filter(cart_item::Column::SavedForLater.eq(false))
         .exec(db)
         .await
         .map_err(|err| ApiError::InternalServerError(format!("Failed to clear cart: {}", err)))?;
@@ -168,51 +673,74 @@ pub async fn clear_cart(db: &DatabaseConnection, cart_id: i32) -> Result<(), Api
     Ok(())
 }
 
-/// Cart handler to create a new cart
-#[actix_web::post("/carts")]
-async fn create_cart_handler(
-    db: web::Data<DatabaseConnection>,
-    request: web::Json<CartRequest>,
-) -> impl Responder {
-    match create_cart(db.get_ref(), request.into_inner()).await {
-        Ok(cart_response) => HttpResponse::Created().json(cart_response),
-        Err(err) => HttpResponse::BadRequest().json(format!("Error: {}", err)),
-    }
-}
-
-/// Cart item handler to add an item to the cart
-#[actix_web::post("/carts/{cart_id}/items")]
-async fn add_item_to_cart_handler(
-    db: web::Data<DatabaseConnection>,
-    cart_id: web::Path<i32>,
-    request: web::Json<CartItemRequest>,
-) -> impl Responder {
-    match add_item_to_cart(db.get_ref(), *cart_id, request.into_inner()).await {
-        Ok(cart_item_response) => HttpResponse::Created().json(cart_item_response),
-        Err(err) => HttpResponse::BadRequest().json(format!("Error: {}", err)),
-    }
-}
-
-/// Cart item handler to remove an item from the cart
-#[actix_web::delete("/carts/items/{cart_item_id}")]
-async fn remove_item_from_cart_handler(
-    db: web::Data<DatabaseConnection>,
-    cart_item_id: web::Path<i32>,
-) -> impl Responder {
-    match remove_item_from_cart(db.get_ref(), *cart_item_id).await {
-        Ok(_) => HttpResponse::Ok().json("Item removed from cart"),
-        Err(err) => HttpResponse::BadRequest().json(format!("Error: {}", err)),
-    }
-}
-
-/// Cart handler to clear all items in the cart
-#[actix_web::delete("/carts/{cart_id}/clear")]
-async fn clear_cart_handler(
-    db: web::Data<DatabaseConnection>,
-    cart_id: web::Path<i32>,
-) -> impl Responder {
-    match clear_cart(db.get_ref(), *cart_id).await {
-        Ok(()) => HttpResponse::Ok().json("Cart cleared"),
-        Err(err) => HttpResponse::BadRequest().json(format!("Error: {}", err)),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::sea_orm_active_enums::ProductStatus;
+
+    #[test]
+    fn added_quantity_must_be_between_one_and_the_line_maximum() {
+        assert!(validate_line_quantity(1).is_ok());
+        assert!(validate_line_quantity(MAX_LINE_QUANTITY).is_ok());
+        assert!(validate_line_quantity(0).is_err());
+        assert!(validate_line_quantity(MAX_LINE_QUANTITY + 1).is_err());
+    }
+
+    #[test]
+    fn updated_quantity_may_be_zero() {
+        assert!(validate_updated_quantity(0).is_ok());
+        assert!(validate_updated_quantity(-1).is_err());
+
+        let error = validate_updated_quantity(MAX_LINE_QUANTITY + 1).unwrap_err();
+        assert_eq!(
+            error.message.as_deref(),
+            Some("Quantity must be between 0 and 999")
+        );
+    }
+
+    fn product(max_quantity: Option<i32>) -> product::Model {
+        let now: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+        product::Model {
+            id: 1,
+            name: "Mug".to_string(),
+            description: None,
+            price: Decimal::ONE_HUNDRED,
+            created_at: now,
+            updated_at: now,
+            category: None,
+            status: ProductStatus::Published,
+            available_from: None,
+            available_until: None,
+            max_quantity,
+            stock: None,
+            weight_grams: None,
+            tax_class_id: None,
+        }
+    }
+
+    #[test]
+    fn merged_lines_are_capped_at_the_lower_of_both_maximums() {
+        assert_eq!(line_quantity_limit(&product(Some(5))), 5);
+        assert_eq!(line_quantity_limit(&product(Some(5000))), MAX_LINE_QUANTITY);
+        assert_eq!(line_quantity_limit(&product(None)), MAX_LINE_QUANTITY);
+    }
+
+    #[test]
+    fn quantity_limit_rejects_merged_quantities_over_the_maximum() {
+        assert!(check_quantity_limit(&product(Some(5)), 5).is_ok());
+        assert!(matches!(
+            check_quantity_limit(&product(Some(5)), 6),
+            Err(ApiError::UnprocessableEntity(_))
+        ));
+        assert!(check_quantity_limit(&product(None), MAX_LINE_QUANTITY + 1).is_err());
+    }
+
+    #[test]
+    fn request_validation_uses_the_line_maximum() {
+        let request = CartItemRequest {
+            product_id: 1,
+            quantity: MAX_LINE_QUANTITY + 1,
+        };
+        assert!(request.validate().is_err());
     }
 }
@@ -0,0 +1,713 @@
+use super::cart_service::find_owned_cart;
+use super::order_service::OrderService;
+use super::pricing_service::{self, split_proportionally, CheckoutRequest};
+use super::tax_service::round_satang;
+use crate::entities::sea_orm_active_enums::CouponKind;
+use crate::entities::{cart, coupon, coupon_category, coupon_product, coupon_redemption, product};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::CartCaller;
+use chrono::{DateTime, FixedOffset, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use validator::{Validate, ValidationError};
+
+/// Struct for creating or updating a coupon (staff only)
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_coupon_request"))]
+pub struct CouponRequest {
+    /// Code customers type in; matched case-insensitively
+    #[validate(length(min = 1, max = 50, message = "Code must be 1-50 characters"))]
+    pub code: String,
+
+    #[validate(length(max = 500, message = "Description cannot exceed 500 characters"))]
+    pub description: Option<String>,
+
+    pub kind: CouponKind,
+
+    /// Percentage off (percentage coupons) or amount off (fixed-amount coupons)
+    pub value: Decimal,
+
+    /// Cart subtotal needed before the code can be used
+    pub min_spend: Option<Decimal>,
+
+    pub starts_at: Option<DateTime<FixedOffset>>,
+    pub ends_at: Option<DateTime<FixedOffset>>,
+
+    /// Redemptions allowed across all customers
+    #[validate(range(min = 1, message = "Usage limit must be at least 1"))]
+    pub usage_limit: Option<i32>,
+
+    /// Redemptions allowed per customer
+    #[validate(range(min = 1, message = "Per-user limit must be at least 1"))]
+    pub per_user_limit: Option<i32>,
+
+    /// Defaults to active on creation and to the current value on update
+    pub active: Option<bool>,
+
+    /// Products the discount is limited to
+    #[serde(default)]
+    pub product_ids: Vec<i32>,
+
+    /// Categories the discount is limited to
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+/// Struct-level validator keeping discounts and validity windows sensible
+fn validate_coupon_request(request: &CouponRequest) -> Result<(), ValidationError> {
+    if request.value <= Decimal::ZERO {
+        let mut error = ValidationError::new("value");
+        error.message = Some("Discount value must be a positive value".into());
+        return Err(error);
+    }
+
+    if request.kind == CouponKind::Percentage && request.value > Decimal::ONE_HUNDRED {
+        let mut error = ValidationError::new("value");
+        error.message = Some("Percentage discounts cannot exceed 100".into());
+        return Err(error);
+    }
+
+    if request
+        .min_spend
+        .is_some_and(|min_spend| min_spend < Decimal::ZERO)
+    {
+        let mut error = ValidationError::new("min_spend");
+        error.message = Some("Minimum spend cannot be negative".into());
+        return Err(error);
+    }
+
+    if let (Some(starts_at), Some(ends_at)) = (request.starts_at, request.ends_at) {
+        if starts_at >= ends_at {
+            let mut error = ValidationError::new("validity_window");
+            error.message = Some("starts_at must be before ends_at".into());
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Struct for applying a code to a cart
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApplyCouponRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be 1-50 characters"))]
+    pub code: String,
+}
+
+/// Struct used to return a coupon
+#[derive(Debug, Serialize)]
+pub struct CouponResponse {
+    pub id: i32,
+    pub code: String,
+    pub description: Option<String>,
+    pub kind: CouponKind,
+    pub value: Decimal,
+    pub min_spend: Option<Decimal>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub usage_limit: Option<i32>,
+    pub per_user_limit: Option<i32>,
+    pub times_redeemed: i32,
+    pub active: bool,
+    pub product_ids: Vec<i32>,
+    pub categories: Vec<String>,
+}
+
+impl From<CartCoupon> for CouponResponse {
+    fn from(applied: CartCoupon) -> Self {
+        let model = applied.coupon;
+        let mut product_ids: Vec<i32> = applied.product_ids.into_iter().collect();
+        product_ids.sort_unstable();
+        let mut categories: Vec<String> = applied.categories.into_iter().collect();
+        categories.sort();
+
+        CouponResponse {
+            id: model.id,
+            code: model.code,
+            description: model.description,
+            kind: model.kind,
+            value: model.value,
+            min_spend: model.min_spend,
+            starts_at: model.starts_at.map(|date| date.to_string()),
+            ends_at: model.ends_at.map(|date| date.to_string()),
+            usage_limit: model.usage_limit,
+            per_user_limit: model.per_user_limit,
+            times_redeemed: model.times_redeemed,
+            active: model.active,
+            product_ids,
+            categories,
+        }
+    }
+}
+
+/// The code applied to a cart and the discount it currently gives
+#[derive(Debug, Serialize)]
+pub struct CartCouponResponse {
+    pub cart_id: i32,
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_amount: Decimal,
+}
+
+/// A coupon with the products and categories it is limited to
+#[derive(Debug, Clone)]
+pub struct CartCoupon {
+    pub coupon: coupon::Model,
+    pub product_ids: HashSet<i32>,
+    pub categories: HashSet<String>,
+}
+
+impl CartCoupon {
+    /// Whether the discount covers a product; unrestricted coupons cover everything
+    pub fn applies_to(&self, product: &product::Model) -> bool {
+        if self.product_ids.is_empty() && self.categories.is_empty() {
+            return true;
+        }
+
+        self.product_ids.contains(&product.id)
+            || product
+                .category
+                .as_ref()
+                .is_some_and(|category| self.categories.contains(category))
+    }
+
+    /// Split the discount over the cart lines it covers.
+    ///
+    /// `lines` holds each product with the amount still to pay for its line;
+    /// the result holds the discount of each line, in the same order. The
+    /// discount is shared out in proportion to those amounts and never
+    /// exceeds them.
+    pub fn allocate(&self, lines: &[(&product::Model, Decimal)]) -> Result<Vec<Decimal>, ApiError> {
+        let code = &self.coupon.code;
+        let subtotal: Decimal = lines.iter().map(|(_, line_total)| *line_total).sum();
+        if let Some(min_spend) = self.coupon.min_spend {
+            if subtotal < min_spend {
+                return Err(ApiError::UnprocessableEntity(format!(
+                    "Spend at least {} to use code {}",
+                    min_spend, code
+                )));
+            }
+        }
+
+        let eligible: Vec<bool> = lines
+            .iter()
+            .map(|(product, _)| self.applies_to(product))
+            .collect();
+        let eligible_total: Decimal = lines
+            .iter()
+            .zip(&eligible)
+            .filter(|(_, eligible)| **eligible)
+            .map(|((_, line_total), _)| *line_total)
+            .sum();
+        if eligible_total <= Decimal::ZERO {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Code {} does not apply to any item in the cart",
+                code
+            )));
+        }
+
+        let discount = match self.coupon.kind {
+            CouponKind::Percentage => {
+                round_satang(eligible_total * self.coupon.value / Decimal::ONE_HUNDRED)
+            }
+            CouponKind::FixedAmount => self.coupon.value.min(eligible_total),
+        };
+
+        let weights: Vec<Decimal> = lines
+            .iter()
+            .zip(&eligible)
+            .map(|((_, line_total), eligible)| {
+                if *eligible {
+                    *line_total
+                } else {
+                    Decimal::ZERO
+                }
+            })
+            .collect();
+        Ok(split_proportionally(discount, &weights))
+    }
+
+    /// What the coupon gives, for the explanation shown on each line
+    pub fn describe(&self) -> String {
+        match self.coupon.kind {
+            CouponKind::Percentage => {
+                format!("{}% off eligible items", self.coupon.value.normalize())
+            }
+            CouponKind::FixedAmount => {
+                format!("{} off, shared across eligible items", self.coupon.value)
+            }
+        }
+    }
+}
+
+/// Load a coupon with its restrictions
+async fn load_cart_coupon<C: ConnectionTrait>(
+    conn: &C,
+    coupon: coupon::Model,
+) -> Result<CartCoupon, ApiError> {
+    let product_ids = coupon_product::Entity::find()
+        .filter(coupon_product::Column::CouponId.eq(coupon.id))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|restriction| restriction.product_id)
+        .collect();
+    let categories = coupon_category::Entity::find()
+        .filter(coupon_category::Column::CouponId.eq(coupon.id))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|restriction| restriction.category)
+        .collect();
+
+    Ok(CartCoupon {
+        coupon,
+        product_ids,
+        categories,
+    })
+}
+
+/// The coupon applied to a cart, if any
+pub async fn find_cart_coupon<C: ConnectionTrait>(
+    conn: &C,
+    cart: &cart::Model,
+) -> Result<Option<CartCoupon>, ApiError> {
+    let Some(coupon_id) = cart.coupon_id else {
+        return Ok(None);
+    };
+
+    match coupon::Entity::find_by_id(coupon_id).one(conn).await? {
+        Some(coupon) => Ok(Some(load_cart_coupon(conn, coupon).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Codes are stored and matched in upper case
+fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+/// Fail with 422 unless the coupon may be used now by the given customer.
+///
+/// The cart contents are checked separately by [`CartCoupon::allocate`].
+pub async fn ensure_redeemable<C: ConnectionTrait>(
+    conn: &C,
+    coupon: &coupon::Model,
+    user_id: Option<i32>,
+    now: DateTime<FixedOffset>,
+) -> Result<(), ApiError> {
+    let code = &coupon.code;
+    if !coupon.active
+        || coupon.starts_at.is_some_and(|starts_at| starts_at > now)
+        || coupon.ends_at.is_some_and(|ends_at| ends_at <= now)
+    {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "Code {} is not valid at this time",
+            code
+        )));
+    }
+
+    if coupon
+        .usage_limit
+        .is_some_and(|limit| coupon.times_redeemed >= limit)
+    {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "Code {} has reached its usage limit",
+            code
+        )));
+    }
+
+    if let (Some(limit), Some(user_id)) = (coupon.per_user_limit, user_id) {
+        if redemptions_by_user(conn, coupon.id, user_id).await? >= limit as u64 {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Code {} has already been used the maximum number of times",
+                code
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of orders a customer placed with a coupon
+async fn redemptions_by_user<C: ConnectionTrait>(
+    conn: &C,
+    coupon_id: i32,
+    user_id: i32,
+) -> Result<u64, ApiError> {
+    Ok(coupon_redemption::Entity::find()
+        .filter(coupon_redemption::Column::CouponId.eq(coupon_id))
+        .filter(coupon_redemption::Column::UserId.eq(user_id))
+        .count(conn)
+        .await?)
+}
+
+/// Count a coupon as used by an order; runs inside the checkout transaction.
+///
+/// The usage counter is only incremented while it is below the limit, in a
+/// single statement, so concurrent checkouts cannot redeem a code more often
+/// than allowed. The row lock taken by that update also serialises the
+/// per-customer check.
+pub async fn redeem<C: ConnectionTrait>(
+    conn: &C,
+    coupon_id: i32,
+    user_id: i32,
+    order_id: i32,
+    amount: Decimal,
+) -> Result<(), ApiError> {
+    let result = coupon::Entity::update_many()
+        .col_expr(
+            coupon::Column::TimesRedeemed,
+            Expr::col(coupon::Column::TimesRedeemed).add(1),
+        )
+        .filter(coupon::Column::Id.eq(coupon_id))
+        .filter(
+            Condition::any()
+                .add(coupon::Column::UsageLimit.is_null())
+                .add(
+                    Expr::col(coupon::Column::TimesRedeemed)
+                        .lt(Expr::col(coupon::Column::UsageLimit)),
+                ),
+        )
+        .exec(conn)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(ApiError::UnprocessableEntity(
+            "The discount code has reached its usage limit".to_string(),
+        ));
+    }
+
+    let coupon = coupon::Entity::find_by_id(coupon_id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Coupon with ID {} not found", coupon_id)))?;
+    if let Some(limit) = coupon.per_user_limit {
+        if redemptions_by_user(conn, coupon_id, user_id).await? >= limit as u64 {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Code {} has already been used the maximum number of times",
+                coupon.code
+            )));
+        }
+    }
+
+    coupon_redemption::ActiveModel {
+        coupon_id: Set(coupon_id),
+        order_id: Set(order_id),
+        user_id: Set(user_id),
+        amount: Set(amount),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Give back the coupon use of a cancelled order
+pub async fn release_redemption<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i32,
+) -> Result<(), ApiError> {
+    let Some(redemption) = coupon_redemption::Entity::find()
+        .filter(coupon_redemption::Column::OrderId.eq(order_id))
+        .one(conn)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    coupon::Entity::update_many()
+        .col_expr(
+            coupon::Column::TimesRedeemed,
+            Expr::col(coupon::Column::TimesRedeemed).sub(1),
+        )
+        .filter(coupon::Column::Id.eq(redemption.coupon_id))
+        .filter(coupon::Column::TimesRedeemed.gt(0))
+        .exec(conn)
+        .await?;
+    coupon_redemption::Entity::delete_by_id(redemption.id)
+        .exec(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Service function to apply a code to a cart the caller owns, replacing any
+/// code applied before
+pub async fn apply_coupon(
+    db: &DatabaseConnection,
+    caller: &CartCaller,
+    cart_id: i32,
+    request: ApplyCouponRequest,
+) -> Result<CartCouponResponse, ApiError> {
+    let txn = db.begin().await?;
+    let cart = find_owned_cart(&txn, caller, cart_id).await?;
+
+    let code = normalize_code(&request.code);
+    let coupon = coupon::Entity::find()
+        .filter(coupon::Column::Code.eq(&code))
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Code {} not found", code)))?;
+    ensure_redeemable(&txn, &coupon, cart.user_id, Utc::now().into()).await?;
+
+    let user_id = cart.user_id;
+    let mut active_model: cart::ActiveModel = cart.into();
+    active_model.coupon_id = Set(Some(coupon.id));
+    active_model.updated_at = Set(Utc::now().into());
+    active_model.update(&txn).await?;
+
+    // Rolled back when the code does not fit the cart
+    let discount_amount = cart_discount(&txn, user_id, cart_id, &coupon.code).await?;
+    txn.commit().await?;
+
+    Ok(CartCouponResponse {
+        cart_id,
+        code: coupon.code,
+        description: coupon.description,
+        discount_amount,
+    })
+}
+
+/// Discount the code applied to a cart gives on its current contents.
+///
+/// The cart goes through the pricing pipeline, so the code is worked out on
+/// current prices after automatic promotions, as it will be at checkout.
+async fn cart_discount<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Option<i32>,
+    cart_id: i32,
+    code: &str,
+) -> Result<Decimal, ApiError> {
+    if OrderService::get_cart_items_for_user(conn, cart_id)
+        .await?
+        .is_empty()
+    {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "Code {} does not apply to any item in the cart",
+            code
+        )));
+    }
+
+    let request = CheckoutRequest {
+        cart_id: Some(cart_id),
+        ..Default::default()
+    };
+    let quote = pricing_service::quote_cart(conn, user_id, cart_id, &request).await?;
+
+    Ok(quote
+        .lines
+        .iter()
+        .flat_map(|line| &line.adjustments)
+        .filter(|adjustment| adjustment.coupon_code.as_deref() == Some(code))
+        .map(|adjustment| adjustment.amount)
+        .sum())
+}
+
+/// Service function to take the code off a cart the caller owns
+pub async fn remove_coupon(
+    db: &DatabaseConnection,
+    caller: &CartCaller,
+    cart_id: i32,
+) -> Result<(), ApiError> {
+    let cart = find_owned_cart(db, caller, cart_id).await?;
+
+    let mut active_model: cart::ActiveModel = cart.into();
+    active_model.coupon_id = Set(None);
+    active_model.updated_at = Set(Utc::now().into());
+    active_model.update(db).await?;
+
+    Ok(())
+}
+
+/// Fail with 409 if another coupon already uses the code
+async fn ensure_code_unused<C: ConnectionTrait>(
+    conn: &C,
+    code: &str,
+    except_id: Option<i32>,
+) -> Result<(), ApiError> {
+    let existing = coupon::Entity::find()
+        .filter(coupon::Column::Code.eq(code))
+        .one(conn)
+        .await?;
+
+    match existing {
+        Some(coupon) if Some(coupon.id) != except_id => Err(ApiError::Conflict(format!(
+            "A coupon with code '{}' already exists",
+            code
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Replace the product and category restrictions of a coupon
+async fn set_restrictions<C: ConnectionTrait>(
+    conn: &C,
+    coupon_id: i32,
+    product_ids: Vec<i32>,
+    categories: Vec<String>,
+) -> Result<(), ApiError> {
+    coupon_product::Entity::delete_many()
+        .filter(coupon_product::Column::CouponId.eq(coupon_id))
+        .exec(conn)
+        .await?;
+    coupon_category::Entity::delete_many()
+        .filter(coupon_category::Column::CouponId.eq(coupon_id))
+        .exec(conn)
+        .await?;
+
+    let product_ids: HashSet<i32> = product_ids.into_iter().collect();
+    if !product_ids.is_empty() {
+        let found = product::Entity::find()
+            .filter(product::Column::Id.is_in(product_ids.iter().copied()))
+            .count(conn)
+            .await?;
+        if found != product_ids.len() as u64 {
+            return Err(ApiError::NotFound(
+                "One or more restricted products do not exist".to_string(),
+            ));
+        }
+
+        coupon_product::Entity::insert_many(product_ids.into_iter().map(|product_id| {
+            coupon_product::ActiveModel {
+                coupon_id: Set(coupon_id),
+                product_id: Set(product_id),
+                ..Default::default()
+            }
+        }))
+        .exec(conn)
+        .await?;
+    }
+
+    let categories: HashSet<String> = categories
+        .into_iter()
+        .map(|category| category.trim().to_string())
+        .filter(|category| !category.is_empty())
+        .collect();
+    if !categories.is_empty() {
+        coupon_category::Entity::insert_many(categories.into_iter().map(|category| {
+            coupon_category::ActiveModel {
+                coupon_id: Set(coupon_id),
+                category: Set(category),
+                ..Default::default()
+            }
+        }))
+        .exec(conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Service function to list every coupon (staff only)
+pub async fn get_all_coupons(db: &DatabaseConnection) -> Result<Vec<CouponResponse>, ApiError> {
+    let coupons = coupon::Entity::find()
+        .order_by_desc(coupon::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let mut product_ids: HashMap<i32, HashSet<i32>> = HashMap::new();
+    for restriction in coupon_product::Entity::find().all(db).await? {
+        product_ids
+            .entry(restriction.coupon_id)
+            .or_default()
+            .insert(restriction.product_id);
+    }
+    let mut categories: HashMap<i32, HashSet<String>> = HashMap::new();
+    for restriction in coupon_category::Entity::find().all(db).await? {
+        categories
+            .entry(restriction.coupon_id)
+            .or_default()
+            .insert(restriction.category);
+    }
+
+    Ok(coupons
+        .into_iter()
+        .map(|coupon| {
+            CouponResponse::from(CartCoupon {
+                product_ids: product_ids.remove(&coupon.id).unwrap_or_default(),
+                categories: categories.remove(&coupon.id).unwrap_or_default(),
+                coupon,
+            })
+        })
+        .collect())
+}
+
+/// Service function to add a coupon
+pub async fn create_coupon(
+    db: &DatabaseConnection,
+    request: CouponRequest,
+) -> Result<CouponResponse, ApiError> {
+    let code = normalize_code(&request.code);
+    let txn = db.begin().await?;
+    ensure_code_unused(&txn, &code, None).await?;
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let coupon = coupon::ActiveModel {
+        code: Set(code),
+        description: Set(request.description),
+        kind: Set(request.kind),
+        value: Set(request.value),
+        min_spend: Set(request.min_spend),
+        starts_at: Set(request.starts_at),
+        ends_at: Set(request.ends_at),
+        usage_limit: Set(request.usage_limit),
+        per_user_limit: Set(request.per_user_limit),
+        times_redeemed: Set(0),
+        active: Set(request.active.unwrap_or(true)),
+        created_at: Set(now_fixed),
+        updated_at: Set(now_fixed),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    set_restrictions(&txn, coupon.id, request.product_ids, request.categories).await?;
+    let coupon = load_cart_coupon(&txn, coupon).await?;
+
+    txn.commit().await?;
+
+    Ok(CouponResponse::from(coupon))
+}
+
+/// Service function to change a coupon; orders already placed keep their discount
+pub async fn update_coupon(
+    db: &DatabaseConnection,
+    coupon_id: i32,
+    request: CouponRequest,
+) -> Result<CouponResponse, ApiError> {
+    let code = normalize_code(&request.code);
+    let txn = db.begin().await?;
+    let existing = coupon::Entity::find_by_id(coupon_id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Coupon with ID {} not found", coupon_id)))?;
+    ensure_code_unused(&txn, &code, Some(coupon_id)).await?;
+
+    let active = request.active.unwrap_or(existing.active);
+    let mut active_model: coupon::ActiveModel = existing.into();
+    active_model.code = Set(code);
+    active_model.description = Set(request.description);
+    active_model.kind = Set(request.kind);
+    active_model.value = Set(request.value);
+    active_model.min_spend = Set(request.min_spend);
+    active_model.starts_at = Set(request.starts_at);
+    active_model.ends_at = Set(request.ends_at);
+    active_model.usage_limit = Set(request.usage_limit);
+    active_model.per_user_limit = Set(request.per_user_limit);
+    active_model.active = Set(active);
+    active_model.updated_at = Set(Utc::now().into());
+    let coupon = active_model.update(&txn).await?;
+    set_restrictions(&txn, coupon.id, request.product_ids, request.categories).await?;
+    let coupon = load_cart_coupon(&txn, coupon).await?;
+
+    txn.commit().await?;
+
+    Ok(CouponResponse::from(coupon))
+}
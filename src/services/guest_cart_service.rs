@@ -0,0 +1,278 @@
+use super::cart_service::{
+    self, build_cart_details, cap_cart_line, find_active_cart, touch_cart, upsert_cart_line,
+    CartDetailResponse, CartItemRequest, CartItemResponse, UpdateCartItemRequest,
+};
+use super::product_service::is_purchasable;
+use crate::entities::{cart, cart_item, product};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::{CartCaller, GuestCartToken};
+use crate::utils::jwt::generate_guest_cart_token;
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use serde::Serialize;
+use std::env;
+
+/// Default idle time after which a guest cart expires (3 days)
+const DEFAULT_GUEST_CART_IDLE_HOURS: i64 = 72;
+
+/// Lifetime of the signed cart token; the idle timeout is enforced separately
+const GUEST_CART_TOKEN_TTL_SECS: i64 = 30 * 24 * 3600;
+
+/// Idle time after which a guest cart expires
+/// (overridable with the `GUEST_CART_IDLE_HOURS` environment variable)
+pub fn guest_cart_idle_timeout() -> Duration {
+    let hours = env::var("GUEST_CART_IDLE_HOURS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(DEFAULT_GUEST_CART_IDLE_HOURS);
+
+    Duration::hours(hours)
+}
+
+/// Struct used to return a newly created guest cart and the token granting access to it
+#[derive(Debug, Serialize)]
+pub struct GuestCartResponse {
+    pub cart_token: String,
+    pub cart: CartDetailResponse,
+}
+
+/// Fetch an unexpired guest cart, deleting it if it has been idle for too long
+async fn find_guest_cart(db: &DatabaseConnection, cart_id: i32) -> Result<cart::Model, ApiError> {
+    let cart = cart::Entity::find_by_id(cart_id)
+        .filter(cart::Column::UserId.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Guest cart not found".to_string()))?;
+
+    if cart.updated_at + guest_cart_idle_timeout() < Utc::now() {
+        let txn = db.begin().await?;
+        cart_item::Entity::delete_many()
+            .filter(cart_item::Column::CartId.eq(cart.id))
+            .exec(&txn)
+            .await?;
+        cart.delete(&txn).await?;
+        txn.commit().await?;
+
+        return Err(ApiError::NotFound("Guest cart has expired".to_string()));
+    }
+
+    Ok(cart)
+}
+
+/// Service function to open a cart for an anonymous visitor
+pub async fn create_guest_cart(
+    db: &DatabaseConnection,
+    locale: &str,
+) -> Result<GuestCartResponse, ApiError> {
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let inserted_cart = cart::ActiveModel {
+        user_id: Set(None),
+        created_at: Set(now_fixed),
+        updated_at: Set(now_fixed),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    let cart_token = generate_guest_cart_token(inserted_cart.id, GUEST_CART_TOKEN_TTL_SECS)
+        .map_err(|_| ApiError::InternalServerError("Failed to sign cart token".to_string()))?;
+
+    Ok(GuestCartResponse {
+        cart_token,
+        cart: build_cart_details(db, inserted_cart, locale).await?,
+    })
+}
+
+/// Service function to read a guest cart
+pub async fn get_guest_cart(
+    db: &DatabaseConnection,
+    cart_id: i32,
+    locale: &str,
+) -> Result<CartDetailResponse, ApiError> {
+    let cart = find_guest_cart(db, cart_id).await?;
+    build_cart_details(db, cart, locale).await
+}
+
+/// Service function to add an item to a guest cart
+pub async fn add_item_to_guest_cart(
+    db: &DatabaseConnection,
+    cart_id: i32,
+    request: CartItemRequest,
+) -> Result<CartItemResponse, ApiError> {
+    let cart = find_guest_cart(db, cart_id).await?;
+    cart_service::add_item_to_cart(db, cart.id, request).await
+}
+
+/// Service function to change the quantity of a guest cart line (0 removes it)
+pub async fn update_guest_cart_item(
+    db: &DatabaseConnection,
+    cart_id: i32,
+    cart_item_id: i32,
+    request: UpdateCartItemRequest,
+) -> Result<Option<CartItemResponse>, ApiError> {
+    let cart = find_guest_cart(db, cart_id).await?;
+    let caller = CartCaller::Guest(GuestCartToken { cart_id: cart.id });
+
+    cart_service::update_cart_item_quantity(db, &caller, cart_item_id, request).await
+}
+
+/// Guest cart lines that can be merged: those whose product still exists and
+/// can still be bought
+fn mergeable_lines(
+    items: Vec<(cart_item::Model, Option<product::Model>)>,
+    now: chrono::DateTime<chrono::FixedOffset>,
+) -> Vec<(cart_item::Model, product::Model)> {
+    items
+        .into_iter()
+        .filter_map(|(item, product)| {
+            product
+                .filter(|product| is_purchasable(product, now))
+                .map(|product| (item, product))
+        })
+        .collect()
+}
+
+/// Move a guest cart into the user's cart after login or registration.
+///
+/// When the user has no cart yet the guest cart simply changes owner.
+/// Otherwise quantities of the same product are summed (up to the product's
+/// maximum quantity), lines whose product can no longer be bought are dropped,
+/// and the guest cart is deleted.
+/// Returns the ID of the user's cart, or `None` if the guest cart is gone
+/// (already merged or expired) so that logging in never fails because of it.
+pub async fn merge_guest_cart(
+    db: &DatabaseConnection,
+    guest_cart_id: i32,
+    user_id: i32,
+) -> Result<Option<i32>, ApiError> {
+    let guest_cart = match find_guest_cart(db, guest_cart_id).await {
+        Ok(guest_cart) => guest_cart,
+        Err(ApiError::NotFound(_)) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let txn = db.begin().await?;
+
+    let user_cart = find_active_cart(&txn, user_id).await?;
+
+    let Some(user_cart) = user_cart else {
+        let mut active_model: cart::ActiveModel = guest_cart.into();
+        active_model.user_id = Set(Some(user_id));
+        active_model.updated_at = Set(now_fixed);
+        let adopted_cart = active_model.update(&txn).await?;
+        txn.commit().await?;
+
+        return Ok(Some(adopted_cart.id));
+    };
+
+    let guest_items = cart_item::Entity::find()
+        .filter(cart_item::Column::CartId.eq(guest_cart.id))
+        .find_also_related(product::Entity)
+        .all(&txn)
+        .await?;
+
+    for (item, product) in mergeable_lines(guest_items, now_fixed) {
+        let line = upsert_cart_line(
+            &txn,
+            user_cart.id,
+            item.product_id,
+            item.quantity,
+            item.unit_price,
+            now_fixed,
+        )
+        .await?;
+        cap_cart_line(&txn, line, &product).await?;
+    }
+
+    cart_item::Entity::delete_many()
+        .filter(cart_item::Column::CartId.eq(guest_cart.id))
+        .exec(&txn)
+        .await?;
+    guest_cart.delete(&txn).await?;
+    touch_cart(&txn, user_cart.id, now_fixed).await?;
+
+    txn.commit().await?;
+
+    Ok(Some(user_cart.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::sea_orm_active_enums::ProductStatus;
+    use rust_decimal::Decimal;
+
+    fn product(id: i32, status: ProductStatus) -> product::Model {
+        let now: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+        product::Model {
+            id,
+            name: format!("Product {}", id),
+            description: None,
+            price: Decimal::ONE_HUNDRED,
+            created_at: now,
+            updated_at: now,
+            category: None,
+            status,
+            available_from: None,
+            available_until: None,
+            max_quantity: None,
+            stock: None,
+            weight_grams: None,
+            tax_class_id: None,
+        }
+    }
+
+    fn item(id: i32, product_id: i32) -> cart_item::Model {
+        let now: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+        cart_item::Model {
+            id,
+            cart_id: 1,
+            product_id,
+            quantity: 1,
+            created_at: now,
+            updated_at: now,
+            saved_for_later: false,
+            unit_price: Decimal::ONE_HUNDRED,
+        }
+    }
+
+    #[test]
+    fn merge_drops_lines_that_can_no_longer_be_bought() {
+        let now = Utc::now().into();
+        let mut ended = product(4, ProductStatus::Published);
+        ended.available_until = Some((Utc::now() - Duration::hours(1)).into());
+        let items = vec![
+            (item(1, 1), Some(product(1, ProductStatus::Published))),
+            (item(2, 2), Some(product(2, ProductStatus::Draft))),
+            (item(3, 3), None),
+            (item(4, 4), Some(ended)),
+            (item(5, 5), Some(product(5, ProductStatus::Unlisted))),
+        ];
+
+        let merged: Vec<i32> = mergeable_lines(items, now)
+            .iter()
+            .map(|(item, _)| item.id)
+            .collect();
+
+        assert_eq!(merged, vec![1, 5]);
+    }
+
+    #[test]
+    fn merge_keeps_lines_once_their_window_opened() {
+        let mut scheduled = product(1, ProductStatus::Published);
+        scheduled.available_from = Some((Utc::now() + Duration::hours(1)).into());
+        let items = vec![(item(1, 1), Some(scheduled))];
+
+        assert!(mergeable_lines(items.clone(), Utc::now().into()).is_empty());
+        assert_eq!(
+            mergeable_lines(items, (Utc::now() + Duration::hours(2)).into()).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::entities::idempotency_key;
+use crate::utils::actix_error::ApiError;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, Set,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::env;
+
+/// Default time a stored response is replayed for (1 day)
+const DEFAULT_IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// How long a key and its response are kept
+/// (overridable with the `IDEMPOTENCY_KEY_TTL_HOURS` environment variable)
+pub fn idempotency_key_ttl() -> Duration {
+    let hours = positive_setting(
+        env::var("IDEMPOTENCY_KEY_TTL_HOURS").ok().as_deref(),
+        DEFAULT_IDEMPOTENCY_KEY_TTL_HOURS,
+    );
+
+    Duration::hours(hours)
+}
+
+/// Default time a key stays claimed by a request that never finished (2 minutes)
+const DEFAULT_IDEMPOTENCY_LEASE_SECS: i64 = 120;
+
+/// How long a running request holds its key; a key left behind by a crashed
+/// request can be reused once this has passed
+/// (overridable with the `IDEMPOTENCY_LEASE_SECS` environment variable)
+pub fn idempotency_lease() -> Duration {
+    let secs = positive_setting(
+        env::var("IDEMPOTENCY_LEASE_SECS").ok().as_deref(),
+        DEFAULT_IDEMPOTENCY_LEASE_SECS,
+    );
+
+    Duration::seconds(secs)
+}
+
+/// Parse a positive setting, falling back to `default` when it is missing or invalid
+fn positive_setting(value: Option<&str>, default: i64) -> i64 {
+    value
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+/// Keys are scoped per operation and user, so two users can share a key value
+pub fn scope(operation: &str, user_id: i32) -> String {
+    format!("{}:{}", operation, user_id)
+}
+
+/// A response recorded for an idempotency key
+#[derive(Debug)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+/// Outcome of claiming an idempotency key
+#[derive(Debug)]
+pub enum IdempotencyState {
+    /// First use of the key: the request must be processed
+    Started,
+    /// The request was already processed: replay its response
+    Replay(StoredResponse),
+}
+
+/// SHA-256 fingerprint of a request, used to detect a key reused for another request
+pub fn fingerprint(request: &impl Serialize) -> String {
+    let bytes = serde_json::to_vec(request).unwrap_or_default();
+    Sha256::digest(&bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Claim `key` for a request, or return the response recorded for it.
+///
+/// Reusing a key with a different request is rejected with 422, and a retry
+/// arriving while the first request is still running gets a 409.
+pub async fn begin(
+    db: &DatabaseConnection,
+    scope: &str,
+    key: &str,
+    fingerprint: &str,
+) -> Result<IdempotencyState, ApiError> {
+    let now: DateTime<FixedOffset> = Utc::now().into();
+
+    // Expired keys, and keys whose request stopped before recording its
+    // response, may be reused for new requests
+    idempotency_key::Entity::delete_many()
+        .filter(idempotency_key::Column::ExpiresAt.lt(now))
+        .exec(db)
+        .await?;
+
+    let claimed = idempotency_key::Entity::insert(idempotency_key::ActiveModel {
+        scope: Set(scope.to_owned()),
+        key: Set(key.to_owned()),
+        fingerprint: Set(fingerprint.to_owned()),
+        response_status: Set(None),
+        response_body: Set(None),
+        created_at: Set(now),
+        // Extended to the full TTL once the response is recorded
+        expires_at: Set(now + idempotency_lease()),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::columns([idempotency_key::Column::Scope, idempotency_key::Column::Key])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+
+    if claimed > 0 {
+        return Ok(IdempotencyState::Started);
+    }
+
+    let existing = idempotency_key::Entity::find()
+        .filter(idempotency_key::Column::Scope.eq(scope))
+        .filter(idempotency_key::Column::Key.eq(key))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ApiError::Conflict("The idempotency key was released; retry the request".to_string())
+        })?;
+
+    if existing.fingerprint != fingerprint {
+        return Err(ApiError::UnprocessableEntity(
+            "Idempotency key was already used with a different request".to_string(),
+        ));
+    }
+
+    match (existing.response_status, existing.response_body) {
+        (Some(status), Some(body)) => Ok(IdempotencyState::Replay(StoredResponse {
+            status: status as u16,
+            body,
+        })),
+        _ => Err(ApiError::Conflict(
+            "A request with this idempotency key is still being processed".to_string(),
+        )),
+    }
+}
+
+/// Record the response of a request so that retries replay it
+pub async fn complete(
+    db: &DatabaseConnection,
+    scope: &str,
+    key: &str,
+    response: StoredResponse,
+) -> Result<(), DbErr> {
+    let Some(existing) = idempotency_key::Entity::find()
+        .filter(idempotency_key::Column::Scope.eq(scope))
+        .filter(idempotency_key::Column::Key.eq(key))
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let now: DateTime<FixedOffset> = Utc::now().into();
+    let mut active_model: idempotency_key::ActiveModel = existing.into();
+    active_model.response_status = Set(Some(response.status as i16));
+    active_model.response_body = Set(Some(response.body));
+    active_model.expires_at = Set(now + idempotency_key_ttl());
+    active_model.update(db).await?;
+
+    Ok(())
+}
+
+/// Free a key whose request did not succeed so the client can retry with it
+pub async fn release(db: &DatabaseConnection, scope: &str, key: &str) -> Result<(), DbErr> {
+    idempotency_key::Entity::delete_many()
+        .filter(idempotency_key::Column::Scope.eq(scope))
+        .filter(idempotency_key::Column::Key.eq(key))
+        .filter(idempotency_key::Column::ResponseStatus.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+    use serde_json::json;
+
+    fn stored_key(
+        fingerprint: &str,
+        response: Option<(i16, serde_json::Value)>,
+    ) -> idempotency_key::Model {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        let (response_status, response_body) = match response {
+            Some((status, body)) => (Some(status), Some(body)),
+            None => (None, None),
+        };
+        idempotency_key::Model {
+            id: 1,
+            scope: "checkout:7".to_string(),
+            key: "key-1".to_string(),
+            fingerprint: fingerprint.to_string(),
+            response_status,
+            response_body,
+            created_at: now,
+            expires_at: now + idempotency_lease(),
+        }
+    }
+
+    /// A connection where the key is already claimed (the insert affects no rows)
+    fn claimed_db(existing: Vec<idempotency_key::Model>) -> DatabaseConnection {
+        let exec = |rows_affected| MockExecResult {
+            last_insert_id: 0,
+            rows_affected,
+        };
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([exec(0), exec(0)])
+            .append_query_results([existing])
+            .into_connection()
+    }
+
+    #[test]
+    fn fingerprint_is_stable_for_equal_requests() {
+        let request = json!({ "cart_id": 3, "coupon_code": "SAVE10" });
+
+        assert_eq!(fingerprint(&request), fingerprint(&request.clone()));
+        assert_eq!(fingerprint(&request).len(), 64);
+    }
+
+    #[test]
+    fn fingerprint_differs_for_different_requests() {
+        assert_ne!(
+            fingerprint(&json!({ "cart_id": 3 })),
+            fingerprint(&json!({ "cart_id": 4 }))
+        );
+    }
+
+    #[test]
+    fn positive_setting_uses_valid_overrides() {
+        assert_eq!(positive_setting(Some("300"), 120), 300);
+    }
+
+    #[test]
+    fn positive_setting_falls_back_on_missing_or_invalid_values() {
+        assert_eq!(positive_setting(None, 120), 120);
+        assert_eq!(positive_setting(Some("soon"), 120), 120);
+        assert_eq!(positive_setting(Some("0"), 120), 120);
+        assert_eq!(positive_setting(Some("-5"), 120), 120);
+    }
+
+    #[test]
+    fn scope_separates_operations_and_users() {
+        assert_eq!(scope("checkout", 7), "checkout:7");
+        assert_ne!(scope("checkout", 7), scope("checkout", 8));
+        assert_ne!(scope("checkout", 7), scope("refund", 7));
+    }
+
+    #[tokio::test]
+    async fn begin_starts_a_request_for_an_unused_key() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 0,
+                },
+                MockExecResult {
+                    last_insert_id: 1,
+                    rows_affected: 1,
+                },
+            ])
+            .into_connection();
+
+        let state = begin(&db, "checkout:7", "key-1", "abc").await.unwrap();
+
+        assert!(matches!(state, IdempotencyState::Started));
+    }
+
+    #[tokio::test]
+    async fn begin_replays_a_recorded_response() {
+        let db = claimed_db(vec![stored_key("abc", Some((201, json!({ "id": 9 }))))]);
+
+        let state = begin(&db, "checkout:7", "key-1", "abc").await.unwrap();
+
+        match state {
+            IdempotencyState::Replay(stored) => {
+                assert_eq!(stored.status, 201);
+                assert_eq!(stored.body, json!({ "id": 9 }));
+            }
+            IdempotencyState::Started => panic!("expected a replay"),
+        }
+    }
+
+    #[tokio::test]
+    async fn begin_rejects_a_key_reused_for_another_request() {
+        let db = claimed_db(vec![stored_key("abc", Some((201, json!({ "id": 9 }))))]);
+
+        let result = begin(&db, "checkout:7", "key-1", "other").await;
+
+        assert!(matches!(result, Err(ApiError::UnprocessableEntity(_))));
+    }
+
+    #[tokio::test]
+    async fn begin_conflicts_while_the_lease_is_held() {
+        let db = claimed_db(vec![stored_key("abc", None)]);
+
+        let result = begin(&db, "checkout:7", "key-1", "abc").await;
+
+        assert!(
+            matches!(result, Err(ApiError::Conflict(message)) if message.contains("still being processed"))
+        );
+    }
+
+    #[tokio::test]
+    async fn begin_conflicts_when_the_key_was_released_meanwhile() {
+        let db = claimed_db(vec![]);
+
+        let result = begin(&db, "checkout:7", "key-1", "abc").await;
+
+        assert!(matches!(result, Err(ApiError::Conflict(message)) if message.contains("released")));
+    }
+}
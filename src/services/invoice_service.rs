@@ -0,0 +1,418 @@
+use super::shipping_service::ShippingAddress;
+use crate::entities::sea_orm_active_enums::{OrderStatus, PaymentStatus, RefundStatus};
+use crate::entities::{invoice, invoice_sequence, order, order_item, product, refund, user};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::invoice_pdf::{self, InvoiceDocument, InvoiceLine, InvoiceSeller};
+use crate::utils::prompt_pay::PromptPayUtils;
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+
+/// Font used when `INVOICE_FONT_PATH` is not set (Thai TLWG fonts package)
+const DEFAULT_INVOICE_FONT: &str = "/usr/share/fonts/truetype/tlwg/Garuda.ttf";
+
+/// Invoices are dated in Thai time (UTC+7)
+const THAI_UTC_OFFSET_SECS: i32 = 7 * 3600;
+
+/// A rendered invoice, ready to be sent to the client
+pub struct InvoicePdf {
+    pub invoice_number: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Struct used to return an issued invoice
+#[derive(Debug, Serialize)]
+pub struct InvoiceResponse {
+    pub order_id: i32,
+    pub invoice_number: String,
+    pub fiscal_year: i32,
+    pub issued_at: String,
+}
+
+impl From<invoice::Model> for InvoiceResponse {
+    fn from(invoice: invoice::Model) -> Self {
+        Self {
+            order_id: invoice.order_id,
+            invoice_number: invoice.invoice_number,
+            fiscal_year: invoice.fiscal_year,
+            issued_at: invoice.issued_at.to_string(),
+        }
+    }
+}
+
+/// Seller details printed on every invoice, from the `SELLER_*` environment variables.
+///
+/// A tax invoice is not valid without the seller's tax ID, so `SELLER_TAX_ID`
+/// must be set.
+fn seller_from_env() -> Result<InvoiceSeller, ApiError> {
+    let tax_id = env::var("SELLER_TAX_ID")
+        .ok()
+        .filter(|tax_id| !tax_id.trim().is_empty())
+        .ok_or_else(|| {
+            ApiError::InternalServerError("SELLER_TAX_ID must be set to issue invoices".to_string())
+        })?;
+
+    Ok(InvoiceSeller {
+        name: env::var("SELLER_NAME").unwrap_or_else(|_| "Rust Shop".to_string()),
+        tax_id,
+        branch: env::var("SELLER_BRANCH")
+            .unwrap_or_else(|_| "สำนักงานใหญ่ / Head office".to_string()),
+        address: env::var("SELLER_ADDRESS")
+            .unwrap_or_default()
+            .replace("\\n", "\n"),
+    })
+}
+
+/// First month of the fiscal year (overridable with `FISCAL_YEAR_START_MONTH`)
+fn fiscal_year_start_month() -> u32 {
+    env::var("FISCAL_YEAR_START_MONTH")
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|month| (1..=12).contains(month))
+        .unwrap_or(1)
+}
+
+/// Fiscal year a date falls in, named after the calendar year it ends in
+/// (with an October start, 15 Nov 2024 belongs to fiscal year 2025)
+pub fn fiscal_year(date: DateTime<FixedOffset>, start_month: u32) -> i32 {
+    if start_month > 1 && date.month() >= start_month {
+        date.year() + 1
+    } else {
+        date.year()
+    }
+}
+
+/// Prefix of invoice numbers (overridable with `INVOICE_NUMBER_PREFIX`)
+fn invoice_number_prefix() -> String {
+    env::var("INVOICE_NUMBER_PREFIX").unwrap_or_else(|_| "INV".to_string())
+}
+
+/// Printed invoice number, e.g. `INV-2025-000042`
+fn format_invoice_number(prefix: &str, fiscal_year: i32, sequence_number: i32) -> String {
+    format!("{}-{}-{:06}", prefix, fiscal_year, sequence_number)
+}
+
+/// Issue the invoice of an order, or return the one already issued.
+///
+/// `order` must be locked by the caller's transaction; the fiscal-year
+/// counter is bumped in that same transaction as the insert, so numbers are
+/// sequential and a rollback never leaves a gap.
+pub async fn issue_invoice<C: ConnectionTrait>(
+    conn: &C,
+    order: &order::Model,
+) -> Result<invoice::Model, ApiError> {
+    if let Some(existing) = invoice::Entity::find()
+        .filter(invoice::Column::OrderId.eq(order.id))
+        .one(conn)
+        .await?
+    {
+        return Ok(existing);
+    }
+
+    if order.status == OrderStatus::Cancelled {
+        return Err(ApiError::Conflict(format!(
+            "Order {} was cancelled before an invoice was issued",
+            order.id
+        )));
+    }
+
+    let now_fixed: DateTime<FixedOffset> = Utc::now().into();
+    let fiscal_year = fiscal_year(thai_time(now_fixed), fiscal_year_start_month());
+    let sequence_number = next_sequence_number(conn, fiscal_year).await?;
+
+    let issued = invoice::ActiveModel {
+        order_id: Set(order.id),
+        fiscal_year: Set(fiscal_year),
+        sequence_number: Set(sequence_number),
+        invoice_number: Set(format_invoice_number(
+            &invoice_number_prefix(),
+            fiscal_year,
+            sequence_number,
+        )),
+        issued_at: Set(now_fixed),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+
+    Ok(issued)
+}
+
+/// Service function to issue the invoice of an order before it is paid
+/// (staff or the customer who placed it); paid orders get theirs on payment
+pub async fn issue_order_invoice(
+    db: &DatabaseConnection,
+    user: &AuthenticatedUser,
+    order_id: i32,
+) -> Result<InvoiceResponse, ApiError> {
+    let txn = db.begin().await?;
+
+    let order = order::Entity::find_by_id(order_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .filter(|order| user.is_staff() || order.user_id == user.id)
+        .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
+
+    let invoice = issue_invoice(&txn, &order).await?;
+    txn.commit().await?;
+
+    Ok(InvoiceResponse::from(invoice))
+}
+
+/// Take the next number of a fiscal year; the row stays locked until the
+/// transaction ends, so concurrent invoices queue up behind it
+async fn next_sequence_number<C: ConnectionTrait>(
+    conn: &C,
+    fiscal_year: i32,
+) -> Result<i32, ApiError> {
+    let counter = invoice_sequence::Entity::insert(invoice_sequence::ActiveModel {
+        fiscal_year: Set(fiscal_year),
+        last_number: Set(1),
+    })
+    .on_conflict(
+        OnConflict::column(invoice_sequence::Column::FiscalYear)
+            .value(
+                invoice_sequence::Column::LastNumber,
+                Expr::col((
+                    invoice_sequence::Entity,
+                    invoice_sequence::Column::LastNumber,
+                ))
+                .add(1),
+            )
+            .to_owned(),
+    )
+    .exec_with_returning(conn)
+    .await?;
+
+    Ok(counter.last_number)
+}
+
+fn thai_time(date: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    let offset = FixedOffset::east_opt(THAI_UTC_OFFSET_SECS).expect("valid UTC offset");
+    date.with_timezone(&offset)
+}
+
+/// Render the issued invoice (or receipt, once paid) of an order as a PDF.
+///
+/// Customers can only fetch their own orders; staff can fetch any order.
+/// Nothing is issued here: orders without an invoice are reported as missing.
+pub async fn get_order_invoice(
+    db: &DatabaseConnection,
+    user: &AuthenticatedUser,
+    order_id: i32,
+) -> Result<InvoicePdf, ApiError> {
+    let order = order::Entity::find_by_id(order_id)
+        .one(db)
+        .await?
+        .filter(|order| user.is_staff() || order.user_id == user.id)
+        .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
+
+    let invoice = invoice::Entity::find()
+        .filter(invoice::Column::OrderId.eq(order_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No invoice has been issued for order {} yet",
+                order_id
+            ))
+        })?;
+
+    let seller = seller_from_env()?;
+    let document = build_document(db, &order, &invoice, seller).await?;
+
+    let font_path =
+        env::var("INVOICE_FONT_PATH").unwrap_or_else(|_| DEFAULT_INVOICE_FONT.to_string());
+    let bytes = invoice_pdf::render_invoice(&document, &font_path).map_err(|e| {
+        ApiError::InternalServerError(format!(
+            "Failed to render invoice {} (font {}): {}",
+            invoice.invoice_number, font_path, e
+        ))
+    })?;
+
+    Ok(InvoicePdf {
+        invoice_number: invoice.invoice_number,
+        bytes,
+    })
+}
+
+/// Gather what is printed on the invoice of an order
+async fn build_document(
+    db: &DatabaseConnection,
+    order: &order::Model,
+    invoice: &invoice::Model,
+    seller: InvoiceSeller,
+) -> Result<InvoiceDocument, ApiError> {
+    let items = order_item::Entity::find()
+        .filter(order_item::Column::OrderId.eq(order.id))
+        .all(db)
+        .await?;
+
+    let product_ids: Vec<i32> = items.iter().map(|item| item.product_id).collect();
+    let product_names: HashMap<i32, String> = product::Entity::find()
+        .filter(product::Column::Id.is_in(product_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|product| (product.id, product.name))
+        .collect();
+
+    let lines = items
+        .iter()
+        .map(|item| InvoiceLine {
+            description: product_names
+                .get(&item.product_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Product #{}", item.product_id)),
+            quantity: item.quantity,
+            unit_price: item.price,
+            discount: item.discount_amount,
+            tax_rate: item.tax_rate,
+            amount: item.gross_amount,
+        })
+        .collect();
+
+    let buyer_email = user::Entity::find_by_id(order.user_id)
+        .one(db)
+        .await?
+        .map(|user| user.email);
+    let mut buyer = order
+        .shipping_address
+        .clone()
+        .and_then(|address| serde_json::from_value::<ShippingAddress>(address).ok())
+        .map(buyer_lines)
+        .unwrap_or_default();
+    buyer.extend(buyer_email);
+
+    // Refunds do not change the tax invoice; they are documented by a credit note
+    let (title, notes) = match order.payment_status {
+        PaymentStatus::Pending => ("ใบแจ้งหนี้ / Invoice", Vec::new()),
+        PaymentStatus::Paid => ("ใบเสร็จรับเงิน / ใบกำกับภาษี  Receipt / Tax Invoice", Vec::new()),
+        PaymentStatus::PartiallyRefunded | PaymentStatus::Refunded => {
+            let refunded: Decimal = refund::Entity::find()
+                .filter(refund::Column::OrderId.eq(order.id))
+                .filter(refund::Column::Status.eq(RefundStatus::Completed))
+                .all(db)
+                .await?
+                .iter()
+                .map(|refund| refund.amount)
+                .sum();
+            let status = if order.payment_status == PaymentStatus::Refunded {
+                "คืนเงินเต็มจำนวน / Fully refunded"
+            } else {
+                "คืนเงินบางส่วน / Partially refunded"
+            };
+            (
+                "ใบเสร็จรับเงิน / ใบกำกับภาษี  Receipt / Tax Invoice",
+                vec![
+                    format!("{}: {}", status, refunded),
+                    "ยอดที่คืนออกเป็นใบลดหนี้แยกต่างหาก / Refunds are documented by a separate credit note"
+                        .to_string(),
+                ],
+            )
+        }
+    };
+
+    Ok(InvoiceDocument {
+        title: title.to_string(),
+        invoice_number: invoice.invoice_number.clone(),
+        issued_on: thai_time(invoice.issued_at).format("%d/%m/%Y").to_string(),
+        order_id: order.id,
+        seller,
+        buyer,
+        lines,
+        discount: order.discount_amount,
+        shipping: order.shipping_cost,
+        net: order.net_amount,
+        tax: order.tax_amount,
+        total: order.total_amount,
+        promptpay_payload: promptpay_payload(order),
+        notes,
+    })
+}
+
+fn buyer_lines(address: ShippingAddress) -> Vec<String> {
+    let mut lines = vec![address.recipient_name, address.address_line1];
+    lines.extend(address.address_line2.filter(|line| !line.is_empty()));
+    lines.push(format!(
+        "{} {} {}",
+        address.city, address.province, address.postal_code
+    ));
+    lines.extend(address.phone.map(|phone| format!("Tel. {}", phone)));
+    lines
+}
+
+/// PromptPay payload for the amount still owed, if the order awaits payment
+fn promptpay_payload(order: &order::Model) -> Option<String> {
+    if order.status != OrderStatus::PendingPayment || order.payment_status != PaymentStatus::Pending
+    {
+        return None;
+    }
+
+    let phone_number = env::var("My_PHONE_NUMBER").ok()?;
+    let amount = order.total_amount.to_f64()?;
+    match PromptPayUtils::generate_payload(phone_number, amount) {
+        Ok(payload) => Some(payload),
+        Err(e) => {
+            tracing::warn!(
+                order_id = order.id,
+                "Skipping PromptPay QR on invoice: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn thai_date(year: i32, month: u32, day: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(THAI_UTC_OFFSET_SECS)
+            .unwrap()
+            .with_ymd_and_hms(year, month, day, 12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn fiscal_year_follows_the_calendar_year_by_default() {
+        assert_eq!(fiscal_year(thai_date(2024, 1, 1), 1), 2024);
+        assert_eq!(fiscal_year(thai_date(2024, 12, 31), 1), 2024);
+    }
+
+    #[test]
+    fn fiscal_year_is_named_after_the_year_it_ends_in() {
+        assert_eq!(fiscal_year(thai_date(2024, 9, 30), 10), 2024);
+        assert_eq!(fiscal_year(thai_date(2024, 10, 1), 10), 2025);
+        assert_eq!(fiscal_year(thai_date(2024, 11, 15), 10), 2025);
+    }
+
+    #[test]
+    fn thai_time_moves_late_utc_evenings_to_the_next_day() {
+        let utc_evening: DateTime<FixedOffset> =
+            Utc.with_ymd_and_hms(2024, 12, 31, 18, 0, 0).unwrap().into();
+
+        assert_eq!(fiscal_year(thai_time(utc_evening), 1), 2025);
+    }
+
+    #[test]
+    fn invoice_numbers_are_zero_padded() {
+        assert_eq!(format_invoice_number("INV", 2025, 42), "INV-2025-000042");
+        assert_eq!(
+            format_invoice_number("TAX", 2025, 1234567),
+            "TAX-2025-1234567"
+        );
+    }
+}
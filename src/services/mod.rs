@@ -1,5 +1,19 @@
+pub mod cart_abandonment_service;
 pub mod cart_service;
+pub mod coupon_service;
+pub mod guest_cart_service;
+pub mod idempotency_service;
+pub mod invoice_service;
 pub mod order_service;
+pub mod pricing_service;
 pub mod product_service;
-pub mod service_error;
+pub mod promotion_service;
+pub mod recommendation_service;
+pub mod refund_service;
+pub mod saved_cart_service;
+pub mod shared_cart_service;
+pub mod shipping_service;
+pub mod tax_service;
+pub mod translation_service;
 pub mod user_service;
+pub mod wishlist_service;
@@ -1,17 +1,32 @@
-use super::cart_service::{CartItemResponse, CartResponse};
-use crate::entities::{cart, cart_item, order, order_item};
+use super::cart_abandonment_service::mark_cart_recovered;
+use super::cart_service::{
+    check_quantity_limit, ensure_purchasable, find_active_cart, CartItemResponse, CartResponse,
+};
+use super::coupon_service;
+use super::invoice_service;
+use super::pricing_service::{
+    self, CheckoutRequest, PriceAdjustment, PriceChangesResponse, QuoteLine,
+};
+use super::shipping_service::ShippingAddress;
+use crate::entities::sea_orm_active_enums::{
+    CartKind, FulfilmentStatus, OrderStatus, PaymentStatus,
+};
+use crate::entities::{
+    cart, cart_item, invoice, order, order_item, product, refund, refund_item, user,
+};
 use crate::utils::actix_error::ApiError;
-use crate::utils::prompt_pay::PromptPayUtils; // Import the PromptPay utility
-use chrono::Utc;
-use dotenvy::dotenv;
-use rust_decimal::prelude::ToPrimitive;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::payment_provider::PaymentProvider;
+use chrono::{DateTime, FixedOffset, Utc};
 use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
-    TransactionTrait,
+    sea_query::{extension::postgres::PgExpr, Expr},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, ItemsAndPagesNumber, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+    QueryTrait, Set, TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
-use std::env;
+use std::collections::HashMap;
 use validator::{Validate, ValidationError};
 
 /// Struct for creating an order
@@ -22,15 +37,27 @@ pub struct CreateOrderRequest {
 
     #[validate(length(min = 1, message = "Order must contain at least one item"))]
     pub items: Vec<OrderItemRequest>,
-}
 
-/// Struct representing an item in an order
-#[derive(Debug, Serialize, Deserialize)]
-pub struct OrderItem {
-    pub product_id: i32,
-    pub quantity: i32,
-    pub price: f64, // You might also want to store the price at the time of the order
-    pub total: f64, // This can be derived as quantity * price
+    pub shipping_method_id: Option<i32>,
+
+    /// Shipping charged on top of the items, included in the total
+    pub shipping_cost: Decimal,
+
+    #[validate(nested)]
+    pub shipping_address: Option<ShippingAddress>,
+
+    /// Discount code used, redeemed when the order is placed
+    pub coupon_id: Option<i32>,
+
+    /// Discount taken off the items, already reflected in the totals
+    pub discount_amount: Decimal,
+
+    /// Order totals excluding and of VAT, computed by the tax engine
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+
+    /// Grand total computed by the pricing pipeline
+    pub total_amount: Decimal,
 }
 
 /// Struct for individual order items in the create request
@@ -47,13 +74,160 @@ pub struct OrderItemRequest {
         message = "Price must be a positive value"
     ))]
     pub price: Decimal,
+
+    /// Discount taken off the whole line
+    pub discount_amount: Decimal,
+
+    /// Promotions and codes behind the discount
+    #[serde(default)]
+    pub adjustments: Vec<PriceAdjustment>,
+
+    /// VAT percentage and amounts of the whole line
+    pub tax_rate: Decimal,
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub gross_amount: Decimal,
+}
+
+/// Result of a checkout attempt
+#[derive(Debug)]
+pub enum CheckoutOutcome {
+    Placed(OrderModel),
+    /// Nothing was written; the client has to confirm the new prices
+    PricesChanged(PriceChangesResponse),
 }
 
 /// Struct for updating payment status
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct UpdatePaymentStatusRequest {
-    #[validate(length(min = 1, message = "Payment status cannot be empty"))]
-    pub new_status: String,
+    pub new_status: PaymentStatus,
+}
+
+/// Struct for cancelling an order; staff must give a reason
+#[derive(Debug, Deserialize, Validate)]
+pub struct CancelOrderRequest {
+    #[validate(length(min = 1, max = 500, message = "Reason must be 1-500 characters"))]
+    pub reason: Option<String>,
+}
+
+/// Struct for moving an order to another status (staff only)
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateOrderStatusRequest {
+    pub status: OrderStatus,
+}
+
+/// Sort orders of order listings
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSort {
+    #[default]
+    Newest,
+    Oldest,
+    TotalAsc,
+    TotalDesc,
+}
+
+/// Query parameters of a customer's order history
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_order_list_dates"))]
+pub struct OrderListQuery {
+    #[validate(range(min = 1, message = "page must be at least 1"))]
+    pub page: Option<u64>,
+    #[validate(range(min = 1, max = 100, message = "per_page must be between 1 and 100"))]
+    pub per_page: Option<u64>,
+    pub status: Option<OrderStatus>,
+    /// Orders placed at or after this time
+    pub from: Option<DateTime<FixedOffset>>,
+    /// Orders placed before this time
+    pub to: Option<DateTime<FixedOffset>>,
+    pub sort: Option<OrderSort>,
+}
+
+/// Query parameters of the staff order search
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_order_search_dates"))]
+pub struct OrderSearchQuery {
+    #[validate(range(min = 1, message = "page must be at least 1"))]
+    pub page: Option<u64>,
+    #[validate(range(min = 1, max = 100, message = "per_page must be between 1 and 100"))]
+    pub per_page: Option<u64>,
+    pub status: Option<OrderStatus>,
+    pub from: Option<DateTime<FixedOffset>>,
+    pub to: Option<DateTime<FixedOffset>>,
+    pub sort: Option<OrderSort>,
+    /// Part of the customer's e-mail address (case-insensitive)
+    #[validate(length(min = 1, message = "email cannot be empty"))]
+    pub email: Option<String>,
+    pub order_id: Option<i32>,
+    /// Exact order total
+    pub amount: Option<Decimal>,
+}
+
+/// Filters shared by the customer listing and the staff search
+#[derive(Debug, Default)]
+struct OrderFilter {
+    page: Option<u64>,
+    per_page: Option<u64>,
+    status: Option<OrderStatus>,
+    from: Option<DateTime<FixedOffset>>,
+    to: Option<DateTime<FixedOffset>>,
+    sort: Option<OrderSort>,
+    user_id: Option<i32>,
+    email: Option<String>,
+    order_id: Option<i32>,
+    amount: Option<Decimal>,
+}
+
+/// Default number of orders per page
+const DEFAULT_ORDERS_PER_PAGE: u64 = 20;
+
+/// Summary of an order in listings
+#[derive(Debug, Serialize)]
+pub struct OrderSummary {
+    pub id: i32,
+    pub user_id: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    pub status: OrderStatus,
+    pub payment_status: PaymentStatus,
+    pub fulfilment_status: FulfilmentStatus,
+    pub total_amount: Decimal,
+    /// Total number of units ordered
+    pub item_count: i32,
+    pub created_at: String,
+}
+
+/// One page of order summaries
+#[derive(Debug, Serialize)]
+pub struct OrderPage {
+    pub orders: Vec<OrderSummary>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_items: u64,
+    pub total_pages: u64,
+}
+
+/// Struct-level validator making sure the date range is not empty
+fn validate_date_range(
+    from: Option<DateTime<FixedOffset>>,
+    to: Option<DateTime<FixedOffset>>,
+) -> Result<(), ValidationError> {
+    if let (Some(from), Some(to)) = (from, to) {
+        if from >= to {
+            let mut error = ValidationError::new("date_range");
+            error.message = Some("from must be before to".into());
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+fn validate_order_list_dates(query: &OrderListQuery) -> Result<(), ValidationError> {
+    validate_date_range(query.from, query.to)
+}
+
+fn validate_order_search_dates(query: &OrderSearchQuery) -> Result<(), ValidationError> {
+    validate_date_range(query.from, query.to)
 }
 
 /// Custom validator for price to ensure it is positive
@@ -69,25 +243,65 @@ fn validate_price(price: &Decimal) -> Result<(), ValidationError> {
 pub struct OrderModel {
     pub id: i32,
     pub user_id: i32,
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
     pub total_amount: Decimal,
-    pub payment_status: String,
+    pub status: OrderStatus,
+    pub payment_status: PaymentStatus,
+    pub fulfilment_status: FulfilmentStatus,
+    pub cancelled_at: Option<String>,
+    pub cancellation_reason: Option<String>,
+    pub shipping_method_id: Option<i32>,
+    pub shipping_cost: Decimal,
+    pub shipping_address: Option<serde_json::Value>,
+    pub coupon_id: Option<i32>,
+    pub discount_amount: Decimal,
     pub created_at: String,
     pub updated_at: String,
 }
 
-impl OrderModel {
-    fn new(id: i32, user_id: i32, total_amount: Decimal, payment_status: String) -> Self {
+impl From<order::Model> for OrderModel {
+    fn from(model: order::Model) -> Self {
         OrderModel {
-            id,
-            user_id,
-            total_amount,
-            payment_status,
-            created_at: Utc::now().to_string(),
-            updated_at: Utc::now().to_string(),
+            id: model.id,
+            user_id: model.user_id,
+            net_amount: model.net_amount,
+            tax_amount: model.tax_amount,
+            total_amount: model.total_amount,
+            status: model.status,
+            payment_status: model.payment_status,
+            fulfilment_status: model.fulfilment_status,
+            cancelled_at: model.cancelled_at.map(|date| date.to_string()),
+            cancellation_reason: model.cancellation_reason,
+            shipping_method_id: model.shipping_method_id,
+            shipping_cost: model.shipping_cost,
+            shipping_address: model.shipping_address,
+            coupon_id: model.coupon_id,
+            discount_amount: model.discount_amount,
+            created_at: model.created_at.to_string(),
+            updated_at: model.updated_at.to_string(),
         }
     }
 }
 
+/// Whether an order may move from one status to another
+pub fn is_allowed_transition(from: OrderStatus, to: OrderStatus) -> bool {
+    use OrderStatus::*;
+
+    matches!(
+        (from, to),
+        (PendingPayment, Paid)
+            | (PendingPayment, Cancelled)
+            | (Paid, Processing)
+            | (Paid, Refunded)
+            | (Processing, Shipped)
+            | (Processing, Refunded)
+            | (Shipped, Delivered)
+            | (Shipped, Refunded)
+            | (Delivered, Refunded)
+    )
+}
+
 /// Custom Order Item model structure
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OrderItemModel {
@@ -96,20 +310,33 @@ pub struct OrderItemModel {
     pub product_id: i32,
     pub quantity: i32,
     pub price: Decimal,
+    pub discount_amount: Decimal,
+    /// Promotions and codes that changed the price of the line
+    pub adjustments: serde_json::Value,
+    pub tax_rate: Decimal,
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub gross_amount: Decimal,
     pub created_at: String,
     pub updated_at: String,
 }
 
-impl OrderItemModel {
-    fn new(id: i32, order_id: i32, product_id: i32, quantity: i32, price: Decimal) -> Self {
+impl From<order_item::Model> for OrderItemModel {
+    fn from(model: order_item::Model) -> Self {
         OrderItemModel {
-            id,
-            order_id,
-            product_id,
-            quantity,
-            price,
-            created_at: Utc::now().to_string(),
-            updated_at: Utc::now().to_string(),
+            id: model.id,
+            order_id: model.order_id,
+            product_id: model.product_id,
+            quantity: model.quantity,
+            price: model.price,
+            discount_amount: model.discount_amount,
+            adjustments: model.adjustments,
+            tax_rate: model.tax_rate,
+            net_amount: model.net_amount,
+            tax_amount: model.tax_amount,
+            gross_amount: model.gross_amount,
+            created_at: model.created_at.to_string(),
+            updated_at: model.updated_at.to_string(),
         }
     }
 }
@@ -118,43 +345,133 @@ impl OrderItemModel {
 pub struct OrderService;
 
 impl OrderService {
-    /// Creates a new order along with its items
-    pub async fn create_order(
+    /// Places an order for a cart in a single transaction.
+    ///
+    /// The cart row is locked so concurrent checkouts of the same cart are
+    /// serialised; the cart is priced, the order is created and the cart is
+    /// cleared before anything is committed.
+    pub async fn checkout(
         db: &DatabaseConnection,
+        payment_provider: &dyn PaymentProvider,
+        user_id: i32,
+        request: &CheckoutRequest,
+    ) -> Result<CheckoutOutcome, ApiError> {
+        let txn = db.begin().await?;
+
+        let cart = Self::get_checkout_cart(&txn, user_id, request.cart_id).await?;
+
+        // A second checkout of the same cart waits here, then finds it empty
+        cart::Entity::find_by_id(cart.id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?;
+
+        let quote = pricing_service::quote_cart(&txn, Some(user_id), cart.id, request).await?;
+        Self::ensure_lines_purchasable(&txn, &quote.lines, Utc::now().into()).await?;
+
+        // Never charge a price the customer has not seen without asking first
+        if let Some(price_changes) = quote.unconfirmed_price_changes(&request.confirmed_prices) {
+            return Ok(CheckoutOutcome::PricesChanged(price_changes));
+        }
+
+        let order_request = quote.order_request(user_id, request);
+        order_request.validate()?;
+
+        Self::reserve_stock(&txn, &order_request.items).await?;
+        let new_order = Self::create_order(&txn, order_request).await?;
+        if let Some(coupon_id) = new_order.coupon_id {
+            coupon_service::redeem(
+                &txn,
+                coupon_id,
+                new_order.user_id,
+                new_order.id,
+                new_order.discount_amount,
+            )
+            .await?;
+        }
+        Self::clear_checked_out_cart(&txn, cart.id).await?;
+
+        txn.commit().await?;
+
+        // The order stands even if the payment request has to be sent again
+        if let Err(e) = payment_provider
+            .request_payment(new_order.id, new_order.total_amount)
+            .await
+        {
+            tracing::warn!(
+                "Failed to request payment for order {}: {}",
+                new_order.id,
+                e
+            );
+        }
+
+        Ok(CheckoutOutcome::Placed(OrderModel::from(new_order)))
+    }
+
+    /// Fail unless every line can still be bought in its quantity.
+    ///
+    /// Products can be withdrawn, leave their availability window or get a
+    /// lower maximum quantity after they were added to the cart.
+    pub async fn ensure_lines_purchasable<C: ConnectionTrait>(
+        conn: &C,
+        lines: &[QuoteLine],
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), ApiError> {
+        let products: HashMap<i32, product::Model> = product::Entity::find()
+            .filter(product::Column::Id.is_in(lines.iter().map(|line| line.product_id)))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|product| (product.id, product))
+            .collect();
+
+        for line in lines {
+            let product = ensure_purchasable(
+                products.get(&line.product_id).cloned(),
+                line.product_id,
+                now,
+            )?;
+            check_quantity_limit(&product, line.quantity)?;
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a new order along with its items; runs inside the caller's transaction
+    pub async fn create_order<C: ConnectionTrait>(
+        conn: &C,
         request: CreateOrderRequest,
-    ) -> Result<OrderModel, DbErr> {
+    ) -> Result<order::Model, DbErr> {
         // Validate the input
         request
             .validate()
             .map_err(|err| DbErr::Custom(format!("Validation failed: {}", err)))?;
 
-        let total_amount: Decimal = request
-            .items
-            .iter()
-            .map(|item| Decimal::from(item.quantity) * item.price)
-            .sum();
-
-        let transaction = db.begin().await?;
-
-        // Create the order
+        // Create the order, charged exactly as quoted by the pricing pipeline
         let new_order = order::ActiveModel {
             user_id: Set(request.user_id),
-            total_amount: Set(total_amount),
-            payment_status: Set("Pending".to_owned()),
+            total_amount: Set(request.total_amount),
+            net_amount: Set(request.net_amount),
+            tax_amount: Set(request.tax_amount),
+            shipping_method_id: Set(request.shipping_method_id),
+            shipping_cost: Set(request.shipping_cost),
+            coupon_id: Set(request.coupon_id),
+            discount_amount: Set(request.discount_amount),
+            shipping_address: Set(request
+                .shipping_address
+                .map(serde_json::to_value)
+                .transpose()
+                .map_err(|err| DbErr::Custom(err.to_string()))?),
+            status: Set(OrderStatus::PendingPayment),
+            payment_status: Set(PaymentStatus::Pending),
+            fulfilment_status: Set(FulfilmentStatus::Unfulfilled),
             created_at: Set(chrono::Utc::now().into()),
             updated_at: Set(chrono::Utc::now().into()),
             ..Default::default()
         }
-        .insert(&transaction)
+        .insert(conn)
         .await?;
 
-        let order_model = OrderModel::new(
-            new_order.id,
-            request.user_id,
-            total_amount,
-            "Pending".to_owned(),
-        );
-
         // Create order items
         for item in request.items {
             let new_order_item = order_item::ActiveModel {
@@ -162,115 +479,318 @@ impl OrderService {
                 product_id: Set(item.product_id),
                 quantity: Set(item.quantity),
                 price: Set(item.price),
+                discount_amount: Set(item.discount_amount),
+                adjustments: Set(serde_json::to_value(&item.adjustments)
+                    .map_err(|err| DbErr::Custom(err.to_string()))?),
+                tax_rate: Set(item.tax_rate),
+                net_amount: Set(item.net_amount),
+                tax_amount: Set(item.tax_amount),
+                gross_amount: Set(item.gross_amount),
                 created_at: Set(chrono::Utc::now().into()),
                 updated_at: Set(chrono::Utc::now().into()),
                 ..Default::default()
             };
 
-            new_order_item.insert(&transaction).await?;
+            new_order_item.insert(conn).await?;
         }
 
-        transaction.commit().await?;
-        dotenv().ok();
-        // Generate PromptPay QR Code after successful order creation
-        let phone_number: String = env::var("My_PHONE_NUMBER").expect("My_PHONE_NUMBER not set");
-        let qr_code_path = format!("qrcodes/order_{}_qr.png", new_order.id);
-
-        match PromptPayUtils::generate_qr(
-            phone_number,
-            total_amount.to_f64().unwrap(),
-            &qr_code_path,
-        ) {
-            Ok(_) => {
-                println!("QR Code saved to: {}", qr_code_path);
-            }
-            Err(e) => {
-                eprintln!("Failed to generate QR Code: {}", e);
+        Ok(new_order)
+    }
+
+    /// Takes the ordered quantities out of stock, failing if any product runs short.
+    ///
+    /// Products that are not stock-tracked are left untouched.
+    async fn reserve_stock<C: ConnectionTrait>(
+        conn: &C,
+        items: &[OrderItemRequest],
+    ) -> Result<(), ApiError> {
+        for item in items {
+            let result = product::Entity::update_many()
+                .col_expr(
+                    product::Column::Stock,
+                    Expr::col(product::Column::Stock).sub(item.quantity),
+                )
+                .filter(product::Column::Id.eq(item.product_id))
+                .filter(
+                    Condition::any()
+                        .add(product::Column::Stock.is_null())
+                        .add(product::Column::Stock.gte(item.quantity)),
+                )
+                .exec(conn)
+                .await?;
+
+            if result.rows_affected == 0 {
+                return Err(ApiError::UnprocessableEntity(format!(
+                    "Not enough stock left for product {}",
+                    item.product_id
+                )));
             }
         }
 
-        Ok(order_model)
+        Ok(())
+    }
+
+    /// Puts the quantities of an order's items back into stock
+    async fn restore_stock<C: ConnectionTrait>(conn: &C, order_id: i32) -> Result<(), DbErr> {
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(order_id))
+            .all(conn)
+            .await?;
+
+        for item in items {
+            product::Entity::update_many()
+                .col_expr(
+                    product::Column::Stock,
+                    Expr::col(product::Column::Stock).add(item.quantity),
+                )
+                .filter(product::Column::Id.eq(item.product_id))
+                .exec(conn)
+                .await?;
+        }
+
+        Ok(())
     }
 
-    /// Retrieves an order with its associated items
+    /// Retrieves an order with its associated items (staff or the customer who placed it)
     pub async fn get_order_with_items(
         db: &DatabaseConnection,
+        user: &AuthenticatedUser,
         order_id: i32,
-    ) -> Result<(OrderModel, Vec<OrderItemModel>), DbErr> {
-        let order = order::Entity::find_by_id(order_id).one(db).await?;
+    ) -> Result<(OrderModel, Vec<OrderItemModel>), ApiError> {
+        // Orders of other customers are reported as missing
+        let order = order::Entity::find_by_id(order_id)
+            .one(db)
+            .await?
+            .filter(|order| user.is_staff() || order.user_id == user.id)
+            .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
         let items = order_item::Entity::find()
             .filter(order_item::Column::OrderId.eq(order_id))
             .all(db)
             .await?;
 
-        match order {
-            Some(order) => {
-                let order_model = OrderModel::new(
-                    order.id,
-                    order.user_id,
-                    order.total_amount,
-                    order.payment_status,
-                );
-                let order_item_models = items
-                    .into_iter()
-                    .map(|item| {
-                        OrderItemModel::new(
-                            item.id,
-                            item.order_id,
-                            item.product_id,
-                            item.quantity,
-                            item.price,
-                        )
-                    })
-                    .collect();
-
-                Ok((order_model, order_item_models))
-            }
-            None => Err(DbErr::RecordNotFound(format!(
-                "Order with ID {} not found",
+        Ok((
+            OrderModel::from(order),
+            items.into_iter().map(OrderItemModel::from).collect(),
+        ))
+    }
+
+    /// Moves an order to a new status, enforcing the allowed transitions.
+    ///
+    /// Payment and fulfilment statuses follow the order status; marking an
+    /// order paid captures its payment with the payment provider and issues
+    /// the order's invoice.
+    pub async fn transition_order_status(
+        db: &DatabaseConnection,
+        payment_provider: &dyn PaymentProvider,
+        order_id: i32,
+        new_status: OrderStatus,
+    ) -> Result<OrderModel, ApiError> {
+        let txn = db.begin().await?;
+
+        // Lock the order so a concurrent cancellation cannot slip in; the
+        // status checks below run against the locked row
+        let order = order::Entity::find_by_id(order_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
+
+        if new_status == OrderStatus::Cancelled {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Use POST /orders/{}/cancel to cancel an order",
                 order_id
-            ))),
+            )));
         }
+
+        if new_status == OrderStatus::Refunded {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Use POST /orders/{}/refunds to refund an order",
+                order_id
+            )));
+        }
+
+        if !is_allowed_transition(order.status, new_status) {
+            return Err(ApiError::Conflict(format!(
+                "Order {} cannot move from {:?} to {:?}",
+                order_id, order.status, new_status
+            )));
+        }
+
+        if new_status == OrderStatus::Paid {
+            payment_provider
+                .capture(order.id, order.total_amount)
+                .await
+                .map_err(|e| {
+                    ApiError::InternalServerError(format!("Payment capture failed: {}", e))
+                })?;
+        }
+
+        let mut active_model: order::ActiveModel = order.into();
+        active_model.status = Set(new_status);
+        match new_status {
+            OrderStatus::Paid => active_model.payment_status = Set(PaymentStatus::Paid),
+            OrderStatus::Processing => {
+                active_model.fulfilment_status = Set(FulfilmentStatus::Processing)
+            }
+            OrderStatus::Shipped => active_model.fulfilment_status = Set(FulfilmentStatus::Shipped),
+            OrderStatus::Delivered => {
+                active_model.fulfilment_status = Set(FulfilmentStatus::Delivered)
+            }
+            // Cancellations and refunds have their own flows
+            OrderStatus::Cancelled | OrderStatus::Refunded | OrderStatus::PendingPayment => {}
+        }
+        active_model.updated_at = Set(chrono::Utc::now().into());
+
+        let updated_order = active_model.update(&txn).await?;
+        // A paid order gets its tax invoice with the payment
+        if new_status == OrderStatus::Paid {
+            invoice_service::issue_invoice(&txn, &updated_order).await?;
+        }
+        txn.commit().await?;
+
+        Ok(OrderModel::from(updated_order))
     }
 
-    /// Updates the payment status of an order
+    /// Updates the payment status of an order through the matching order transition
     pub async fn update_payment_status(
         db: &DatabaseConnection,
+        payment_provider: &dyn PaymentProvider,
         order_id: i32,
         request: UpdatePaymentStatusRequest,
-    ) -> Result<OrderModel, DbErr> {
-        // Validate the input
-        request
-            .validate()
-            .map_err(|err| DbErr::Custom(format!("Validation failed: {}", err)))?;
+    ) -> Result<OrderModel, ApiError> {
+        let new_status = match request.new_status {
+            PaymentStatus::Paid => OrderStatus::Paid,
+            PaymentStatus::Refunded | PaymentStatus::PartiallyRefunded => {
+                return Err(ApiError::UnprocessableEntity(format!(
+                    "Use POST /orders/{}/refunds to refund an order",
+                    order_id
+                )))
+            }
+            PaymentStatus::Pending => {
+                return Err(ApiError::Conflict(format!(
+                    "The payment of order {} cannot be reset to pending",
+                    order_id
+                )))
+            }
+        };
+
+        Self::transition_order_status(db, payment_provider, order_id, new_status).await
+    }
 
+    /// Cancels an order, puts its items back into stock and voids its payment QR code.
+    ///
+    /// Only unpaid orders can be cancelled: customers may cancel their own,
+    /// staff may cancel any but must give a reason. Paid orders are refunded
+    /// through `POST /orders/{id}/refunds` instead, so the money goes back.
+    pub async fn cancel_order(
+        db: &DatabaseConnection,
+        payment_provider: &dyn PaymentProvider,
+        user: &AuthenticatedUser,
+        order_id: i32,
+        request: CancelOrderRequest,
+    ) -> Result<OrderModel, ApiError> {
+        let txn = db.begin().await?;
+
+        // Lock the order so a concurrent payment cannot slip in
         let order = order::Entity::find_by_id(order_id)
-            .one(db)
+            .lock_exclusive()
+            .one(&txn)
             .await?
-            .ok_or_else(|| {
-                DbErr::RecordNotFound(format!("Order with ID {} not found", order_id))
-            })?;
+            .filter(|order| user.is_staff() || order.user_id == user.id)
+            .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
 
+        if user.is_staff() && request.reason.is_none() {
+            return Err(ApiError::ValidationError(
+                "A reason is required when staff cancel an order".to_string(),
+            ));
+        }
+
+        if !is_allowed_transition(order.status, OrderStatus::Cancelled) {
+            // Paid orders keep their stock reserved until the money is refunded
+            let message = if !is_allowed_transition(order.status, OrderStatus::Refunded) {
+                format!(
+                    "Order {} cannot be cancelled once it is {:?}",
+                    order_id, order.status
+                )
+            } else if user.is_staff() {
+                format!(
+                    "Order {} has already been paid; refund it with POST /orders/{}/refunds instead",
+                    order_id, order_id
+                )
+            } else {
+                format!(
+                    "Order {} has already been paid; contact support for a refund",
+                    order_id
+                )
+            };
+            return Err(ApiError::Conflict(message));
+        }
+
+        Self::restore_stock(&txn, order.id).await?;
+        coupon_service::release_redemption(&txn, order.id).await?;
+
+        let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
         let mut active_model: order::ActiveModel = order.into();
-        active_model.payment_status = Set(request.new_status);
-        active_model.updated_at = Set(chrono::Utc::now().into());
+        active_model.status = Set(OrderStatus::Cancelled);
+        active_model.fulfilment_status = Set(FulfilmentStatus::Cancelled);
+        active_model.cancelled_at = Set(Some(now_fixed));
+        active_model.cancellation_reason = Set(request.reason);
+        active_model.updated_at = Set(now_fixed);
+        let cancelled_order = active_model.update(&txn).await?;
 
-        let updated_order = active_model.update(db).await?;
+        txn.commit().await?;
 
-        let order_model = OrderModel::new(
-            updated_order.id,
-            updated_order.user_id,
-            updated_order.total_amount,
-            updated_order.payment_status,
-        );
+        if let Err(e) = payment_provider.void_payment(cancelled_order.id).await {
+            tracing::warn!(
+                "Failed to void the payment of order {}: {}",
+                cancelled_order.id,
+                e
+            );
+        }
 
-        Ok(order_model)
+        Ok(OrderModel::from(cancelled_order))
     }
 
-    /// Deletes an order and its items
-    pub async fn delete_order(db: &DatabaseConnection, order_id: i32) -> Result<(), DbErr> {
+    /// Permanently deletes an order, its items and refunds; meant for purging test data only.
+    ///
+    /// Invoiced orders are kept: issued invoices must stay on record.
+    pub async fn purge_order(
+        db: &DatabaseConnection,
+        payment_provider: &dyn PaymentProvider,
+        order_id: i32,
+    ) -> Result<(), ApiError> {
         let transaction = db.begin().await?;
 
+        if let Some(invoice) = invoice::Entity::find()
+            .filter(invoice::Column::OrderId.eq(order_id))
+            .one(&transaction)
+            .await?
+        {
+            return Err(ApiError::Conflict(format!(
+                "Order {} cannot be purged: invoice {} was issued for it",
+                order_id, invoice.invoice_number
+            )));
+        }
+
+        // Give back the coupon use, so purging does not leave the coupon's
+        // redemption count pointing at an order that no longer exists
+        coupon_service::release_redemption(&transaction, order_id).await?;
+
+        // Delete the refunds, then the order items they point at
+        let refund_ids = refund::Entity::find()
+            .select_only()
+            .column(refund::Column::Id)
+            .filter(refund::Column::OrderId.eq(order_id))
+            .into_query();
+        refund_item::Entity::delete_many()
+            .filter(refund_item::Column::RefundId.in_subquery(refund_ids))
+            .exec(&transaction)
+            .await?;
+        refund::Entity::delete_many()
+            .filter(refund::Column::OrderId.eq(order_id))
+            .exec(&transaction)
+            .await?;
+
         // Delete order items
         order_item::Entity::delete_many()
             .filter(order_item::Column::OrderId.eq(order_id))
@@ -278,95 +798,451 @@ impl OrderService {
             .await?;
 
         // Delete the order
-        order::Entity::delete_by_id(order_id)
+        let result = order::Entity::delete_by_id(order_id)
             .exec(&transaction)
             .await?;
+        if result.rows_affected == 0 {
+            return Err(ApiError::NotFound(format!(
+                "Order with ID {} not found",
+                order_id
+            )));
+        }
 
         transaction.commit().await?;
+
+        if let Err(e) = payment_provider.void_payment(order_id).await {
+            tracing::warn!("Failed to void the payment of order {}: {}", order_id, e);
+        }
         Ok(())
     }
 
-    // Helper function to get all carts for the user
-    pub async fn get_all_carts_for_user(
+    /// Lists the orders of a customer, newest first by default
+    pub async fn list_orders_for_user(
         db: &DatabaseConnection,
         user_id: i32,
-    ) -> Result<Vec<CartResponse>, ApiError> {
-        let carts = cart::Entity::find()
-            .filter(cart::Column::UserId.eq(user_id)) // Fetch all carts for the user
+        query: OrderListQuery,
+    ) -> Result<OrderPage, ApiError> {
+        Self::find_order_page(
+            db,
+            OrderFilter {
+                page: query.page,
+                per_page: query.per_page,
+                status: query.status,
+                from: query.from,
+                to: query.to,
+                sort: query.sort,
+                user_id: Some(user_id),
+                ..Default::default()
+            },
+            false,
+        )
+        .await
+    }
+
+    /// Searches the orders of all customers (staff only)
+    pub async fn search_orders(
+        db: &DatabaseConnection,
+        query: OrderSearchQuery,
+    ) -> Result<OrderPage, ApiError> {
+        Self::find_order_page(
+            db,
+            OrderFilter {
+                page: query.page,
+                per_page: query.per_page,
+                status: query.status,
+                from: query.from,
+                to: query.to,
+                sort: query.sort,
+                user_id: None,
+                email: query.email,
+                order_id: query.order_id,
+                amount: query.amount,
+            },
+            true,
+        )
+        .await
+    }
+
+    /// Fetch one page of orders matching `filter` with their item counts
+    async fn find_order_page(
+        db: &DatabaseConnection,
+        filter: OrderFilter,
+        include_email: bool,
+    ) -> Result<OrderPage, ApiError> {
+        let page = filter.page.unwrap_or(1);
+        let per_page = filter.per_page.unwrap_or(DEFAULT_ORDERS_PER_PAGE);
+
+        let mut select = order::Entity::find();
+        if let Some(user_id) = filter.user_id {
+            select = select.filter(order::Column::UserId.eq(user_id));
+        }
+        if let Some(status) = filter.status {
+            select = select.filter(order::Column::Status.eq(status));
+        }
+        if let Some(from) = filter.from {
+            select = select.filter(order::Column::CreatedAt.gte(from));
+        }
+        if let Some(to) = filter.to {
+            select = select.filter(order::Column::CreatedAt.lt(to));
+        }
+        if let Some(order_id) = filter.order_id {
+            select = select.filter(order::Column::Id.eq(order_id));
+        }
+        if let Some(amount) = filter.amount {
+            select = select.filter(order::Column::TotalAmount.eq(amount));
+        }
+        if let Some(email) = filter.email {
+            // Match the text literally inside the LIKE pattern
+            let email = email
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            let user_ids = user::Entity::find()
+                .select_only()
+                .column(user::Column::Id)
+                .filter(Expr::col(user::Column::Email).ilike(format!("%{}%", email)))
+                .into_query();
+            select = select.filter(order::Column::UserId.in_subquery(user_ids));
+        }
+
+        select = match filter.sort.unwrap_or_default() {
+            OrderSort::Newest => select.order_by_desc(order::Column::CreatedAt),
+            OrderSort::Oldest => select.order_by_asc(order::Column::CreatedAt),
+            OrderSort::TotalAsc => select.order_by_asc(order::Column::TotalAmount),
+            OrderSort::TotalDesc => select.order_by_desc(order::Column::TotalAmount),
+        }
+        // Stable order between pages
+        .order_by_desc(order::Column::Id);
+
+        let paginator = select.paginate(db, per_page);
+        let ItemsAndPagesNumber {
+            number_of_items,
+            number_of_pages,
+        } = paginator.num_items_and_pages().await?;
+        let orders = paginator.fetch_page(page - 1).await?;
+
+        let order_ids: Vec<i32> = orders.iter().map(|order| order.id).collect();
+        let mut item_counts: HashMap<i32, i32> = HashMap::new();
+        for item in order_item::Entity::find()
+            .filter(order_item::Column::OrderId.is_in(order_ids))
             .all(db)
-            .await?;
+            .await?
+        {
+            *item_counts.entry(item.order_id).or_default() += item.quantity;
+        }
 
-        // Convert the result into the desired response format
-        Ok(carts.into_iter().map(CartResponse::from).collect())
+        let emails: HashMap<i32, String> = if include_email {
+            let user_ids: Vec<i32> = orders.iter().map(|order| order.user_id).collect();
+            user::Entity::find()
+                .filter(user::Column::Id.is_in(user_ids))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|user| (user.id, user.email))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let orders = orders
+            .into_iter()
+            .map(|order| OrderSummary {
+                id: order.id,
+                user_id: order.user_id,
+                email: emails.get(&order.user_id).cloned(),
+                status: order.status,
+                payment_status: order.payment_status,
+                fulfilment_status: order.fulfilment_status,
+                total_amount: order.total_amount,
+                item_count: item_counts.get(&order.id).copied().unwrap_or(0),
+                created_at: order.created_at.to_string(),
+            })
+            .collect();
+
+        Ok(OrderPage {
+            orders,
+            page,
+            per_page,
+            total_items: number_of_items,
+            total_pages: number_of_pages,
+        })
+    }
+
+    // Helper function to resolve the one cart a checkout operates on
+    pub async fn get_checkout_cart<C: ConnectionTrait>(
+        conn: &C,
+        user_id: i32,
+        cart_id: Option<i32>,
+    ) -> Result<CartResponse, ApiError> {
+        let cart = match cart_id {
+            // An explicit cart must belong to the user (active or saved)
+            Some(cart_id) => cart::Entity::find_by_id(cart_id)
+                .filter(cart::Column::UserId.eq(user_id))
+                .one(conn)
+                .await?
+                .ok_or_else(|| ApiError::NotFound(format!("Cart with ID {} not found", cart_id)))?,
+            None => find_active_cart(conn, user_id).await?.ok_or_else(|| {
+                ApiError::ValidationError("No active cart found for the user".to_string())
+            })?,
+        };
+
+        Ok(CartResponse::from(cart))
     }
     // Function to fetch all items from a specific cart
-    pub async fn get_cart_items_for_user(
-        db: &DatabaseConnection,
+    pub async fn get_cart_items_for_user<C: ConnectionTrait>(
+        conn: &C,
         cart_id: i32,
     ) -> Result<Vec<CartItemResponse>, ApiError> {
         let cart_items = cart_item::Entity::find()
             .filter(cart_item::Column::CartId.eq(cart_id)) // Fetch all items in a specific cart
-            .all(db)
+            .filter(cart_item::Column::SavedForLater.eq(false)) // Items saved for later are not bought
+            .all(conn)
             .await?;
 
         // Convert the result into the desired response format
         Ok(cart_items.into_iter().map(CartItemResponse::from).collect())
     }
 
-    // Service function to clear all carts for a user
-    pub async fn clear_all_carts(db: &DatabaseConnection, user_id: i32) -> Result<(), ApiError> {
-        use crate::entities::{cart, cart_item};
-        use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
-
-        // Start a transaction to ensure atomicity
-        let txn = db.begin().await?;
+    // Service function to empty the cart that was checked out; runs inside the checkout transaction
+    pub async fn clear_checked_out_cart<C: ConnectionTrait>(
+        conn: &C,
+        cart_id: i32,
+    ) -> Result<(), ApiError> {
+        // Delete the purchased items; items saved for later stay in the cart
+        cart_item::Entity::delete_many()
+            .filter(cart_item::Column::CartId.eq(cart_id))
+            .filter(cart_item::Column::SavedForLater.eq(false))
+            .exec(conn)
+            .await?;
 
-        // Fetch all cart IDs for the user
-        let cart_ids: Vec<i32> = cart::Entity::find()
-            .filter(cart::Column::UserId.eq(user_id))
-            .all(&txn)
+        // A saved cart has served its purpose once nothing is left in it;
+        // the active cart is kept for reuse
+        let has_remaining_items = cart_item::Entity::find()
+            .filter(cart_item::Column::CartId.eq(cart_id))
+            .one(conn)
             .await?
-            .into_iter()
-            .map(|cart| cart.id)
-            .collect();
-
-        if cart_ids.is_empty() {
-            return Ok(()); // No carts to clear
+            .is_some();
+        if !has_remaining_items {
+            cart::Entity::delete_many()
+                .filter(cart::Column::Id.eq(cart_id))
+                .filter(cart::Column::Kind.eq(CartKind::Saved))
+                .exec(conn)
+                .await?;
         }
 
-        // Delete all cart items for the fetched cart IDs
-        cart_item::Entity::delete_many()
-            .filter(cart_item::Column::CartId.is_in(cart_ids.clone()))
-            .exec(&txn)
-            .await?;
-
-        // Delete all carts for the user
-        cart::Entity::delete_many()
-            .filter(cart::Column::UserId.eq(user_id))
-            .exec(&txn)
+        // The discount code was used up by this order
+        cart::Entity::update_many()
+            .col_expr(cart::Column::CouponId, Expr::value(Option::<i32>::None))
+            .filter(cart::Column::Id.eq(cart_id))
+            .exec(conn)
             .await?;
 
-        // Commit the transaction
-        txn.commit().await?;
+        // Checking out counts as coming back to an abandoned cart
+        mark_cart_recovered(conn, cart_id, Utc::now().into()).await?;
 
         Ok(())
     }
-    /// Fetch price for a given product ID
-    pub async fn get_product_price(
-        db: &DatabaseConnection,
-        product_id: i32,
-    ) -> Result<Decimal, ApiError> {
-        use crate::entities::product;
+}
 
-        // Fetch the product by its ID
-        let product = product::Entity::find_by_id(product_id)
-            .one(db)
-            .await?
-            .ok_or_else(|| {
-                ApiError::NotFound(format!("Product with ID {} not found", product_id))
-            })?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::sea_orm_active_enums::{ProductStatus, UserRole};
+    use sea_orm::{DatabaseBackend, Iterable, MockDatabase};
+
+    #[test]
+    fn orders_follow_the_happy_path() {
+        use OrderStatus::*;
+
+        assert!(is_allowed_transition(PendingPayment, Paid));
+        assert!(is_allowed_transition(Paid, Processing));
+        assert!(is_allowed_transition(Processing, Shipped));
+        assert!(is_allowed_transition(Shipped, Delivered));
+    }
+
+    #[test]
+    fn orders_cannot_skip_or_go_back() {
+        use OrderStatus::*;
+
+        assert!(!is_allowed_transition(PendingPayment, Shipped));
+        assert!(!is_allowed_transition(Paid, PendingPayment));
+        assert!(!is_allowed_transition(Delivered, Shipped));
+        assert!(!is_allowed_transition(Shipped, Cancelled));
+    }
+
+    #[test]
+    fn only_unpaid_orders_can_be_cancelled() {
+        use OrderStatus::*;
+
+        assert!(is_allowed_transition(PendingPayment, Cancelled));
+        assert!(!is_allowed_transition(Paid, Cancelled));
+        assert!(!is_allowed_transition(Processing, Cancelled));
+    }
+
+    #[test]
+    fn unpaid_orders_cannot_be_refunded() {
+        assert!(!is_allowed_transition(
+            OrderStatus::PendingPayment,
+            OrderStatus::Refunded
+        ));
+    }
+
+    #[test]
+    fn cancelled_and_refunded_orders_are_final() {
+        for to in OrderStatus::iter() {
+            assert!(!is_allowed_transition(OrderStatus::Cancelled, to));
+            assert!(!is_allowed_transition(OrderStatus::Refunded, to));
+        }
+    }
+
+    #[test]
+    fn no_status_transitions_to_itself() {
+        for status in OrderStatus::iter() {
+            assert!(!is_allowed_transition(status, status));
+        }
+    }
+
+    fn product(status: ProductStatus, max_quantity: Option<i32>) -> product::Model {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        product::Model {
+            id: 7,
+            name: "Mug".to_string(),
+            description: None,
+            price: Decimal::ONE_HUNDRED,
+            created_at: now,
+            updated_at: now,
+            category: None,
+            status,
+            available_from: None,
+            available_until: None,
+            max_quantity,
+            stock: None,
+            weight_grams: None,
+            tax_class_id: None,
+        }
+    }
+
+    fn quote_line(quantity: i32) -> QuoteLine {
+        QuoteLine {
+            cart_item_id: 1,
+            product_id: 7,
+            quantity,
+            unit_price: Decimal::ONE_HUNDRED,
+            added_price: Decimal::ONE_HUNDRED,
+            line_total: Decimal::ONE_HUNDRED * Decimal::from(quantity),
+            discount_amount: Decimal::ZERO,
+            adjustments: Vec::new(),
+            tax_rate: Decimal::ZERO,
+            tax_exempt: false,
+            net_amount: Decimal::ZERO,
+            tax_amount: Decimal::ZERO,
+            gross_amount: Decimal::ZERO,
+        }
+    }
+
+    async fn check_lines(product: Option<product::Model>, quantity: i32) -> Result<(), ApiError> {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([product.into_iter().collect::<Vec<_>>()])
+            .into_connection();
+
+        OrderService::ensure_lines_purchasable(&db, &[quote_line(quantity)], Utc::now().into())
+            .await
+    }
+
+    #[tokio::test]
+    async fn checkout_accepts_lines_that_can_still_be_bought() {
+        let result = check_lines(Some(product(ProductStatus::Published, Some(5))), 5).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_products_moved_back_to_draft() {
+        let result = check_lines(Some(product(ProductStatus::Draft, None)), 1).await;
+        assert!(matches!(result, Err(ApiError::UnprocessableEntity(_))));
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_products_whose_window_closed() {
+        let mut closed = product(ProductStatus::Published, None);
+        closed.available_until = Some((Utc::now() - chrono::Duration::hours(1)).into());
+
+        let result = check_lines(Some(closed), 1).await;
+        assert!(matches!(result, Err(ApiError::UnprocessableEntity(_))));
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_lines_over_a_lowered_maximum() {
+        let result = check_lines(Some(product(ProductStatus::Published, Some(2))), 3).await;
+        assert!(matches!(result, Err(ApiError::UnprocessableEntity(_))));
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_deleted_products() {
+        let result = check_lines(None, 1).await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+    }
+
+    fn placed_order(user_id: i32) -> order::Model {
+        let now: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+        order::Model {
+            id: 5,
+            user_id,
+            total_amount: Decimal::ONE_HUNDRED,
+            payment_status: PaymentStatus::Pending,
+            created_at: now,
+            updated_at: now,
+            status: OrderStatus::PendingPayment,
+            fulfilment_status: FulfilmentStatus::Unfulfilled,
+            cancelled_at: None,
+            cancellation_reason: None,
+            shipping_method_id: None,
+            shipping_cost: Decimal::ZERO,
+            shipping_address: None,
+            net_amount: Decimal::ONE_HUNDRED,
+            tax_amount: Decimal::ZERO,
+            coupon_id: None,
+            discount_amount: Decimal::ZERO,
+        }
+    }
+
+    async fn fetch_order(
+        user: AuthenticatedUser,
+    ) -> Result<(OrderModel, Vec<OrderItemModel>), ApiError> {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![placed_order(7)]])
+            .append_query_results([Vec::<order_item::Model>::new()])
+            .into_connection();
+
+        OrderService::get_order_with_items(&db, &user, 5).await
+    }
+
+    #[tokio::test]
+    async fn customers_can_read_their_own_orders() {
+        let user = AuthenticatedUser {
+            id: 7,
+            role: UserRole::Customer,
+        };
+        assert!(fetch_order(user).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn orders_of_other_customers_are_reported_as_not_found() {
+        let user = AuthenticatedUser {
+            id: 8,
+            role: UserRole::Customer,
+        };
+        assert!(matches!(
+            fetch_order(user).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
 
-        // Return the product's price
-        Ok(product.price)
+    #[tokio::test]
+    async fn staff_can_read_any_order() {
+        let user = AuthenticatedUser {
+            id: 8,
+            role: UserRole::Staff,
+        };
+        assert!(fetch_order(user).await.is_ok());
     }
 }
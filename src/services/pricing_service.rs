@@ -0,0 +1,557 @@
+use super::coupon_service;
+use super::order_service::{CreateOrderRequest, OrderItemRequest, OrderService};
+use super::promotion_service::{self, AppliedPromotion, PromotionLine, SkippedPromotion};
+use super::shipping_service::{self, ShippingAddress, ShippingQuote};
+use super::tax_service::{self, round_satang, TaxBreakdown};
+use crate::entities::{cart, product};
+use crate::utils::actix_error::ApiError;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use validator::Validate;
+
+/// Request body shared by the checkout preview and order creation
+#[derive(Debug, Default, Deserialize, Serialize, Validate)]
+pub struct CheckoutRequest {
+    /// The cart to check out; defaults to the user's active cart
+    pub cart_id: Option<i32>,
+    /// New prices the client accepted after being told they changed since
+    /// the items were added to the cart
+    #[serde(default)]
+    #[validate(length(max = 200, message = "At most 200 confirmed prices can be sent"))]
+    pub confirmed_prices: Vec<ConfirmedPrice>,
+    /// How the order reaches the customer; no shipping is charged when absent
+    pub shipping_method_id: Option<i32>,
+    /// Required by every shipping method except store pickup
+    #[validate(nested)]
+    pub shipping_address: Option<ShippingAddress>,
+}
+
+/// A changed cart line price the client confirmed, echoed from a `PriceChange`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConfirmedPrice {
+    pub cart_item_id: i32,
+    pub unit_price: Decimal,
+}
+
+/// Why the price of a line changed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PriceAdjustment {
+    /// Promotion that gave the discount, if it came from one
+    pub promotion_id: Option<i32>,
+    /// Discount code that gave the discount, if it came from one
+    pub coupon_code: Option<String>,
+    pub label: String,
+    pub amount: Decimal,
+    pub explanation: String,
+}
+
+/// One priced cart line
+#[derive(Debug, Serialize)]
+pub struct QuoteLine {
+    pub cart_item_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    /// Current unit price, the one that will be charged
+    pub unit_price: Decimal,
+    /// Unit price when the product was added to the cart
+    pub added_price: Decimal,
+    pub line_total: Decimal,
+    /// Part of the line total taken off by promotions and the discount code
+    pub discount_amount: Decimal,
+    /// Every discount given on the line, in the order they were applied
+    pub adjustments: Vec<PriceAdjustment>,
+    /// VAT percentage charged on the line (0 for exempt products)
+    pub tax_rate: Decimal,
+    pub tax_exempt: bool,
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub gross_amount: Decimal,
+}
+
+/// A cart line whose product price changed after it was added to the cart
+#[derive(Debug, Serialize)]
+pub struct PriceChange {
+    pub cart_item_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    pub previous_price: Decimal,
+    pub current_price: Decimal,
+}
+
+/// Returned instead of an order when the client has to confirm new prices
+#[derive(Debug, Serialize)]
+pub struct PriceChangesResponse {
+    pub changes: Vec<PriceChange>,
+    pub previous_total: Decimal,
+    pub current_total: Decimal,
+}
+
+/// Final numbers of a checkout, as previewed and as charged
+#[derive(Debug, Serialize)]
+pub struct CheckoutQuote {
+    pub cart_id: i32,
+    pub lines: Vec<QuoteLine>,
+    pub subtotal: Decimal,
+    /// Discount code applied to the cart, if any
+    pub coupon_id: Option<i32>,
+    pub coupon_code: Option<String>,
+    /// Automatic promotions that changed the price
+    pub promotions: Vec<AppliedPromotion>,
+    /// Running promotions that did not apply, with the reason
+    pub skipped_promotions: Vec<SkippedPromotion>,
+    pub discount_total: Decimal,
+    pub shipping: Option<ShippingQuote>,
+    pub shipping_total: Decimal,
+    /// Whether the prices above already include VAT
+    pub prices_include_vat: bool,
+    pub net_total: Decimal,
+    pub vat_total: Decimal,
+    pub grand_total: Decimal,
+}
+
+impl CheckoutQuote {
+    /// Lines whose price differs from the one shown when they were added
+    pub fn price_changes(&self) -> Vec<PriceChange> {
+        self.lines
+            .iter()
+            .filter(|line| line.unit_price != line.added_price)
+            .map(|line| PriceChange {
+                cart_item_id: line.cart_item_id,
+                product_id: line.product_id,
+                quantity: line.quantity,
+                previous_price: line.added_price,
+                current_price: line.unit_price,
+            })
+            .collect()
+    }
+
+    /// Summary of the price changes, or `None` when every changed price was
+    /// confirmed at exactly its current value.
+    ///
+    /// A price that moved again after the client confirmed it has to be
+    /// confirmed again.
+    pub fn unconfirmed_price_changes(
+        &self,
+        confirmed_prices: &[ConfirmedPrice],
+    ) -> Option<PriceChangesResponse> {
+        let changes = self.price_changes();
+        let all_confirmed = changes.iter().all(|change| {
+            confirmed_prices.iter().any(|confirmed| {
+                confirmed.cart_item_id == change.cart_item_id
+                    && confirmed.unit_price == change.current_price
+            })
+        });
+        if all_confirmed {
+            return None;
+        }
+
+        Some(PriceChangesResponse {
+            changes,
+            previous_total: self
+                .lines
+                .iter()
+                .map(|line| line.added_price * Decimal::from(line.quantity))
+                .sum(),
+            current_total: self.subtotal,
+        })
+    }
+
+    /// Order creation request charging exactly this quote
+    pub fn order_request(&self, user_id: i32, request: &CheckoutRequest) -> CreateOrderRequest {
+        CreateOrderRequest {
+            user_id,
+            items: self
+                .lines
+                .iter()
+                .map(|line| OrderItemRequest {
+                    product_id: line.product_id,
+                    quantity: line.quantity,
+                    price: line.unit_price,
+                    discount_amount: line.discount_amount,
+                    adjustments: line.adjustments.clone(),
+                    tax_rate: line.tax_rate,
+                    net_amount: line.net_amount,
+                    tax_amount: line.tax_amount,
+                    gross_amount: line.gross_amount,
+                })
+                .collect(),
+            shipping_method_id: self.shipping.as_ref().map(|shipping| shipping.method_id),
+            shipping_cost: self.shipping_total,
+            shipping_address: request.shipping_address.clone(),
+            coupon_id: self.coupon_id,
+            discount_amount: self.discount_total,
+            net_amount: self.net_total,
+            tax_amount: self.vat_total,
+            total_amount: self.grand_total,
+        }
+    }
+}
+
+/// Price the cart a checkout request refers to, without writing anything
+pub async fn quote_checkout<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    request: &CheckoutRequest,
+) -> Result<CheckoutQuote, ApiError> {
+    // Resolve the single cart being checked out (the active cart by default)
+    let cart = OrderService::get_checkout_cart(conn, user_id, request.cart_id).await?;
+
+    quote_cart(conn, Some(user_id), cart.id, request).await
+}
+
+/// The pricing pipeline: price every line of a cart and compute the totals.
+///
+/// Order creation charges the quote returned here, so the checkout preview
+/// and the placed order always agree. Automatic promotions are applied first,
+/// then the cart's discount code on what is left, and VAT is worked out on
+/// the discounted lines. `user_id` is the customer the discount code limits
+/// are checked for; guest carts have none.
+pub async fn quote_cart<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Option<i32>,
+    cart_id: i32,
+    request: &CheckoutRequest,
+) -> Result<CheckoutQuote, ApiError> {
+    let cart_items = OrderService::get_cart_items_for_user(conn, cart_id).await?;
+    if cart_items.is_empty() {
+        return Err(ApiError::ValidationError(
+            "No items in the cart to create an order".to_string(),
+        ));
+    }
+
+    let product_ids: Vec<i32> = cart_items.iter().map(|item| item.product_id).collect();
+    let products: HashMap<i32, product::Model> = product::Entity::find()
+        .filter(product::Column::Id.is_in(product_ids))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|product| (product.id, product))
+        .collect();
+
+    let prices_include_vat = tax_service::prices_include_vat();
+    let tax_rates = tax_service::tax_rates_for_products(conn, products.values()).await?;
+
+    let mut priced = Vec::with_capacity(cart_items.len());
+    for item in cart_items {
+        let product = products.get(&item.product_id).ok_or_else(|| {
+            ApiError::NotFound(format!("Product with ID {} not found", item.product_id))
+        })?;
+        let line_total = product.price * Decimal::from(item.quantity);
+        priced.push((item, product, line_total));
+    }
+
+    let cart = cart::Entity::find_by_id(cart_id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Cart with ID {} not found", cart_id)))?;
+    let coupon = coupon_service::find_cart_coupon(conn, &cart).await?;
+    let now = Utc::now().into();
+
+    let rules = promotion_service::running_promotions(conn, now).await?;
+    let promotion_lines: Vec<PromotionLine> = priced
+        .iter()
+        .map(|(item, product, _)| PromotionLine {
+            cart_item_id: item.id,
+            product,
+            quantity: item.quantity,
+        })
+        .collect();
+    let promotions =
+        promotion_service::evaluate_promotions(&rules, &promotion_lines, coupon.is_some());
+
+    let mut adjustments: Vec<Vec<PriceAdjustment>> = vec![Vec::new(); priced.len()];
+    let mut index_by_item: HashMap<i32, usize> = HashMap::new();
+    for (index, (item, _, _)) in priced.iter().enumerate() {
+        index_by_item.insert(item.id, index);
+    }
+    for promotion in &promotions.applied {
+        for line in &promotion.lines {
+            adjustments[index_by_item[&line.cart_item_id]].push(PriceAdjustment {
+                promotion_id: Some(promotion.promotion_id),
+                coupon_code: None,
+                label: promotion.name.clone(),
+                amount: line.amount,
+                explanation: line.explanation.clone(),
+            });
+        }
+    }
+
+    // A code that can no longer be used blocks checkout until it is removed
+    let mut discounts = promotions.line_discounts;
+    if let Some(coupon) = &coupon {
+        coupon_service::ensure_redeemable(conn, &coupon.coupon, user_id, now).await?;
+        let coupon_discounts = coupon.allocate(
+            &priced
+                .iter()
+                .zip(&discounts)
+                .map(|((_, product, line_total), discount)| (*product, *line_total - *discount))
+                .collect::<Vec<_>>(),
+        )?;
+
+        for (index, amount) in coupon_discounts.into_iter().enumerate() {
+            if amount > Decimal::ZERO {
+                discounts[index] += amount;
+                adjustments[index].push(PriceAdjustment {
+                    promotion_id: None,
+                    coupon_code: Some(coupon.coupon.code.clone()),
+                    label: format!("Code {}", coupon.coupon.code),
+                    amount,
+                    explanation: coupon.describe(),
+                });
+            }
+        }
+    }
+
+    let mut lines = Vec::with_capacity(priced.len());
+    let mut parcel = Vec::with_capacity(priced.len());
+    for (((item, product, line_total), discount_amount), adjustments) in
+        priced.into_iter().zip(discounts).zip(adjustments)
+    {
+        let tax_rate = &tax_rates[&product.id];
+        let tax = TaxBreakdown::from_price(
+            line_total - discount_amount,
+            tax_rate.rate_percent,
+            prices_include_vat,
+        );
+        parcel.push((product, item.quantity));
+
+        lines.push(QuoteLine {
+            cart_item_id: item.id,
+            product_id: item.product_id,
+            quantity: item.quantity,
+            unit_price: product.price,
+            added_price: item.unit_price,
+            line_total,
+            discount_amount,
+            adjustments,
+            tax_rate: tax_rate.rate_percent,
+            tax_exempt: tax_rate.exempt,
+            net_amount: tax.net,
+            tax_amount: tax.tax,
+            gross_amount: tax.gross,
+        });
+    }
+
+    let subtotal: Decimal = lines.iter().map(|line| line.line_total).sum();
+    let discount_total: Decimal = lines.iter().map(|line| line.discount_amount).sum();
+    let shipping = shipping_service::quote_shipping(
+        conn,
+        request.shipping_method_id,
+        request.shipping_address.as_ref(),
+        subtotal - discount_total,
+        shipping_service::parcel_weight(parcel),
+    )
+    .await?;
+    let shipping_total = shipping
+        .as_ref()
+        .map_or(Decimal::ZERO, |shipping| shipping.cost);
+
+    // Shipping is a service taxed at the standard rate
+    let shipping_tax = TaxBreakdown::from_price(
+        shipping_total,
+        tax_service::standard_tax_rate(conn).await?.rate_percent,
+        prices_include_vat,
+    );
+    let totals = lines
+        .iter()
+        .map(|line| TaxBreakdown {
+            net: line.net_amount,
+            tax: line.tax_amount,
+            gross: line.gross_amount,
+        })
+        .sum::<TaxBreakdown>()
+        + shipping_tax;
+
+    Ok(CheckoutQuote {
+        cart_id,
+        lines,
+        subtotal,
+        coupon_id: coupon.as_ref().map(|coupon| coupon.coupon.id),
+        coupon_code: coupon.map(|coupon| coupon.coupon.code),
+        promotions: promotions.applied,
+        skipped_promotions: promotions.skipped,
+        discount_total,
+        shipping,
+        shipping_total,
+        prices_include_vat,
+        net_total: totals.net,
+        vat_total: totals.tax,
+        grand_total: totals.gross,
+    })
+}
+
+/// Split an amount over parts in proportion to their weights, to the satang.
+///
+/// The last part with a weight takes the rounding remainder, so the shares
+/// always add up to `amount`.
+pub fn split_proportionally(amount: Decimal, weights: &[Decimal]) -> Vec<Decimal> {
+    let total: Decimal = weights.iter().sum();
+    if total <= Decimal::ZERO {
+        return vec![Decimal::ZERO; weights.len()];
+    }
+
+    let last = weights.iter().rposition(|weight| *weight > Decimal::ZERO);
+    let mut remaining = amount;
+    weights
+        .iter()
+        .enumerate()
+        .map(|(index, weight)| {
+            if *weight <= Decimal::ZERO {
+                return Decimal::ZERO;
+            }
+            let share = if Some(index) == last {
+                remaining
+            } else {
+                round_satang(amount * *weight / total).min(remaining)
+            };
+            remaining -= share;
+            share
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn split_proportionally_gives_rounding_remainder_to_last_part() {
+        assert_eq!(
+            split_proportionally(dec("100"), &[dec("1"), dec("1"), dec("1")]),
+            vec![dec("33.33"), dec("33.33"), dec("33.34")]
+        );
+    }
+
+    #[test]
+    fn split_proportionally_follows_weights() {
+        assert_eq!(
+            split_proportionally(dec("30"), &[dec("200"), dec("100")]),
+            vec![dec("20.00"), dec("10.00")]
+        );
+    }
+
+    #[test]
+    fn split_proportionally_skips_parts_without_weight() {
+        // The remainder goes to the last part that has a weight
+        assert_eq!(
+            split_proportionally(dec("10"), &[dec("1"), dec("2"), Decimal::ZERO]),
+            vec![dec("3.33"), dec("6.67"), Decimal::ZERO]
+        );
+    }
+
+    #[test]
+    fn split_proportionally_without_weights_gives_nothing() {
+        assert_eq!(
+            split_proportionally(dec("10"), &[Decimal::ZERO, Decimal::ZERO]),
+            vec![Decimal::ZERO, Decimal::ZERO]
+        );
+        assert!(split_proportionally(dec("10"), &[]).is_empty());
+    }
+
+    #[test]
+    fn split_proportionally_shares_add_up_to_amount() {
+        let weights = [dec("19.99"), dec("0.01"), dec("7.35"), dec("120.50")];
+        let shares = split_proportionally(dec("13.57"), &weights);
+        assert_eq!(shares.iter().sum::<Decimal>(), dec("13.57"));
+    }
+
+    fn line(cart_item_id: i32, added_price: &str, unit_price: &str) -> QuoteLine {
+        QuoteLine {
+            cart_item_id,
+            product_id: cart_item_id,
+            quantity: 2,
+            unit_price: dec(unit_price),
+            added_price: dec(added_price),
+            line_total: dec(unit_price) * Decimal::TWO,
+            discount_amount: Decimal::ZERO,
+            adjustments: Vec::new(),
+            tax_rate: Decimal::ZERO,
+            tax_exempt: false,
+            net_amount: Decimal::ZERO,
+            tax_amount: Decimal::ZERO,
+            gross_amount: Decimal::ZERO,
+        }
+    }
+
+    fn quote(lines: Vec<QuoteLine>) -> CheckoutQuote {
+        CheckoutQuote {
+            cart_id: 1,
+            subtotal: lines.iter().map(|line| line.line_total).sum(),
+            lines,
+            coupon_id: None,
+            coupon_code: None,
+            promotions: Vec::new(),
+            skipped_promotions: Vec::new(),
+            discount_total: Decimal::ZERO,
+            shipping: None,
+            shipping_total: Decimal::ZERO,
+            prices_include_vat: true,
+            net_total: Decimal::ZERO,
+            vat_total: Decimal::ZERO,
+            grand_total: Decimal::ZERO,
+        }
+    }
+
+    fn confirmed(cart_item_id: i32, unit_price: &str) -> ConfirmedPrice {
+        ConfirmedPrice {
+            cart_item_id,
+            unit_price: dec(unit_price),
+        }
+    }
+
+    #[test]
+    fn unchanged_prices_need_no_confirmation() {
+        let quote = quote(vec![line(1, "10", "10"), line(2, "25", "25")]);
+        assert!(quote.unconfirmed_price_changes(&[]).is_none());
+    }
+
+    #[test]
+    fn changed_prices_are_reported_with_both_totals() {
+        let quote = quote(vec![line(1, "10", "12"), line(2, "25", "25")]);
+
+        let response = quote.unconfirmed_price_changes(&[]).unwrap();
+
+        assert_eq!(response.changes.len(), 1);
+        assert_eq!(response.changes[0].cart_item_id, 1);
+        assert_eq!(response.changes[0].previous_price, dec("10"));
+        assert_eq!(response.changes[0].current_price, dec("12"));
+        assert_eq!(response.previous_total, dec("70"));
+        assert_eq!(response.current_total, dec("74"));
+    }
+
+    #[test]
+    fn confirmed_current_prices_are_accepted() {
+        let quote = quote(vec![line(1, "10", "12"), line(2, "25", "20")]);
+        let prices = [confirmed(1, "12"), confirmed(2, "20")];
+
+        assert!(quote.unconfirmed_price_changes(&prices).is_none());
+    }
+
+    #[test]
+    fn confirming_a_stale_price_is_not_enough() {
+        // The price moved again after the client saw 12
+        let quote = quote(vec![line(1, "10", "13")]);
+
+        assert!(quote
+            .unconfirmed_price_changes(&[confirmed(1, "12")])
+            .is_some());
+    }
+
+    #[test]
+    fn every_changed_line_must_be_confirmed() {
+        let quote = quote(vec![line(1, "10", "12"), line(2, "25", "20")]);
+
+        let response = quote
+            .unconfirmed_price_changes(&[confirmed(1, "12")])
+            .unwrap();
+        assert_eq!(response.changes.len(), 2);
+    }
+}
@@ -1,10 +1,50 @@
 use crate::entities::product;
+use crate::entities::sea_orm_active_enums::ProductStatus;
 use crate::models::product::{ProductRequest, ProductResponse};
+use crate::services::tax_service;
 use crate::utils::actix_error::ApiError;
 use sea_orm::entity::ModelTrait;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, Set,
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+/// Condition matching products whose availability window contains `now`
+fn available_at(now: DateTime<FixedOffset>) -> Condition {
+    Condition::all()
+        .add(
+            Condition::any()
+                .add(product::Column::AvailableFrom.is_null())
+                .add(product::Column::AvailableFrom.lte(now)),
+        )
+        .add(
+            Condition::any()
+                .add(product::Column::AvailableUntil.is_null())
+                .add(product::Column::AvailableUntil.gt(now)),
+        )
+}
+
+/// Condition matching products shown in public listings
+pub fn publicly_listed(now: DateTime<FixedOffset>) -> Condition {
+    Condition::all()
+        .add(product::Column::Status.eq(ProductStatus::Published))
+        .add(available_at(now))
+}
+
+/// Condition matching products reachable by ID (published or unlisted, within their window)
+pub fn publicly_visible(now: DateTime<FixedOffset>) -> Condition {
+    Condition::all()
+        .add(product::Column::Status.ne(ProductStatus::Draft))
+        .add(available_at(now))
+}
 
-use chrono::Utc;
+/// Whether customers may currently view and buy the product
+pub fn is_purchasable(product: &product::Model, now: DateTime<FixedOffset>) -> bool {
+    product.status != ProductStatus::Draft
+        && product.available_from.is_none_or(|from| from <= now)
+        && product.available_until.is_none_or(|until| until > now)
+}
 
 pub async fn create_product(
     db: &DatabaseConnection,
@@ -14,11 +54,23 @@ pub async fn create_product(
     let now_utc = Utc::now();
     let now_fixed: chrono::DateTime<chrono::FixedOffset> = now_utc.into(); // Convert to FixedOffset
 
+    if let Some(tax_class_id) = request.tax_class_id {
+        tax_service::ensure_tax_class_exists(db, tax_class_id).await?;
+    }
+
     // Create a new product record with timestamps
     let new_product = product::ActiveModel {
         name: Set(request.name),
         description: Set(request.description),
         price: Set(request.price),
+        category: Set(request.category),
+        status: Set(request.status.unwrap_or(ProductStatus::Published)),
+        available_from: Set(request.available_from),
+        available_until: Set(request.available_until),
+        max_quantity: Set(request.max_quantity),
+        stock: Set(request.stock),
+        weight_grams: Set(request.weight_grams),
+        tax_class_id: Set(request.tax_class_id),
         created_at: Set(now_fixed), // Set the created_at timestamp
         updated_at: Set(now_fixed), // Set the updated_at timestamp
         ..Default::default()
@@ -28,28 +80,21 @@ pub async fn create_product(
     let result = new_product.insert(db).await?;
 
     // Return the response with product details
-    Ok(ProductResponse {
-        id: result.id,
-        name: result.name,
-        description: result.description,
-        price: result.price,
-    })
+    Ok(ProductResponse::from(result))
 }
 
-/// Fetches a product by its ID
+/// Fetches a product by its ID, hiding drafts and products outside their availability window
 pub async fn get_product_by_id(
     db: &DatabaseConnection,
     product_id: i32,
 ) -> Result<ProductResponse, ApiError> {
     // Fetch the product by ID
-    let product = product::Entity::find_by_id(product_id).one(db).await?;
+    let product = product::Entity::find_by_id(product_id)
+        .filter(publicly_visible(Utc::now().into()))
+        .one(db)
+        .await?;
     match product {
-        Some(product) => Ok(ProductResponse {
-            id: product.id,
-            name: product.name,
-            description: product.description,
-            price: product.price,
-        }),
+        Some(product) => Ok(ProductResponse::from(product)),
         None => Err(ApiError::NotFound(format!(
             "Product with ID {} not found",
             product_id
@@ -58,23 +103,39 @@ pub async fn get_product_by_id(
 }
 
 pub async fn get_all_products(db: &DatabaseConnection) -> Result<Vec<ProductResponse>, ApiError> {
-    // Fetch all products
-    let products = product::Entity::find().all(db).await?; // Assuming the error is a string
+    // Fetch the published products that are currently available
+    let products = product::Entity::find()
+        .filter(publicly_listed(Utc::now().into()))
+        .all(db)
+        .await?;
 
     // Convert the Vec<product::Model> to Vec<ProductResponse>
-    let product_responses = products
-        .iter()
-        .map(|product| ProductResponse {
-            id: product.id,
-            name: product.name.clone(),
-            description: product.description.clone(),
-            price: product.price,
-        })
-        .collect();
+    let product_responses = products.into_iter().map(ProductResponse::from).collect();
 
     Ok(product_responses)
 }
 
+/// Fetches a product by its ID regardless of its status (staff only)
+pub async fn get_product_by_id_for_staff(
+    db: &DatabaseConnection,
+    product_id: i32,
+) -> Result<ProductResponse, ApiError> {
+    product::Entity::find_by_id(product_id)
+        .one(db)
+        .await?
+        .map(ProductResponse::from)
+        .ok_or_else(|| ApiError::NotFound(format!("Product with ID {} not found", product_id)))
+}
+
+/// Fetches every product including drafts and scheduled ones (staff only)
+pub async fn get_all_products_for_staff(
+    db: &DatabaseConnection,
+) -> Result<Vec<ProductResponse>, ApiError> {
+    let products = product::Entity::find().all(db).await?;
+
+    Ok(products.into_iter().map(ProductResponse::from).collect())
+}
+
 /// Updates an existing product
 pub async fn update_product(
     db: &DatabaseConnection,
@@ -84,19 +145,14 @@ pub async fn update_product(
     let now_utc = Utc::now();
     let now_fixed: chrono::DateTime<chrono::FixedOffset> = now_utc.into(); // Convert to FixedOffset
                                                                            // Fetch the existing product by ID
-    let product = product::Entity::find_by_id(product_id).one(db).await?;
-    match product {
-        Some(product) => Ok(ProductResponse {
-            id: product.id,
-            name: product.name,
-            description: product.description,
-            price: product.price,
-        }),
-        None => Err(ApiError::NotFound(format!(
-            "Product with ID {} not found",
-            product_id
-        ))),
-    };
+    let existing_product = product::Entity::find_by_id(product_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Product with ID {} not found", product_id)))?;
+
+    if let Some(tax_class_id) = request.tax_class_id {
+        tax_service::ensure_tax_class_exists(db, tax_class_id).await?;
+    }
 
     // Convert the fetched model to ActiveModel for updates
     let updated_product = product::ActiveModel {
@@ -104,6 +160,14 @@ pub async fn update_product(
         name: Set(request.name),
         description: Set(request.description),
         price: Set(request.price),
+        category: Set(request.category),
+        status: Set(request.status.unwrap_or(existing_product.status)),
+        available_from: Set(request.available_from),
+        available_until: Set(request.available_until),
+        max_quantity: Set(request.max_quantity),
+        stock: Set(request.stock),
+        weight_grams: Set(request.weight_grams),
+        tax_class_id: Set(request.tax_class_id),
         updated_at: Set(now_fixed),
         ..Default::default() // Keep the rest of the fields unchanged
     };
@@ -112,12 +176,7 @@ pub async fn update_product(
     let updated_product = updated_product.update(db).await?;
 
     // Return the updated product details
-    Ok(ProductResponse {
-        id: updated_product.id,
-        name: updated_product.name,
-        description: updated_product.description,
-        price: updated_product.price,
-    })
+    Ok(ProductResponse::from(updated_product))
 }
 
 /// Deletes a product by its ID
@@ -134,3 +193,39 @@ pub async fn delete_product(db: &DatabaseConnection, product_id: i32) -> Result<
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn request() -> ProductRequest {
+        ProductRequest {
+            name: "Mug".to_string(),
+            description: None,
+            price: Decimal::ONE_HUNDRED,
+            category: None,
+            status: None,
+            available_from: None,
+            available_until: None,
+            max_quantity: None,
+            stock: None,
+            weight_grams: None,
+            tax_class_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn update_product_reports_unknown_products_as_not_found() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<product::Model>::new()])
+            .into_connection();
+
+        let result = update_product(&db, 42, request()).await;
+
+        assert!(matches!(result, Err(ApiError::NotFound(message)) if message.contains("42")));
+        // Nothing is written for a product that does not exist
+        assert_eq!(db.into_transaction_log().len(), 1);
+    }
+}
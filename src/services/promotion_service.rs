@@ -0,0 +1,1049 @@
+use super::pricing_service::split_proportionally;
+use super::tax_service::round_satang;
+use crate::entities::sea_orm_active_enums::PromotionKind;
+use crate::entities::{product, promotion, promotion_category, promotion_product};
+use crate::utils::actix_error::ApiError;
+use chrono::{DateTime, FixedOffset, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use validator::{Validate, ValidationError};
+
+/// One spend level of a tiered discount
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PromotionTier {
+    /// Amount the eligible items must reach
+    pub min_subtotal: Decimal,
+    pub percent_off: Decimal,
+}
+
+/// Struct for creating or updating a promotion (staff only)
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_promotion_request"))]
+pub struct PromotionRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+
+    /// Shown to customers next to the discount
+    #[validate(length(max = 500, message = "Description cannot exceed 500 characters"))]
+    pub description: Option<String>,
+
+    pub kind: PromotionKind,
+
+    /// Promotions with a higher priority are evaluated first
+    #[serde(default)]
+    pub priority: i32,
+
+    /// Whether other promotions may apply to the same cart; an exclusive
+    /// promotion only applies when nothing was applied before it and stops
+    /// the evaluation afterwards. Defaults to true on creation.
+    pub stackable: Option<bool>,
+
+    /// Whether the promotion still applies when a discount code is used.
+    /// Defaults to true on creation.
+    pub combinable_with_coupons: Option<bool>,
+
+    /// Buy X get Y: units to pay for and units given free in each group
+    #[validate(range(min = 1, message = "Buy quantity must be at least 1"))]
+    pub buy_quantity: Option<i32>,
+    #[validate(range(min = 1, message = "Get quantity must be at least 1"))]
+    pub get_quantity: Option<i32>,
+
+    /// Bundle pricing: this many eligible units for the bundle price
+    #[validate(range(min = 2, message = "Bundle quantity must be at least 2"))]
+    pub bundle_quantity: Option<i32>,
+    pub bundle_price: Option<Decimal>,
+
+    /// Tiered percentage: the highest tier reached applies
+    #[serde(default)]
+    pub tiers: Vec<PromotionTier>,
+
+    pub starts_at: Option<DateTime<FixedOffset>>,
+    pub ends_at: Option<DateTime<FixedOffset>>,
+
+    /// Defaults to active on creation and to the current value on update
+    pub active: Option<bool>,
+
+    /// Products the promotion is limited to
+    #[serde(default)]
+    pub product_ids: Vec<i32>,
+
+    /// Categories the promotion is limited to
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+/// Struct-level validator making sure every kind of rule has what it needs
+fn validate_promotion_request(request: &PromotionRequest) -> Result<(), ValidationError> {
+    let missing = match request.kind {
+        PromotionKind::BuyXGetY => (request.buy_quantity.is_none()
+            || request.get_quantity.is_none())
+        .then_some("Buy X get Y promotions need a buy_quantity and a get_quantity"),
+        PromotionKind::BundlePrice => (request.bundle_quantity.is_none()
+            || request.bundle_price.is_none())
+        .then_some("Bundle promotions need a bundle_quantity and a bundle_price"),
+        PromotionKind::TieredPercentage => request
+            .tiers
+            .is_empty()
+            .then_some("Tiered promotions need at least one tier"),
+    };
+    if let Some(message) = missing {
+        let mut error = ValidationError::new("promotion_rule");
+        error.message = Some(message.into());
+        return Err(error);
+    }
+
+    if request
+        .bundle_price
+        .is_some_and(|price| price < Decimal::ZERO)
+    {
+        let mut error = ValidationError::new("bundle_price");
+        error.message = Some("Bundle price cannot be negative".into());
+        return Err(error);
+    }
+
+    if request.tiers.iter().any(|tier| {
+        tier.min_subtotal < Decimal::ZERO
+            || tier.percent_off <= Decimal::ZERO
+            || tier.percent_off > Decimal::ONE_HUNDRED
+    }) {
+        let mut error = ValidationError::new("tiers");
+        error.message =
+            Some("Tiers need a non-negative min_subtotal and a percent_off of 0-100".into());
+        return Err(error);
+    }
+
+    if let (Some(starts_at), Some(ends_at)) = (request.starts_at, request.ends_at) {
+        if starts_at >= ends_at {
+            let mut error = ValidationError::new("validity_window");
+            error.message = Some("starts_at must be before ends_at".into());
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Struct used to return a promotion
+#[derive(Debug, Serialize)]
+pub struct PromotionResponse {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: PromotionKind,
+    pub priority: i32,
+    pub stackable: bool,
+    pub combinable_with_coupons: bool,
+    pub buy_quantity: Option<i32>,
+    pub get_quantity: Option<i32>,
+    pub bundle_quantity: Option<i32>,
+    pub bundle_price: Option<Decimal>,
+    pub tiers: Vec<PromotionTier>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub active: bool,
+    pub product_ids: Vec<i32>,
+    pub categories: Vec<String>,
+}
+
+impl From<PromotionRule> for PromotionResponse {
+    fn from(rule: PromotionRule) -> Self {
+        let model = rule.promotion;
+        let mut product_ids: Vec<i32> = rule.product_ids.into_iter().collect();
+        product_ids.sort_unstable();
+        let mut categories: Vec<String> = rule.categories.into_iter().collect();
+        categories.sort();
+
+        PromotionResponse {
+            id: model.id,
+            name: model.name,
+            description: model.description,
+            kind: model.kind,
+            priority: model.priority,
+            stackable: model.stackable,
+            combinable_with_coupons: model.combinable_with_coupons,
+            buy_quantity: model.buy_quantity,
+            get_quantity: model.get_quantity,
+            bundle_quantity: model.bundle_quantity,
+            bundle_price: model.bundle_price,
+            tiers: rule.tiers,
+            starts_at: model.starts_at.map(|date| date.to_string()),
+            ends_at: model.ends_at.map(|date| date.to_string()),
+            active: model.active,
+            product_ids,
+            categories,
+        }
+    }
+}
+
+/// A promotion with its tiers and the products and categories it is limited to
+#[derive(Debug, Clone)]
+pub struct PromotionRule {
+    pub promotion: promotion::Model,
+    pub tiers: Vec<PromotionTier>,
+    pub product_ids: HashSet<i32>,
+    pub categories: HashSet<String>,
+}
+
+impl PromotionRule {
+    /// Whether the promotion covers a product; unrestricted promotions cover everything
+    fn applies_to(&self, product: &product::Model) -> bool {
+        if self.product_ids.is_empty() && self.categories.is_empty() {
+            return true;
+        }
+
+        self.product_ids.contains(&product.id)
+            || product
+                .category
+                .as_ref()
+                .is_some_and(|category| self.categories.contains(category))
+    }
+}
+
+/// A cart line as seen by the promotion engine
+pub struct PromotionLine<'a> {
+    pub cart_item_id: i32,
+    pub product: &'a product::Model,
+    pub quantity: i32,
+}
+
+/// The part of a promotion's discount given on one cart line
+#[derive(Debug, Clone, Serialize)]
+pub struct PromotionLineDiscount {
+    pub cart_item_id: i32,
+    pub product_id: i32,
+    pub amount: Decimal,
+    pub explanation: String,
+}
+
+/// A promotion that changed the price of the cart
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedPromotion {
+    pub promotion_id: i32,
+    pub name: String,
+    pub kind: PromotionKind,
+    pub amount: Decimal,
+    pub explanation: String,
+    pub lines: Vec<PromotionLineDiscount>,
+}
+
+/// A running promotion that did not change the price, and why
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedPromotion {
+    pub promotion_id: i32,
+    pub name: String,
+    pub reason: String,
+}
+
+/// Result of running the promotions over a cart
+#[derive(Debug, Default)]
+pub struct PromotionOutcome {
+    pub applied: Vec<AppliedPromotion>,
+    pub skipped: Vec<SkippedPromotion>,
+    /// Discount given on each line, in the order of the lines evaluated
+    pub line_discounts: Vec<Decimal>,
+}
+
+/// Why a promotion gave no discount
+type NotApplied = String;
+
+/// Units of one cart line sharing a unit price
+#[derive(Debug, Clone, Copy)]
+struct UnitRun {
+    index: usize,
+    price: Decimal,
+    count: usize,
+}
+
+/// Eligible units of a cart grouped by line, most expensive first.
+///
+/// Quantities can be large, so units are counted per line instead of being
+/// listed one by one.
+fn eligible_units(rule: &PromotionRule, lines: &[PromotionLine]) -> Vec<UnitRun> {
+    let mut runs: Vec<UnitRun> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.quantity > 0 && rule.applies_to(line.product))
+        .map(|(index, line)| UnitRun {
+            index,
+            price: line.product.price,
+            count: line.quantity as usize,
+        })
+        .collect();
+    runs.sort_by(|a, b| b.price.cmp(&a.price).then(a.index.cmp(&b.index)));
+    runs
+}
+
+/// Buy X get Y: in every group of X + Y eligible units the Y cheapest are free
+fn buy_x_get_y(
+    rule: &PromotionRule,
+    lines: &[PromotionLine],
+) -> Result<(Vec<Decimal>, Vec<String>, String), NotApplied> {
+    let (buy, get) = match (rule.promotion.buy_quantity, rule.promotion.get_quantity) {
+        (Some(buy), Some(get)) if buy > 0 && get > 0 => (buy as usize, get as usize),
+        _ => return Err("The promotion is not configured completely".to_string()),
+    };
+
+    let runs = eligible_units(rule, lines);
+    let unit_count: usize = runs.iter().map(|run| run.count).sum();
+    let free_count = unit_count / (buy + get) * get;
+    if free_count == 0 {
+        return Err(format!(
+            "Add {} more eligible unit(s) to get {} free",
+            buy + get - unit_count % (buy + get),
+            get
+        ));
+    }
+
+    // The cheapest units are the free ones
+    let mut discounts = vec![Decimal::ZERO; lines.len()];
+    let mut free_units = vec![0; lines.len()];
+    let mut left = free_count;
+    for run in runs.iter().rev() {
+        let free = run.count.min(left);
+        discounts[run.index] += run.price * Decimal::from(free);
+        free_units[run.index] += free;
+        left -= free;
+        if left == 0 {
+            break;
+        }
+    }
+
+    let explanations = free_units
+        .iter()
+        .map(|units| format!("{} unit(s) free", units))
+        .collect();
+    Ok((
+        discounts,
+        explanations,
+        format!(
+            "Buy {} get {} free: {} of {} eligible unit(s) free, cheapest first",
+            buy, get, free_count, unit_count
+        ),
+    ))
+}
+
+/// Bundle pricing: every full set of N eligible units costs the bundle price
+fn bundle_price(
+    rule: &PromotionRule,
+    lines: &[PromotionLine],
+) -> Result<(Vec<Decimal>, Vec<String>, String), NotApplied> {
+    let (size, price) = match (rule.promotion.bundle_quantity, rule.promotion.bundle_price) {
+        (Some(size), Some(price)) if size > 0 => (size as usize, price),
+        _ => return Err("The promotion is not configured completely".to_string()),
+    };
+
+    let mut runs = eligible_units(rule, lines);
+    let unit_count: usize = runs.iter().map(|run| run.count).sum();
+    let bundle_count = unit_count / size;
+    if bundle_count == 0 {
+        return Err(format!(
+            "Add {} more eligible unit(s) to complete a bundle",
+            size - unit_count
+        ));
+    }
+
+    // Bundles are filled most expensive first. Consecutive bundles taken from
+    // a single line are identical, so they are priced once and multiplied.
+    let mut discounts = vec![Decimal::ZERO; lines.len()];
+    let mut bundled_units = vec![0; lines.len()];
+    let mut bundles_with_saving = 0;
+    let mut bundles_left = bundle_count;
+    let mut next_run = 0;
+    while bundles_left > 0 {
+        let (bundle, repeat) = if runs[next_run].count >= size {
+            let repeat = (runs[next_run].count / size).min(bundles_left);
+            runs[next_run].count -= size * repeat;
+            (
+                vec![UnitRun {
+                    count: size,
+                    ..runs[next_run]
+                }],
+                repeat,
+            )
+        } else {
+            let mut bundle = Vec::new();
+            let mut needed = size;
+            while needed > 0 {
+                let taken = runs[next_run].count.min(needed);
+                bundle.push(UnitRun {
+                    count: taken,
+                    ..runs[next_run]
+                });
+                runs[next_run].count -= taken;
+                needed -= taken;
+                if runs[next_run].count == 0 {
+                    next_run += 1;
+                }
+            }
+            (bundle, 1)
+        };
+        if runs.get(next_run).is_some_and(|run| run.count == 0) {
+            next_run += 1;
+        }
+        bundles_left -= repeat;
+
+        let weights: Vec<Decimal> = bundle
+            .iter()
+            .map(|part| part.price * Decimal::from(part.count))
+            .collect();
+        let regular: Decimal = weights.iter().sum();
+        if regular <= price {
+            continue;
+        }
+        bundles_with_saving += repeat;
+
+        let shares = split_proportionally(regular - price, &weights);
+        for (part, share) in bundle.iter().zip(shares) {
+            discounts[part.index] += share * Decimal::from(repeat);
+            bundled_units[part.index] += part.count * repeat;
+        }
+    }
+    if bundles_with_saving == 0 {
+        return Err("The bundle price is not lower than the regular price".to_string());
+    }
+
+    let explanations = bundled_units
+        .iter()
+        .map(|units| format!("{} unit(s) priced as part of a bundle", units))
+        .collect();
+    Ok((
+        discounts,
+        explanations,
+        format!(
+            "{} bundle(s) of {} eligible unit(s) for {} each",
+            bundles_with_saving, size, price
+        ),
+    ))
+}
+
+/// Tiered percentage: the highest tier reached by the eligible items applies to them
+fn tiered_percentage(
+    rule: &PromotionRule,
+    lines: &[PromotionLine],
+    remaining: &[Decimal],
+) -> Result<(Vec<Decimal>, Vec<String>, String), NotApplied> {
+    let eligible: Vec<Decimal> = lines
+        .iter()
+        .zip(remaining)
+        .map(|(line, amount)| {
+            if rule.applies_to(line.product) {
+                *amount
+            } else {
+                Decimal::ZERO
+            }
+        })
+        .collect();
+    let eligible_total: Decimal = eligible.iter().sum();
+    if eligible_total <= Decimal::ZERO {
+        return Err("Earlier promotions already cover the items".to_string());
+    }
+
+    let Some(tier) = rule
+        .tiers
+        .iter()
+        .filter(|tier| tier.min_subtotal <= eligible_total)
+        .max_by(|a, b| a.min_subtotal.cmp(&b.min_subtotal))
+    else {
+        let lowest = rule.tiers.iter().map(|tier| tier.min_subtotal).min();
+        return Err(match lowest {
+            Some(lowest) => format!(
+                "Spend {} more on eligible items to reach the first tier",
+                lowest - eligible_total
+            ),
+            None => "The promotion is not configured completely".to_string(),
+        });
+    };
+    let discount = round_satang(eligible_total * tier.percent_off / Decimal::ONE_HUNDRED);
+    let explanations = vec![format!("{}% off", tier.percent_off.normalize()); lines.len()];
+    Ok((
+        split_proportionally(discount, &eligible),
+        explanations,
+        format!(
+            "{}% off because the eligible items total {} (tier from {})",
+            tier.percent_off.normalize(),
+            eligible_total,
+            tier.min_subtotal
+        ),
+    ))
+}
+
+/// Run the promotions over the lines of a cart.
+///
+/// Promotions are evaluated by descending priority, each on what is left to
+/// pay after the ones before it, and no line is ever discounted below zero.
+/// A non-stackable promotion only applies to an otherwise untouched cart and
+/// ends the evaluation. Every promotion that is running but gives nothing is
+/// reported with the reason, so support can explain the price of each line.
+pub fn evaluate_promotions(
+    rules: &[PromotionRule],
+    lines: &[PromotionLine],
+    has_coupon: bool,
+) -> PromotionOutcome {
+    let mut remaining: Vec<Decimal> = lines
+        .iter()
+        .map(|line| line.product.price * Decimal::from(line.quantity))
+        .collect();
+    let mut outcome = PromotionOutcome {
+        line_discounts: vec![Decimal::ZERO; lines.len()],
+        ..Default::default()
+    };
+
+    let mut rules: Vec<&PromotionRule> = rules.iter().collect();
+    rules.sort_by(|a, b| {
+        b.promotion
+            .priority
+            .cmp(&a.promotion.priority)
+            .then(a.promotion.id.cmp(&b.promotion.id))
+    });
+
+    let mut closed_by: Option<&str> = None;
+    for rule in rules {
+        let promotion = &rule.promotion;
+        let skip = |reason: String| SkippedPromotion {
+            promotion_id: promotion.id,
+            name: promotion.name.clone(),
+            reason,
+        };
+
+        if let Some(exclusive) = closed_by {
+            outcome.skipped.push(skip(format!(
+                "Not combined with the exclusive promotion \"{}\"",
+                exclusive
+            )));
+            continue;
+        }
+        if !promotion.stackable && !outcome.applied.is_empty() {
+            outcome
+                .skipped
+                .push(skip("Not combined with other promotions".to_string()));
+            continue;
+        }
+        if has_coupon && !promotion.combinable_with_coupons {
+            outcome
+                .skipped
+                .push(skip("Not combined with discount codes".to_string()));
+            continue;
+        }
+        if !lines.iter().any(|line| rule.applies_to(line.product)) {
+            outcome
+                .skipped
+                .push(skip("No eligible items in the cart".to_string()));
+            continue;
+        }
+
+        let result = match promotion.kind {
+            PromotionKind::BuyXGetY => buy_x_get_y(rule, lines),
+            PromotionKind::BundlePrice => bundle_price(rule, lines),
+            PromotionKind::TieredPercentage => tiered_percentage(rule, lines, &remaining),
+        };
+        let (discounts, explanations, explanation) = match result {
+            Ok(result) => result,
+            Err(reason) => {
+                outcome.skipped.push(skip(reason));
+                continue;
+            }
+        };
+
+        let mut applied_lines = Vec::new();
+        for (index, (discount, line_explanation)) in
+            discounts.into_iter().zip(explanations).enumerate()
+        {
+            let amount = discount.min(remaining[index]);
+            if amount <= Decimal::ZERO {
+                continue;
+            }
+            remaining[index] -= amount;
+            outcome.line_discounts[index] += amount;
+            applied_lines.push(PromotionLineDiscount {
+                cart_item_id: lines[index].cart_item_id,
+                product_id: lines[index].product.id,
+                amount,
+                explanation: line_explanation,
+            });
+        }
+
+        if applied_lines.is_empty() {
+            outcome.skipped.push(skip(
+                "Earlier promotions already cover the items".to_string(),
+            ));
+            continue;
+        }
+
+        outcome.applied.push(AppliedPromotion {
+            promotion_id: promotion.id,
+            name: promotion.name.clone(),
+            kind: promotion.kind,
+            amount: applied_lines.iter().map(|line| line.amount).sum(),
+            explanation,
+            lines: applied_lines,
+        });
+        if !promotion.stackable {
+            closed_by = Some(&promotion.name);
+        }
+    }
+
+    outcome
+}
+
+/// Load promotions with their tiers and restrictions
+async fn load_rules<C: ConnectionTrait>(
+    conn: &C,
+    promotions: Vec<promotion::Model>,
+) -> Result<Vec<PromotionRule>, ApiError> {
+    let ids: Vec<i32> = promotions.iter().map(|promotion| promotion.id).collect();
+
+    let mut product_ids: HashMap<i32, HashSet<i32>> = HashMap::new();
+    for restriction in promotion_product::Entity::find()
+        .filter(promotion_product::Column::PromotionId.is_in(ids.clone()))
+        .all(conn)
+        .await?
+    {
+        product_ids
+            .entry(restriction.promotion_id)
+            .or_default()
+            .insert(restriction.product_id);
+    }
+    let mut categories: HashMap<i32, HashSet<String>> = HashMap::new();
+    for restriction in promotion_category::Entity::find()
+        .filter(promotion_category::Column::PromotionId.is_in(ids))
+        .all(conn)
+        .await?
+    {
+        categories
+            .entry(restriction.promotion_id)
+            .or_default()
+            .insert(restriction.category);
+    }
+
+    Ok(promotions
+        .into_iter()
+        .map(|promotion| PromotionRule {
+            tiers: promotion
+                .tiers
+                .clone()
+                .and_then(|tiers| serde_json::from_value(tiers).ok())
+                .unwrap_or_default(),
+            product_ids: product_ids.remove(&promotion.id).unwrap_or_default(),
+            categories: categories.remove(&promotion.id).unwrap_or_default(),
+            promotion,
+        })
+        .collect())
+}
+
+/// Promotions running at the given time
+pub async fn running_promotions<C: ConnectionTrait>(
+    conn: &C,
+    now: DateTime<FixedOffset>,
+) -> Result<Vec<PromotionRule>, ApiError> {
+    let promotions = promotion::Entity::find()
+        .filter(promotion::Column::Active.eq(true))
+        .filter(
+            Condition::any()
+                .add(promotion::Column::StartsAt.is_null())
+                .add(promotion::Column::StartsAt.lte(now)),
+        )
+        .filter(
+            Condition::any()
+                .add(promotion::Column::EndsAt.is_null())
+                .add(promotion::Column::EndsAt.gt(now)),
+        )
+        .all(conn)
+        .await?;
+
+    load_rules(conn, promotions).await
+}
+
+/// Replace the product and category restrictions of a promotion
+async fn set_restrictions<C: ConnectionTrait>(
+    conn: &C,
+    promotion_id: i32,
+    product_ids: Vec<i32>,
+    categories: Vec<String>,
+) -> Result<(), ApiError> {
+    promotion_product::Entity::delete_many()
+        .filter(promotion_product::Column::PromotionId.eq(promotion_id))
+        .exec(conn)
+        .await?;
+    promotion_category::Entity::delete_many()
+        .filter(promotion_category::Column::PromotionId.eq(promotion_id))
+        .exec(conn)
+        .await?;
+
+    let product_ids: HashSet<i32> = product_ids.into_iter().collect();
+    if !product_ids.is_empty() {
+        let found = product::Entity::find()
+            .filter(product::Column::Id.is_in(product_ids.iter().copied()))
+            .count(conn)
+            .await?;
+        if found != product_ids.len() as u64 {
+            return Err(ApiError::NotFound(
+                "One or more restricted products do not exist".to_string(),
+            ));
+        }
+
+        promotion_product::Entity::insert_many(product_ids.into_iter().map(|product_id| {
+            promotion_product::ActiveModel {
+                promotion_id: Set(promotion_id),
+                product_id: Set(product_id),
+                ..Default::default()
+            }
+        }))
+        .exec(conn)
+        .await?;
+    }
+
+    let categories: HashSet<String> = categories
+        .into_iter()
+        .map(|category| category.trim().to_string())
+        .filter(|category| !category.is_empty())
+        .collect();
+    if !categories.is_empty() {
+        promotion_category::Entity::insert_many(categories.into_iter().map(|category| {
+            promotion_category::ActiveModel {
+                promotion_id: Set(promotion_id),
+                category: Set(category),
+                ..Default::default()
+            }
+        }))
+        .exec(conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Tiers as stored on the promotion; only tiered promotions keep them
+fn tiers_to_json(
+    kind: PromotionKind,
+    tiers: Vec<PromotionTier>,
+) -> Result<Option<serde_json::Value>, ApiError> {
+    if kind != PromotionKind::TieredPercentage {
+        return Ok(None);
+    }
+
+    serde_json::to_value(tiers)
+        .map(Some)
+        .map_err(|_| ApiError::InternalServerError("Failed to store the tiers".to_string()))
+}
+
+/// Service function to list every promotion (staff only)
+pub async fn get_all_promotions(
+    db: &DatabaseConnection,
+) -> Result<Vec<PromotionResponse>, ApiError> {
+    let promotions = promotion::Entity::find()
+        .order_by_desc(promotion::Column::Priority)
+        .order_by_asc(promotion::Column::Id)
+        .all(db)
+        .await?;
+
+    Ok(load_rules(db, promotions)
+        .await?
+        .into_iter()
+        .map(PromotionResponse::from)
+        .collect())
+}
+
+/// Service function to add a promotion
+pub async fn create_promotion(
+    db: &DatabaseConnection,
+    request: PromotionRequest,
+) -> Result<PromotionResponse, ApiError> {
+    let txn = db.begin().await?;
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let promotion = promotion::ActiveModel {
+        name: Set(request.name),
+        description: Set(request.description),
+        kind: Set(request.kind),
+        priority: Set(request.priority),
+        stackable: Set(request.stackable.unwrap_or(true)),
+        combinable_with_coupons: Set(request.combinable_with_coupons.unwrap_or(true)),
+        buy_quantity: Set(request.buy_quantity),
+        get_quantity: Set(request.get_quantity),
+        bundle_quantity: Set(request.bundle_quantity),
+        bundle_price: Set(request.bundle_price),
+        tiers: Set(tiers_to_json(request.kind, request.tiers)?),
+        starts_at: Set(request.starts_at),
+        ends_at: Set(request.ends_at),
+        active: Set(request.active.unwrap_or(true)),
+        created_at: Set(now_fixed),
+        updated_at: Set(now_fixed),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    set_restrictions(&txn, promotion.id, request.product_ids, request.categories).await?;
+    let rule = load_rules(&txn, vec![promotion]).await?.remove(0);
+
+    txn.commit().await?;
+
+    Ok(PromotionResponse::from(rule))
+}
+
+/// Service function to change a promotion; orders already placed keep their prices
+pub async fn update_promotion(
+    db: &DatabaseConnection,
+    promotion_id: i32,
+    request: PromotionRequest,
+) -> Result<PromotionResponse, ApiError> {
+    let txn = db.begin().await?;
+    let existing = promotion::Entity::find_by_id(promotion_id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Promotion with ID {} not found", promotion_id))
+        })?;
+
+    let stackable = request.stackable.unwrap_or(existing.stackable);
+    let combinable_with_coupons = request
+        .combinable_with_coupons
+        .unwrap_or(existing.combinable_with_coupons);
+    let active = request.active.unwrap_or(existing.active);
+    let mut active_model: promotion::ActiveModel = existing.into();
+    active_model.name = Set(request.name);
+    active_model.description = Set(request.description);
+    active_model.kind = Set(request.kind);
+    active_model.priority = Set(request.priority);
+    active_model.stackable = Set(stackable);
+    active_model.combinable_with_coupons = Set(combinable_with_coupons);
+    active_model.buy_quantity = Set(request.buy_quantity);
+    active_model.get_quantity = Set(request.get_quantity);
+    active_model.bundle_quantity = Set(request.bundle_quantity);
+    active_model.bundle_price = Set(request.bundle_price);
+    active_model.tiers = Set(tiers_to_json(request.kind, request.tiers)?);
+    active_model.starts_at = Set(request.starts_at);
+    active_model.ends_at = Set(request.ends_at);
+    active_model.active = Set(active);
+    active_model.updated_at = Set(Utc::now().into());
+    let promotion = active_model.update(&txn).await?;
+    set_restrictions(&txn, promotion.id, request.product_ids, request.categories).await?;
+    let rule = load_rules(&txn, vec![promotion]).await?.remove(0);
+
+    txn.commit().await?;
+
+    Ok(PromotionResponse::from(rule))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::sea_orm_active_enums::ProductStatus;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn product(id: i32, price: &str) -> product::Model {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        product::Model {
+            id,
+            name: format!("Product {}", id),
+            description: None,
+            price: dec(price),
+            created_at: now,
+            updated_at: now,
+            category: None,
+            status: ProductStatus::Published,
+            available_from: None,
+            available_until: None,
+            max_quantity: None,
+            stock: None,
+            weight_grams: None,
+            tax_class_id: None,
+        }
+    }
+
+    fn rule(id: i32, kind: PromotionKind, priority: i32) -> PromotionRule {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        PromotionRule {
+            promotion: promotion::Model {
+                id,
+                name: format!("Promotion {}", id),
+                description: None,
+                kind,
+                priority,
+                stackable: true,
+                combinable_with_coupons: true,
+                buy_quantity: None,
+                get_quantity: None,
+                bundle_quantity: None,
+                bundle_price: None,
+                tiers: None,
+                starts_at: None,
+                ends_at: None,
+                active: true,
+                created_at: now,
+                updated_at: now,
+            },
+            tiers: Vec::new(),
+            product_ids: HashSet::new(),
+            categories: HashSet::new(),
+        }
+    }
+
+    fn buy_x_get_y_rule(id: i32, priority: i32, buy: i32, get: i32) -> PromotionRule {
+        let mut rule = rule(id, PromotionKind::BuyXGetY, priority);
+        rule.promotion.buy_quantity = Some(buy);
+        rule.promotion.get_quantity = Some(get);
+        rule
+    }
+
+    fn bundle_rule(id: i32, priority: i32, size: i32, price: &str) -> PromotionRule {
+        let mut rule = rule(id, PromotionKind::BundlePrice, priority);
+        rule.promotion.bundle_quantity = Some(size);
+        rule.promotion.bundle_price = Some(dec(price));
+        rule
+    }
+
+    fn tiered_rule(id: i32, priority: i32, percent_off: &str) -> PromotionRule {
+        let mut rule = rule(id, PromotionKind::TieredPercentage, priority);
+        rule.tiers = vec![PromotionTier {
+            min_subtotal: Decimal::ZERO,
+            percent_off: dec(percent_off),
+        }];
+        rule
+    }
+
+    fn line(cart_item_id: i32, product: &product::Model, quantity: i32) -> PromotionLine<'_> {
+        PromotionLine {
+            cart_item_id,
+            product,
+            quantity,
+        }
+    }
+
+    fn applied_ids(outcome: &PromotionOutcome) -> Vec<i32> {
+        outcome
+            .applied
+            .iter()
+            .map(|applied| applied.promotion_id)
+            .collect()
+    }
+
+    #[test]
+    fn buy_x_get_y_gives_the_cheapest_units_free() {
+        let (shirt, sock) = (product(1, "100"), product(2, "50"));
+        let lines = [line(1, &shirt, 2), line(2, &sock, 1)];
+
+        let outcome = evaluate_promotions(&[buy_x_get_y_rule(1, 0, 2, 1)], &lines, false);
+
+        assert_eq!(outcome.line_discounts, vec![Decimal::ZERO, dec("50")]);
+    }
+
+    #[test]
+    fn buy_x_get_y_handles_large_quantities() {
+        let pen = product(1, "10");
+        let lines = [line(1, &pen, 1_000_000_000)];
+
+        let outcome = evaluate_promotions(&[buy_x_get_y_rule(1, 0, 1, 1)], &lines, false);
+
+        assert_eq!(outcome.line_discounts, vec![dec("5000000000")]);
+    }
+
+    #[test]
+    fn bundle_price_shares_the_saving_across_the_bundle() {
+        let (mug, cup) = (product(1, "50"), product(2, "30"));
+        let lines = [line(1, &mug, 2), line(2, &cup, 2)];
+
+        // One bundle of mug, mug, cup (130 for 100); the last cup is left over
+        let outcome = evaluate_promotions(&[bundle_rule(1, 0, 3, "100")], &lines, false);
+
+        assert_eq!(outcome.line_discounts, vec![dec("23.08"), dec("6.92")]);
+    }
+
+    #[test]
+    fn bundle_price_repeats_bundles_of_one_line() {
+        let (mug, cup) = (product(1, "40"), product(2, "25"));
+        let lines = [line(1, &mug, 5), line(2, &cup, 3)];
+
+        // Mug pairs twice, then mug + cup, then cup + cup: 20 + 20 + 5 + 0
+        let outcome = evaluate_promotions(&[bundle_rule(1, 0, 2, "60")], &lines, false);
+
+        assert_eq!(outcome.applied[0].amount, dec("45"));
+        assert_eq!(outcome.line_discounts.iter().sum::<Decimal>(), dec("45"));
+        assert!(outcome.applied[0].explanation.starts_with("3 bundle(s)"));
+    }
+
+    #[test]
+    fn promotions_stack_by_descending_priority() {
+        let shirt = product(1, "100");
+        let lines = [line(1, &shirt, 2)];
+        let rules = [tiered_rule(1, 0, "10"), buy_x_get_y_rule(2, 10, 1, 1)];
+
+        let outcome = evaluate_promotions(&rules, &lines, false);
+
+        // The tier applies to what the free unit left to pay
+        assert_eq!(applied_ids(&outcome), vec![2, 1]);
+        assert_eq!(outcome.applied[0].amount, dec("100"));
+        assert_eq!(outcome.applied[1].amount, dec("10"));
+        assert_eq!(outcome.line_discounts, vec![dec("110")]);
+    }
+
+    #[test]
+    fn equal_priorities_are_evaluated_by_id() {
+        let shirt = product(1, "100");
+        let lines = [line(1, &shirt, 1)];
+        let rules = [tiered_rule(2, 5, "10"), tiered_rule(1, 5, "20")];
+
+        let outcome = evaluate_promotions(&rules, &lines, false);
+
+        assert_eq!(applied_ids(&outcome), vec![1, 2]);
+        assert_eq!(outcome.applied[1].amount, dec("8"));
+    }
+
+    #[test]
+    fn exclusive_promotion_stops_the_evaluation() {
+        let shirt = product(1, "100");
+        let lines = [line(1, &shirt, 1)];
+        let mut exclusive = tiered_rule(1, 10, "20");
+        exclusive.promotion.stackable = false;
+        let rules = [exclusive, tiered_rule(2, 0, "10")];
+
+        let outcome = evaluate_promotions(&rules, &lines, false);
+
+        assert_eq!(applied_ids(&outcome), vec![1]);
+        assert_eq!(outcome.skipped[0].promotion_id, 2);
+        assert!(outcome.skipped[0].reason.contains("exclusive"));
+    }
+
+    #[test]
+    fn exclusive_promotion_is_skipped_after_another_applied() {
+        let shirt = product(1, "100");
+        let lines = [line(1, &shirt, 1)];
+        let mut exclusive = tiered_rule(2, 0, "20");
+        exclusive.promotion.stackable = false;
+        let rules = [tiered_rule(1, 10, "10"), exclusive];
+
+        let outcome = evaluate_promotions(&rules, &lines, false);
+
+        assert_eq!(applied_ids(&outcome), vec![1]);
+        assert_eq!(
+            outcome.skipped[0].reason,
+            "Not combined with other promotions"
+        );
+    }
+
+    #[test]
+    fn promotions_not_combinable_with_coupons_are_skipped_with_a_code() {
+        let shirt = product(1, "100");
+        let lines = [line(1, &shirt, 1)];
+        let mut rule = tiered_rule(1, 0, "10");
+        rule.promotion.combinable_with_coupons = false;
+
+        let outcome = evaluate_promotions(std::slice::from_ref(&rule), &lines, true);
+        assert!(outcome.applied.is_empty());
+        assert_eq!(
+            outcome.skipped[0].reason,
+            "Not combined with discount codes"
+        );
+
+        let outcome = evaluate_promotions(&[rule], &lines, false);
+        assert_eq!(applied_ids(&outcome), vec![1]);
+    }
+}
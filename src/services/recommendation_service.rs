@@ -0,0 +1,220 @@
+use crate::entities::{cart_item, product, product_recommendation};
+use crate::models::product::ProductResponse;
+use crate::services::product_service::{publicly_listed, publicly_visible};
+use crate::utils::actix_error::ApiError;
+use chrono::Utc;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Statement, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use validator::Validate;
+
+/// Query parameters accepted by the recommendation endpoints
+#[derive(Debug, Deserialize, Validate)]
+pub struct RelatedProductsQuery {
+    #[validate(range(min = 1, max = 20, message = "Limit must be between 1 and 20"))]
+    pub limit: Option<u64>,
+}
+
+impl RelatedProductsQuery {
+    pub fn limit(&self) -> u64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT)
+    }
+}
+
+const DEFAULT_LIMIT: u64 = 5;
+
+/// Why a product was recommended
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationSource {
+    /// Bought together with the product in past orders
+    BoughtTogether,
+    /// Filler from the same category when order history is sparse
+    SameCategory,
+}
+
+/// Struct used to return a recommended product in the response
+#[derive(Debug, Serialize)]
+pub struct RelatedProductResponse {
+    #[serde(flatten)]
+    pub product: ProductResponse,
+    pub score: i32,
+    pub source: RecommendationSource,
+}
+
+/// Rebuild the `product_recommendation` table from the order history.
+///
+/// The score of a pair is the number of distinct orders containing both products.
+pub async fn refresh_recommendations(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let txn = db.begin().await?;
+
+    product_recommendation::Entity::delete_many()
+        .exec(&txn)
+        .await?;
+
+    let result = txn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO product_recommendation (product_id, related_product_id, score, updated_at)
+            SELECT a.product_id, b.product_id, COUNT(DISTINCT a.order_id)::int, NOW()
+            FROM order_item a
+            JOIN order_item b ON a.order_id = b.order_id AND a.product_id <> b.product_id
+            GROUP BY a.product_id, b.product_id
+            "#,
+        ))
+        .await?;
+
+    txn.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Fetch products by ID, keeping the order of `scored_ids`
+async fn load_scored_products(
+    db: &DatabaseConnection,
+    scored_ids: Vec<(i32, i32)>,
+) -> Result<Vec<RelatedProductResponse>, ApiError> {
+    let ids: Vec<i32> = scored_ids.iter().map(|(id, _)| *id).collect();
+    let mut products: HashMap<i32, product::Model> = product::Entity::find()
+        .filter(product::Column::Id.is_in(ids))
+        .filter(publicly_listed(Utc::now().into()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|product| (product.id, product))
+        .collect();
+
+    Ok(scored_ids
+        .into_iter()
+        .filter_map(|(id, score)| {
+            products.remove(&id).map(|product| RelatedProductResponse {
+                product: ProductResponse::from(product),
+                score,
+                source: RecommendationSource::BoughtTogether,
+            })
+        })
+        .collect())
+}
+
+/// Top up `related` with products from the given categories until it holds `limit` entries
+async fn fill_with_same_category(
+    db: &DatabaseConnection,
+    related: &mut Vec<RelatedProductResponse>,
+    categories: Vec<String>,
+    mut exclude_ids: Vec<i32>,
+    limit: u64,
+) -> Result<(), ApiError> {
+    let remaining = limit.saturating_sub(related.len() as u64);
+    if remaining == 0 || categories.is_empty() {
+        return Ok(());
+    }
+
+    exclude_ids.extend(related.iter().map(|item| item.product.id));
+
+    let fillers = product::Entity::find()
+        .filter(product::Column::Category.is_in(categories))
+        .filter(product::Column::Id.is_not_in(exclude_ids))
+        .filter(publicly_listed(Utc::now().into()))
+        .order_by_desc(product::Column::CreatedAt)
+        .limit(remaining)
+        .all(db)
+        .await?;
+
+    related.extend(fillers.into_iter().map(|product| RelatedProductResponse {
+        product: ProductResponse::from(product),
+        score: 0,
+        source: RecommendationSource::SameCategory,
+    }));
+
+    Ok(())
+}
+
+/// Products frequently bought together with the given product
+pub async fn get_related_products(
+    db: &DatabaseConnection,
+    product_id: i32,
+    limit: u64,
+) -> Result<Vec<RelatedProductResponse>, ApiError> {
+    let product = product::Entity::find_by_id(product_id)
+        .filter(publicly_visible(Utc::now().into()))
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Product with ID {} not found", product_id)))?;
+
+    let scored_ids = product_recommendation::Entity::find()
+        .filter(product_recommendation::Column::ProductId.eq(product_id))
+        .order_by_desc(product_recommendation::Column::Score)
+        .order_by_asc(product_recommendation::Column::RelatedProductId)
+        .limit(limit)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|rec| (rec.related_product_id, rec.score))
+        .collect();
+
+    let mut related = load_scored_products(db, scored_ids).await?;
+
+    fill_with_same_category(
+        db,
+        &mut related,
+        product.category.into_iter().collect(),
+        vec![product_id],
+        limit,
+    )
+    .await?;
+
+    Ok(related)
+}
+
+/// Products frequently bought together with anything already in the cart
+pub async fn get_cart_recommendations(
+    db: &DatabaseConnection,
+    cart_id: i32,
+    limit: u64,
+) -> Result<Vec<RelatedProductResponse>, ApiError> {
+    let cart_products: Vec<(cart_item::Model, Option<product::Model>)> = cart_item::Entity::find()
+        .filter(cart_item::Column::CartId.eq(cart_id))
+        .find_also_related(product::Entity)
+        .all(db)
+        .await?;
+
+    if cart_products.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let in_cart: Vec<i32> = cart_products
+        .iter()
+        .map(|(item, _)| item.product_id)
+        .collect();
+    let mut categories: Vec<String> = cart_products
+        .into_iter()
+        .filter_map(|(_, product)| product.and_then(|product| product.category))
+        .collect();
+    categories.sort();
+    categories.dedup();
+
+    // Sum the scores of every cart product's neighbours
+    let mut scores: HashMap<i32, i32> = HashMap::new();
+    let recommendations = product_recommendation::Entity::find()
+        .filter(product_recommendation::Column::ProductId.is_in(in_cart.clone()))
+        .filter(product_recommendation::Column::RelatedProductId.is_not_in(in_cart.clone()))
+        .all(db)
+        .await?;
+    for rec in recommendations {
+        *scores.entry(rec.related_product_id).or_insert(0) += rec.score;
+    }
+
+    let mut scored_ids: Vec<(i32, i32)> = scores.into_iter().collect();
+    scored_ids.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored_ids.truncate(limit as usize);
+
+    let mut related = load_scored_products(db, scored_ids).await?;
+
+    fill_with_same_category(db, &mut related, categories, in_cart, limit).await?;
+
+    Ok(related)
+}
@@ -0,0 +1,615 @@
+use super::order_service::OrderModel;
+use super::tax_service::round_satang;
+use crate::entities::sea_orm_active_enums::{
+    FulfilmentStatus, OrderStatus, PaymentStatus, RefundStatus,
+};
+use crate::entities::{order, order_item, refund, refund_item};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::payment_provider::PaymentProvider;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use validator::{Validate, ValidationError};
+
+/// Struct for refunding some units of an order line
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefundItemRequest {
+    #[validate(range(min = 1, message = "Order item ID must be at least 1"))]
+    pub order_item_id: i32,
+
+    #[validate(range(min = 1, message = "Quantity must be at least 1"))]
+    pub quantity: i32,
+}
+
+/// Struct for recording a refund (staff only)
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_refund_request"))]
+pub struct CreateRefundRequest {
+    /// Amount to return; defaults to the value of the refunded items
+    pub amount: Option<Decimal>,
+
+    /// Order lines the refund covers; empty for a refund of an amount only
+    #[serde(default)]
+    #[validate(nested)]
+    pub items: Vec<RefundItemRequest>,
+
+    #[validate(length(min = 1, max = 500, message = "Reason must be 1-500 characters"))]
+    pub reason: Option<String>,
+}
+
+/// Struct-level validator making sure a refund says what it returns
+fn validate_refund_request(request: &CreateRefundRequest) -> Result<(), ValidationError> {
+    if request.amount.is_none() && request.items.is_empty() {
+        let mut error = ValidationError::new("refund_empty");
+        error.message = Some("Give an amount or the items to refund".into());
+        return Err(error);
+    }
+
+    if request.amount.is_some_and(|amount| amount <= Decimal::ZERO) {
+        let mut error = ValidationError::new("refund_amount");
+        error.message = Some("Refund amount must be a positive value".into());
+        return Err(error);
+    }
+
+    let mut seen = HashSet::new();
+    if !request
+        .items
+        .iter()
+        .all(|item| seen.insert(item.order_item_id))
+    {
+        let mut error = ValidationError::new("refund_items");
+        error.message = Some("Each order item may only be listed once".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// One refunded order line
+#[derive(Debug, Serialize)]
+pub struct RefundItemResponse {
+    pub order_item_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    pub value: Decimal,
+}
+
+/// A refund recorded for an order
+#[derive(Debug, Serialize)]
+pub struct RefundResponse {
+    pub id: i32,
+    pub amount: Decimal,
+    pub reason: Option<String>,
+    pub provider_reference: Option<String>,
+    pub status: RefundStatus,
+    pub created_by: i32,
+    pub created_at: String,
+    pub items: Vec<RefundItemResponse>,
+}
+
+/// The refunds of an order and how much of its payment is left to refund
+#[derive(Debug, Serialize)]
+pub struct OrderRefundsResponse {
+    pub order: OrderModel,
+    pub refunded_total: Decimal,
+    pub refundable_amount: Decimal,
+    pub refunds: Vec<RefundResponse>,
+}
+
+/// What the customer paid for some units of an order line, after discount and VAT
+fn units_value(line: &order_item::Model, quantity: i32) -> Decimal {
+    round_satang(line.gross_amount * Decimal::from(quantity) / Decimal::from(line.quantity))
+}
+
+/// Value of the order lines a refund covers, making sure no line is refunded
+/// for more units than are left on it
+fn refunded_items_value(
+    order_id: i32,
+    items: &[RefundItemRequest],
+    order_items: &HashMap<i32, order_item::Model>,
+    already_refunded: &HashMap<i32, i32>,
+) -> Result<Decimal, ApiError> {
+    let mut items_value = Decimal::ZERO;
+    for item in items {
+        let line = order_items.get(&item.order_item_id).ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Order item with ID {} not found in order {}",
+                item.order_item_id, order_id
+            ))
+        })?;
+
+        let refundable_units = line.quantity - already_refunded.get(&line.id).copied().unwrap_or(0);
+        if item.quantity > refundable_units {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "Only {} unit(s) of order item {} can still be refunded",
+                refundable_units, line.id
+            )));
+        }
+
+        items_value += units_value(line, item.quantity);
+    }
+
+    Ok(items_value)
+}
+
+/// Amount a refund returns: the requested amount, or the value of its items.
+///
+/// A refund of items never returns more than they are worth, and no refund
+/// returns more than is left of the order's payment.
+fn refund_amount(
+    order_id: i32,
+    request: &CreateRefundRequest,
+    items_value: Decimal,
+    refundable_amount: Decimal,
+) -> Result<Decimal, ApiError> {
+    let amount = request.amount.unwrap_or(items_value);
+    if !request.items.is_empty() && amount > items_value {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "Refund of {} exceeds the {} value of the refunded items",
+            amount, items_value
+        )));
+    }
+
+    if amount > refundable_amount {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "Refund of {} exceeds the {} left to refund on order {}",
+            amount, refundable_amount, order_id
+        )));
+    }
+
+    Ok(amount)
+}
+
+/// Amount of the refunds of an order that are in one of `statuses`
+async fn refunds_total<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i32,
+    statuses: &[RefundStatus],
+) -> Result<Decimal, ApiError> {
+    Ok(refund::Entity::find()
+        .filter(refund::Column::OrderId.eq(order_id))
+        .filter(refund::Column::Status.is_in(statuses.iter().copied()))
+        .all(conn)
+        .await?
+        .iter()
+        .map(|refund| refund.amount)
+        .sum())
+}
+
+/// Units of each order line of an order that were already refunded (or are being refunded)
+async fn refunded_quantities<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i32,
+) -> Result<HashMap<i32, i32>, ApiError> {
+    let refund_items = refund_item::Entity::find()
+        .inner_join(refund::Entity)
+        .filter(refund::Column::OrderId.eq(order_id))
+        .filter(refund::Column::Status.ne(RefundStatus::Failed))
+        .all(conn)
+        .await?;
+
+    let mut quantities = HashMap::new();
+    for item in refund_items {
+        *quantities.entry(item.order_item_id).or_insert(0) += item.quantity;
+    }
+
+    Ok(quantities)
+}
+
+/// Service function to return money to the customer of an order.
+///
+/// The refund goes through the payment provider and may cover specific order
+/// lines; all refunds together never exceed what was paid for the order. The
+/// payment becomes refunded once everything has been returned and partially
+/// refunded before that.
+///
+/// The refund is recorded as pending before the provider is asked to send the
+/// money, and marked completed (or failed) once it answers, so money never
+/// leaves without a record of it.
+pub async fn create_refund(
+    db: &DatabaseConnection,
+    payment_provider: &dyn PaymentProvider,
+    user: &AuthenticatedUser,
+    order_id: i32,
+    request: CreateRefundRequest,
+) -> Result<OrderRefundsResponse, ApiError> {
+    let txn = db.begin().await?;
+
+    // Lock the order so concurrent refunds cannot exceed the payment together
+    let order = order::Entity::find_by_id(order_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
+
+    if !matches!(
+        order.payment_status,
+        PaymentStatus::Paid | PaymentStatus::PartiallyRefunded
+    ) {
+        return Err(ApiError::Conflict(format!(
+            "Order {} has no payment left to refund",
+            order_id
+        )));
+    }
+
+    let order_items: HashMap<i32, order_item::Model> = order_item::Entity::find()
+        .filter(order_item::Column::OrderId.eq(order_id))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|item| (item.id, item))
+        .collect();
+    let already_refunded = refunded_quantities(&txn, order_id).await?;
+
+    let items_value =
+        refunded_items_value(order_id, &request.items, &order_items, &already_refunded)?;
+
+    // Refunds still waiting for the provider count as spent
+    let refunded_total = refunds_total(
+        &txn,
+        order_id,
+        &[RefundStatus::Pending, RefundStatus::Completed],
+    )
+    .await?;
+    let amount = refund_amount(
+        order_id,
+        &request,
+        items_value,
+        order.total_amount - refunded_total,
+    )?;
+
+    let new_refund = refund::ActiveModel {
+        order_id: Set(order.id),
+        amount: Set(amount),
+        reason: Set(request.reason),
+        provider_reference: Set(None),
+        status: Set(RefundStatus::Pending),
+        created_by: Set(user.id),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    for item in request.items {
+        refund_item::ActiveModel {
+            refund_id: Set(new_refund.id),
+            order_item_id: Set(item.order_item_id),
+            quantity: Set(item.quantity),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+    }
+
+    txn.commit().await?;
+
+    let refund_id = new_refund.id;
+    match payment_provider.refund(order_id, amount).await {
+        Ok(provider_reference) => {
+            complete_refund(db, new_refund, provider_reference.clone())
+                .await
+                .map_err(|e| {
+                    tracing::error!(
+                        "Refund {} of order {} was sent ({}) but could not be marked completed: {}",
+                        refund_id,
+                        order_id,
+                        provider_reference,
+                        e
+                    );
+                    e
+                })?;
+        }
+        Err(e) => {
+            let mut failed: refund::ActiveModel = new_refund.into();
+            failed.status = Set(RefundStatus::Failed);
+            if let Err(db_error) = failed.update(db).await {
+                tracing::error!(
+                    "Refund {} of order {} failed but could not be marked failed: {}",
+                    refund_id,
+                    order_id,
+                    db_error
+                );
+            }
+            return Err(ApiError::InternalServerError(format!(
+                "Refund failed: {}",
+                e
+            )));
+        }
+    }
+
+    build_order_refunds(db, order_id).await
+}
+
+/// Mark a refund sent by the provider as completed and move the order's
+/// payment status along
+async fn complete_refund(
+    db: &DatabaseConnection,
+    pending: refund::Model,
+    provider_reference: String,
+) -> Result<(), ApiError> {
+    let txn = db.begin().await?;
+
+    let order = order::Entity::find_by_id(pending.order_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Order with ID {} not found", pending.order_id))
+        })?;
+
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+    let mut completed: refund::ActiveModel = pending.into();
+    completed.status = Set(RefundStatus::Completed);
+    completed.provider_reference = Set(Some(provider_reference));
+    completed.update(&txn).await?;
+
+    let refunded_total = refunds_total(&txn, order.id, &[RefundStatus::Completed]).await?;
+    let fully_refunded = refunded_total >= order.total_amount;
+    let (status, fulfilment_status) = (order.status, order.fulfilment_status);
+    let mut active_model: order::ActiveModel = order.into();
+    if fully_refunded {
+        active_model.payment_status = Set(PaymentStatus::Refunded);
+        // Cancelled orders stay cancelled; the others end as refunded
+        if status != OrderStatus::Cancelled {
+            active_model.status = Set(OrderStatus::Refunded);
+        }
+        // Goods that have not left the warehouse are no longer sent
+        if matches!(
+            fulfilment_status,
+            FulfilmentStatus::Unfulfilled | FulfilmentStatus::Processing
+        ) {
+            active_model.fulfilment_status = Set(FulfilmentStatus::Cancelled);
+        }
+    } else {
+        active_model.payment_status = Set(PaymentStatus::PartiallyRefunded);
+    }
+    active_model.updated_at = Set(now_fixed);
+    active_model.update(&txn).await?;
+
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Service function to list the refunds of an order (staff or the customer who placed it)
+pub async fn get_order_refunds(
+    db: &DatabaseConnection,
+    user: &AuthenticatedUser,
+    order_id: i32,
+) -> Result<OrderRefundsResponse, ApiError> {
+    order::Entity::find_by_id(order_id)
+        .one(db)
+        .await?
+        .filter(|order| user.is_staff() || order.user_id == user.id)
+        .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
+
+    build_order_refunds(db, order_id).await
+}
+
+/// Assemble an order with its refunds
+async fn build_order_refunds(
+    db: &DatabaseConnection,
+    order_id: i32,
+) -> Result<OrderRefundsResponse, ApiError> {
+    let order = order::Entity::find_by_id(order_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", order_id)))?;
+
+    let order_items: HashMap<i32, order_item::Model> = order_item::Entity::find()
+        .filter(order_item::Column::OrderId.eq(order_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|item| (item.id, item))
+        .collect();
+
+    let refunds = refund::Entity::find()
+        .filter(refund::Column::OrderId.eq(order_id))
+        .order_by_asc(refund::Column::CreatedAt)
+        .find_with_related(refund_item::Entity)
+        .all(db)
+        .await?;
+
+    let refunded_total: Decimal = refunds
+        .iter()
+        .filter(|(refund, _)| refund.status == RefundStatus::Completed)
+        .map(|(refund, _)| refund.amount)
+        .sum();
+    let pending_total: Decimal = refunds
+        .iter()
+        .filter(|(refund, _)| refund.status == RefundStatus::Pending)
+        .map(|(refund, _)| refund.amount)
+        .sum();
+
+    let refunds = refunds
+        .into_iter()
+        .map(|(refund, items)| RefundResponse {
+            id: refund.id,
+            amount: refund.amount,
+            reason: refund.reason,
+            provider_reference: refund.provider_reference,
+            status: refund.status,
+            created_by: refund.created_by,
+            created_at: refund.created_at.to_string(),
+            items: items
+                .into_iter()
+                .filter_map(|item| {
+                    let line = order_items.get(&item.order_item_id)?;
+                    Some(RefundItemResponse {
+                        order_item_id: item.order_item_id,
+                        product_id: line.product_id,
+                        quantity: item.quantity,
+                        value: units_value(line, item.quantity),
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(OrderRefundsResponse {
+        refundable_amount: order.total_amount - refunded_total - pending_total,
+        refunded_total,
+        order: OrderModel::from(order),
+        refunds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    /// An order line of `quantity` units that cost `gross_amount` together
+    fn line(id: i32, quantity: i32, gross_amount: &str) -> order_item::Model {
+        let now: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+        order_item::Model {
+            id,
+            order_id: 1,
+            product_id: id * 10,
+            quantity,
+            price: dec(gross_amount) / Decimal::from(quantity),
+            created_at: now,
+            updated_at: now,
+            tax_rate: dec("7.00"),
+            net_amount: Decimal::ZERO,
+            tax_amount: Decimal::ZERO,
+            gross_amount: dec(gross_amount),
+            discount_amount: Decimal::ZERO,
+            adjustments: serde_json::json!([]),
+        }
+    }
+
+    fn lines(models: Vec<order_item::Model>) -> HashMap<i32, order_item::Model> {
+        models.into_iter().map(|line| (line.id, line)).collect()
+    }
+
+    fn item(order_item_id: i32, quantity: i32) -> RefundItemRequest {
+        RefundItemRequest {
+            order_item_id,
+            quantity,
+        }
+    }
+
+    fn request(amount: Option<&str>, items: Vec<RefundItemRequest>) -> CreateRefundRequest {
+        CreateRefundRequest {
+            amount: amount.map(dec),
+            items,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn units_value_splits_the_paid_amount_evenly() {
+        let line = line(1, 3, "100.00");
+
+        assert_eq!(units_value(&line, 1), dec("33.33"));
+        assert_eq!(units_value(&line, 2), dec("66.67"));
+        assert_eq!(units_value(&line, 3), dec("100.00"));
+    }
+
+    #[test]
+    fn refunded_items_value_adds_up_the_refunded_units() {
+        let order_items = lines(vec![line(1, 2, "200.00"), line(2, 4, "80.00")]);
+
+        let value =
+            refunded_items_value(1, &[item(1, 1), item(2, 3)], &order_items, &HashMap::new())
+                .unwrap();
+
+        assert_eq!(value, dec("160.00"));
+    }
+
+    #[test]
+    fn refunded_items_value_rejects_units_already_refunded() {
+        let order_items = lines(vec![line(1, 3, "90.00")]);
+        let already_refunded = HashMap::from([(1, 2)]);
+
+        assert!(refunded_items_value(1, &[item(1, 1)], &order_items, &already_refunded).is_ok());
+        let result = refunded_items_value(1, &[item(1, 2)], &order_items, &already_refunded);
+
+        assert!(matches!(
+            result,
+            Err(ApiError::UnprocessableEntity(message)) if message.contains("Only 1 unit(s)")
+        ));
+    }
+
+    #[test]
+    fn refunded_items_value_reports_lines_of_other_orders_as_not_found() {
+        let order_items = lines(vec![line(1, 1, "10.00")]);
+
+        let result = refunded_items_value(1, &[item(99, 1)], &order_items, &HashMap::new());
+
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+    }
+
+    #[test]
+    fn refund_amount_defaults_to_the_items_value() {
+        let amount = refund_amount(1, &request(None, vec![item(1, 1)]), dec("40"), dec("100"));
+
+        assert_eq!(amount.unwrap(), dec("40"));
+    }
+
+    #[test]
+    fn refund_amount_allows_less_than_the_items_value() {
+        let amount = refund_amount(
+            1,
+            &request(Some("25"), vec![item(1, 1)]),
+            dec("40"),
+            dec("100"),
+        );
+
+        assert_eq!(amount.unwrap(), dec("25"));
+    }
+
+    #[test]
+    fn refund_amount_rejects_more_than_the_items_value() {
+        let result = refund_amount(
+            1,
+            &request(Some("41"), vec![item(1, 1)]),
+            dec("40"),
+            dec("100"),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ApiError::UnprocessableEntity(message)) if message.contains("value of the refunded items")
+        ));
+    }
+
+    #[test]
+    fn refund_amount_rejects_more_than_is_left_to_refund() {
+        let result = refund_amount(1, &request(Some("60.01"), vec![]), Decimal::ZERO, dec("60"));
+
+        assert!(matches!(
+            result,
+            Err(ApiError::UnprocessableEntity(message)) if message.contains("left to refund")
+        ));
+        assert_eq!(
+            refund_amount(1, &request(Some("60"), vec![]), Decimal::ZERO, dec("60")).unwrap(),
+            dec("60")
+        );
+    }
+
+    #[test]
+    fn refund_requests_must_say_what_they_return() {
+        assert!(request(None, vec![]).validate().is_err());
+        assert!(request(Some("0"), vec![]).validate().is_err());
+        assert!(request(None, vec![item(1, 1), item(1, 2)])
+            .validate()
+            .is_err());
+        assert!(request(Some("10"), vec![]).validate().is_ok());
+        assert!(request(None, vec![item(1, 1), item(2, 1)])
+            .validate()
+            .is_ok());
+    }
+}
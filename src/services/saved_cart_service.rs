@@ -0,0 +1,179 @@
+use super::cart_abandonment_service::mark_cart_recovered;
+use super::cart_service::{
+    build_cart_details, cap_cart_line, find_active_cart, get_or_create_active_cart, touch_cart,
+    upsert_cart_line, CartDetailResponse,
+};
+use super::product_service::is_purchasable;
+use crate::entities::{cart, cart_item, product, sea_orm_active_enums::CartKind};
+use crate::utils::actix_error::ApiError;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+/// Struct for saving the active cart under a name
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveCartRequest {
+    #[validate(length(min = 1, max = 100, message = "Cart name must be 1-100 characters"))]
+    pub name: String,
+}
+
+/// Fetch a saved cart, making sure it belongs to the given user
+async fn find_saved_cart(
+    db: &DatabaseConnection,
+    user_id: i32,
+    cart_id: i32,
+) -> Result<cart::Model, ApiError> {
+    cart::Entity::find_by_id(cart_id)
+        .filter(cart::Column::UserId.eq(user_id))
+        .filter(cart::Column::Kind.eq(CartKind::Saved))
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Saved cart with ID {} not found", cart_id)))
+}
+
+/// Service function to park the active cart under a name.
+///
+/// The active cart itself becomes the saved cart; a fresh active cart is
+/// created on the next add.
+pub async fn save_active_cart(
+    db: &DatabaseConnection,
+    user_id: i32,
+    request: SaveCartRequest,
+    locale: &str,
+) -> Result<CartDetailResponse, ApiError> {
+    let active_cart = find_active_cart(db, user_id)
+        .await?
+        .ok_or_else(|| ApiError::ValidationError("There is no active cart to save".to_string()))?;
+
+    let has_items = cart_item::Entity::find()
+        .filter(cart_item::Column::CartId.eq(active_cart.id))
+        .one(db)
+        .await?
+        .is_some();
+    if !has_items {
+        return Err(ApiError::ValidationError(
+            "Cannot save an empty cart".to_string(),
+        ));
+    }
+
+    let name_taken = cart::Entity::find()
+        .filter(cart::Column::UserId.eq(user_id))
+        .filter(cart::Column::Kind.eq(CartKind::Saved))
+        .filter(cart::Column::Name.eq(request.name.clone()))
+        .one(db)
+        .await?
+        .is_some();
+    if name_taken {
+        return Err(ApiError::ValidationError(format!(
+            "A saved cart named '{}' already exists",
+            request.name
+        )));
+    }
+
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let mut active_model: cart::ActiveModel = active_cart.into();
+    active_model.kind = Set(CartKind::Saved);
+    active_model.name = Set(Some(request.name));
+    active_model.updated_at = Set(now_fixed);
+    let saved_cart = active_model.update(db).await?;
+    mark_cart_recovered(db, saved_cart.id, now_fixed).await?;
+
+    build_cart_details(db, saved_cart, locale).await
+}
+
+/// Service function to list the saved carts of a user
+pub async fn get_saved_carts(
+    db: &DatabaseConnection,
+    user_id: i32,
+    locale: &str,
+) -> Result<Vec<CartDetailResponse>, ApiError> {
+    let carts = cart::Entity::find()
+        .filter(cart::Column::UserId.eq(user_id))
+        .filter(cart::Column::Kind.eq(CartKind::Saved))
+        .order_by_desc(cart::Column::UpdatedAt)
+        .all(db)
+        .await?;
+
+    let mut responses = Vec::with_capacity(carts.len());
+    for cart in carts {
+        responses.push(build_cart_details(db, cart, locale).await?);
+    }
+
+    Ok(responses)
+}
+
+/// Service function to move a saved cart back into the active cart.
+///
+/// Quantities of products already in the active cart are summed (up to the
+/// product's maximum quantity), lines whose product can no longer be bought
+/// are dropped, and the saved cart is deleted.
+pub async fn restore_saved_cart(
+    db: &DatabaseConnection,
+    user_id: i32,
+    cart_id: i32,
+    locale: &str,
+) -> Result<CartDetailResponse, ApiError> {
+    let saved_cart = find_saved_cart(db, user_id, cart_id).await?;
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let txn = db.begin().await?;
+
+    let active_cart = get_or_create_active_cart(&txn, user_id, now_fixed).await?;
+
+    let saved_items = cart_item::Entity::find()
+        .filter(cart_item::Column::CartId.eq(saved_cart.id))
+        .find_also_related(product::Entity)
+        .all(&txn)
+        .await?;
+
+    for (item, product) in saved_items {
+        let Some(product) = product.filter(|product| is_purchasable(product, now_fixed)) else {
+            continue;
+        };
+        let line = upsert_cart_line(
+            &txn,
+            active_cart.id,
+            item.product_id,
+            item.quantity,
+            item.unit_price,
+            now_fixed,
+        )
+        .await?;
+        cap_cart_line(&txn, line, &product).await?;
+    }
+
+    cart_item::Entity::delete_many()
+        .filter(cart_item::Column::CartId.eq(saved_cart.id))
+        .exec(&txn)
+        .await?;
+    saved_cart.delete(&txn).await?;
+    touch_cart(&txn, active_cart.id, now_fixed).await?;
+
+    txn.commit().await?;
+
+    build_cart_details(db, active_cart, locale).await
+}
+
+/// Service function to delete a saved cart and its items
+pub async fn delete_saved_cart(
+    db: &DatabaseConnection,
+    user_id: i32,
+    cart_id: i32,
+) -> Result<(), ApiError> {
+    let saved_cart = find_saved_cart(db, user_id, cart_id).await?;
+
+    let txn = db.begin().await?;
+    cart_item::Entity::delete_many()
+        .filter(cart_item::Column::CartId.eq(saved_cart.id))
+        .exec(&txn)
+        .await?;
+    saved_cart.delete(&txn).await?;
+    txn.commit().await?;
+
+    Ok(())
+}
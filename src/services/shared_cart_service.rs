@@ -0,0 +1,201 @@
+use super::cart_service::{
+    build_cart_details, get_or_create_active_cart, touch_cart, upsert_cart_line, CartDetailResponse,
+};
+use super::product_service::is_purchasable;
+use crate::entities::{cart, cart_item, product};
+use crate::utils::actix_error::ApiError;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::jwt::{decode_shared_cart_token, generate_shared_cart_token, SharedCartLine};
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use validator::Validate;
+
+/// Default lifetime of a shared cart link (7 days)
+const DEFAULT_SHARE_VALID_HOURS: i64 = 168;
+
+/// Struct for creating a shareable link to a cart
+#[derive(Debug, Deserialize, Validate)]
+pub struct ShareCartRequest {
+    #[validate(range(min = 1, max = 720, message = "Links can be valid for 1-720 hours"))]
+    pub valid_for_hours: Option<i64>,
+}
+
+/// Struct for importing a shared cart into the caller's cart
+#[derive(Debug, Deserialize, Validate)]
+pub struct ImportSharedCartRequest {
+    #[validate(length(min = 1, message = "Share token cannot be empty"))]
+    pub share_token: String,
+}
+
+/// Struct used to return a newly created share link
+#[derive(Debug, Serialize)]
+pub struct SharedCartResponse {
+    pub share_token: String,
+    pub expires_at: String,
+    pub items: Vec<SharedCartLine>,
+}
+
+/// Why a shared line was not (fully) imported
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnavailableReason {
+    /// The product no longer exists
+    Removed,
+    /// The product is a draft or outside its availability window
+    NotAvailable,
+    /// Only part of the quantity fits under the product's maximum quantity
+    QuantityLimited,
+}
+
+/// A shared line that could not be imported as is
+#[derive(Debug, Serialize)]
+pub struct UnavailableItem {
+    pub product_id: i32,
+    pub requested_quantity: i32,
+    pub imported_quantity: i32,
+    pub reason: UnavailableReason,
+}
+
+/// Struct used to return the recipient's cart after an import
+#[derive(Debug, Serialize)]
+pub struct ImportSharedCartResponse {
+    pub cart: CartDetailResponse,
+    pub unavailable_items: Vec<UnavailableItem>,
+}
+
+/// Service function to snapshot a cart into a signed, expiring share token.
+///
+/// Customers can share their own carts; staff can share any cart.
+/// Items saved for later are not shared.
+pub async fn share_cart(
+    db: &DatabaseConnection,
+    user: &AuthenticatedUser,
+    cart_id: i32,
+    request: ShareCartRequest,
+) -> Result<SharedCartResponse, ApiError> {
+    let cart = cart::Entity::find_by_id(cart_id)
+        .one(db)
+        .await?
+        .filter(|cart| user.is_staff() || cart.user_id == Some(user.id))
+        .ok_or_else(|| ApiError::NotFound(format!("Cart with ID {} not found", cart_id)))?;
+
+    let items: Vec<SharedCartLine> = cart_item::Entity::find()
+        .filter(cart_item::Column::CartId.eq(cart.id))
+        .filter(cart_item::Column::SavedForLater.eq(false))
+        .order_by_asc(cart_item::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|item| SharedCartLine {
+            product_id: item.product_id,
+            quantity: item.quantity,
+        })
+        .collect();
+
+    if items.is_empty() {
+        return Err(ApiError::ValidationError(
+            "Cannot share an empty cart".to_string(),
+        ));
+    }
+
+    let valid_for = Duration::hours(request.valid_for_hours.unwrap_or(DEFAULT_SHARE_VALID_HOURS));
+    let share_token =
+        generate_shared_cart_token(user.id, items.clone(), valid_for.num_seconds())
+            .map_err(|_| ApiError::InternalServerError("Failed to sign share token".to_string()))?;
+
+    Ok(SharedCartResponse {
+        share_token,
+        expires_at: (Utc::now() + valid_for).to_string(),
+        items,
+    })
+}
+
+/// Service function to add the lines of a shared cart to the caller's active cart.
+///
+/// Lines are priced at today's price; products that can no longer be bought
+/// are skipped and reported, and quantities are capped at the product's maximum.
+pub async fn import_shared_cart(
+    db: &DatabaseConnection,
+    user_id: i32,
+    request: ImportSharedCartRequest,
+    locale: &str,
+) -> Result<ImportSharedCartResponse, ApiError> {
+    let claims = decode_shared_cart_token(&request.share_token).map_err(|_| {
+        ApiError::ValidationError("Share link is invalid or has expired".to_string())
+    })?;
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let product_ids: Vec<i32> = claims.items.iter().map(|line| line.product_id).collect();
+    let products: HashMap<i32, product::Model> = product::Entity::find()
+        .filter(product::Column::Id.is_in(product_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|product| (product.id, product))
+        .collect();
+
+    let txn = db.begin().await?;
+
+    let cart = get_or_create_active_cart(&txn, user_id, now_fixed).await?;
+
+    let mut unavailable_items = Vec::new();
+    for line in claims.items {
+        let reason = match products.get(&line.product_id) {
+            None => Some(UnavailableReason::Removed),
+            Some(product) if !is_purchasable(product, now_fixed) => {
+                Some(UnavailableReason::NotAvailable)
+            }
+            Some(_) => None,
+        };
+        if let Some(reason) = reason {
+            unavailable_items.push(UnavailableItem {
+                product_id: line.product_id,
+                requested_quantity: line.quantity,
+                imported_quantity: 0,
+                reason,
+            });
+            continue;
+        }
+        let product = &products[&line.product_id];
+
+        let merged_line = upsert_cart_line(
+            &txn,
+            cart.id,
+            line.product_id,
+            line.quantity,
+            product.price,
+            now_fixed,
+        )
+        .await?;
+
+        // Keep the merged line within the product's maximum quantity
+        if let Some(max_quantity) = product.max_quantity {
+            let excess = merged_line.quantity - max_quantity;
+            if excess > 0 {
+                unavailable_items.push(UnavailableItem {
+                    product_id: line.product_id,
+                    requested_quantity: line.quantity,
+                    imported_quantity: (line.quantity - excess).max(0),
+                    reason: UnavailableReason::QuantityLimited,
+                });
+
+                let mut active_model: cart_item::ActiveModel = merged_line.into();
+                active_model.quantity = Set(max_quantity);
+                active_model.update(&txn).await?;
+            }
+        }
+    }
+
+    touch_cart(&txn, cart.id, now_fixed).await?;
+    txn.commit().await?;
+
+    Ok(ImportSharedCartResponse {
+        cart: build_cart_details(db, cart, locale).await?,
+        unavailable_items,
+    })
+}
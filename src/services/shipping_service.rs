@@ -0,0 +1,515 @@
+use super::cart_service::find_active_cart;
+use super::order_service::OrderService;
+use super::pricing_service::{self, CheckoutRequest};
+use crate::entities::sea_orm_active_enums::ShippingMethodKind;
+use crate::entities::{product, shipping_method};
+use crate::utils::actix_error::ApiError;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use validator::{Validate, ValidationError};
+
+/// Address an order is delivered to
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct ShippingAddress {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Recipient name must be 1-100 characters"
+    ))]
+    pub recipient_name: String,
+
+    #[validate(length(min = 1, max = 200, message = "Address line must be 1-200 characters"))]
+    pub address_line1: String,
+
+    #[validate(length(max = 200, message = "Address line cannot exceed 200 characters"))]
+    pub address_line2: Option<String>,
+
+    #[validate(length(min = 1, max = 100, message = "City must be 1-100 characters"))]
+    pub city: String,
+
+    #[validate(length(min = 1, max = 100, message = "Province must be 1-100 characters"))]
+    pub province: String,
+
+    #[validate(length(min = 1, max = 10, message = "Postal code must be 1-10 characters"))]
+    pub postal_code: String,
+
+    #[validate(length(equal = 2, message = "Country code must be a 2-letter ISO code"))]
+    pub country_code: String,
+
+    #[validate(length(max = 20, message = "Phone number cannot exceed 20 characters"))]
+    pub phone: Option<String>,
+}
+
+/// Struct for creating or updating a shipping method (staff only)
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_shipping_method_request"))]
+pub struct ShippingMethodRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be 1-50 characters"))]
+    pub code: String,
+
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+
+    pub kind: ShippingMethodKind,
+
+    /// Flat fee, base fee of weight-based rates, or fee below the free threshold
+    #[serde(default)]
+    pub fee: Decimal,
+
+    /// Fee per started kilogram (weight-based rates)
+    pub per_kg_fee: Option<Decimal>,
+
+    /// Subtotal from which shipping is free (free-over-threshold rates)
+    pub free_over: Option<Decimal>,
+
+    #[validate(range(min = 1, message = "Max weight must be at least 1 gram"))]
+    pub max_weight_grams: Option<i32>,
+
+    /// Only offered for addresses in this country; everywhere when absent
+    #[validate(length(equal = 2, message = "Country code must be a 2-letter ISO code"))]
+    pub country_code: Option<String>,
+
+    /// Defaults to active on creation and to the current value on update
+    pub active: Option<bool>,
+}
+
+/// Struct-level validator making sure every kind of rate has what it needs
+fn validate_shipping_method_request(
+    request: &ShippingMethodRequest,
+) -> Result<(), ValidationError> {
+    let amounts = [Some(request.fee), request.per_kg_fee, request.free_over];
+    if amounts
+        .into_iter()
+        .flatten()
+        .any(|amount| amount < Decimal::ZERO)
+    {
+        let mut error = ValidationError::new("shipping_fee");
+        error.message = Some("Fees and thresholds cannot be negative".into());
+        return Err(error);
+    }
+
+    match request.kind {
+        ShippingMethodKind::WeightBased if request.per_kg_fee.is_none() => {
+            let mut error = ValidationError::new("per_kg_fee");
+            error.message = Some("Weight-based rates need a per_kg_fee".into());
+            Err(error)
+        }
+        ShippingMethodKind::FreeOverThreshold if request.free_over.is_none() => {
+            let mut error = ValidationError::new("free_over");
+            error.message = Some("Free-over-threshold rates need a free_over amount".into());
+            Err(error)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Query parameters for listing the shipping methods available for an address
+#[derive(Debug, Deserialize, Validate)]
+pub struct AvailableShippingMethodsQuery {
+    #[validate(length(equal = 2, message = "Country code must be a 2-letter ISO code"))]
+    pub country_code: String,
+
+    /// The cart to price; defaults to the signed-in user's active cart
+    pub cart_id: Option<i32>,
+}
+
+/// Struct used to return a shipping method
+#[derive(Debug, Serialize)]
+pub struct ShippingMethodResponse {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub kind: ShippingMethodKind,
+    pub fee: Decimal,
+    pub per_kg_fee: Option<Decimal>,
+    pub free_over: Option<Decimal>,
+    pub max_weight_grams: Option<i32>,
+    pub country_code: Option<String>,
+    pub active: bool,
+}
+
+impl From<shipping_method::Model> for ShippingMethodResponse {
+    fn from(model: shipping_method::Model) -> Self {
+        ShippingMethodResponse {
+            id: model.id,
+            code: model.code,
+            name: model.name,
+            kind: model.kind,
+            fee: model.fee,
+            per_kg_fee: model.per_kg_fee,
+            free_over: model.free_over,
+            max_weight_grams: model.max_weight_grams,
+            country_code: model.country_code,
+            active: model.active,
+        }
+    }
+}
+
+/// A shipping method a customer can pick, with its cost when a cart was given
+#[derive(Debug, Serialize)]
+pub struct AvailableShippingMethod {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub kind: ShippingMethodKind,
+    pub cost: Option<Decimal>,
+}
+
+/// Shipping charged for a checkout
+#[derive(Debug, Clone, Serialize)]
+pub struct ShippingQuote {
+    pub method_id: i32,
+    pub method_name: String,
+    pub kind: ShippingMethodKind,
+    pub weight_grams: i64,
+    pub cost: Decimal,
+}
+
+/// Whether a method ships to the country (when there is one) and can carry the weight
+fn is_available(
+    method: &shipping_method::Model,
+    country_code: Option<&str>,
+    weight_grams: i64,
+) -> bool {
+    method.active
+        && method
+            .country_code
+            .as_deref()
+            .zip(country_code)
+            .is_none_or(|(country, wanted)| country.eq_ignore_ascii_case(wanted))
+        && method
+            .max_weight_grams
+            .is_none_or(|max| weight_grams <= i64::from(max))
+}
+
+/// What a method charges for a parcel of the given subtotal and weight
+pub fn shipping_cost(
+    method: &shipping_method::Model,
+    subtotal: Decimal,
+    weight_grams: i64,
+) -> Decimal {
+    match method.kind {
+        ShippingMethodKind::FlatRate => method.fee,
+        ShippingMethodKind::WeightBased => {
+            // Every started kilogram is charged
+            let kilograms = (weight_grams + 999) / 1000;
+            method.fee + method.per_kg_fee.unwrap_or_default() * Decimal::from(kilograms)
+        }
+        ShippingMethodKind::FreeOverThreshold => match method.free_over {
+            Some(free_over) if subtotal >= free_over => Decimal::ZERO,
+            _ => method.fee,
+        },
+        ShippingMethodKind::StorePickup => Decimal::ZERO,
+    }
+}
+
+/// Total shipping weight of some products and quantities, in grams
+pub fn parcel_weight<'a>(lines: impl IntoIterator<Item = (&'a product::Model, i32)>) -> i64 {
+    lines
+        .into_iter()
+        .map(|(product, quantity)| {
+            i64::from(product.weight_grams.unwrap_or(0)) * i64::from(quantity)
+        })
+        .sum()
+}
+
+/// Price the shipping method chosen at checkout.
+///
+/// Delivery methods need an address they ship to; store pickup does not.
+/// Returns `None` when no method was chosen.
+pub async fn quote_shipping<C: ConnectionTrait>(
+    conn: &C,
+    method_id: Option<i32>,
+    address: Option<&ShippingAddress>,
+    subtotal: Decimal,
+    weight_grams: i64,
+) -> Result<Option<ShippingQuote>, ApiError> {
+    let Some(method_id) = method_id else {
+        return Ok(None);
+    };
+
+    let method = shipping_method::Entity::find_by_id(method_id)
+        .filter(shipping_method::Column::Active.eq(true))
+        .one(conn)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Shipping method with ID {} not found", method_id))
+        })?;
+
+    let country_code = match (method.kind, address) {
+        (_, Some(address)) => Some(address.country_code.as_str()),
+        (ShippingMethodKind::StorePickup, None) => None,
+        (_, None) => {
+            return Err(ApiError::ValidationError(format!(
+                "{} needs a shipping address",
+                method.name
+            )))
+        }
+    };
+    if !is_available(&method, country_code, weight_grams) {
+        return Err(ApiError::UnprocessableEntity(format!(
+            "{} is not available for this order",
+            method.name
+        )));
+    }
+
+    Ok(Some(ShippingQuote {
+        method_id: method.id,
+        cost: shipping_cost(&method, subtotal, weight_grams),
+        method_name: method.name,
+        kind: method.kind,
+        weight_grams,
+    }))
+}
+
+/// Subtotal and weight of the parcel a cart would be shipped in
+async fn cart_parcel(
+    db: &DatabaseConnection,
+    user_id: i32,
+    cart_id: i32,
+) -> Result<(Decimal, i64), ApiError> {
+    let items = OrderService::get_cart_items_for_user(db, cart_id).await?;
+    if items.is_empty() {
+        return Ok((Decimal::ZERO, 0));
+    }
+
+    let products: HashMap<i32, product::Model> = product::Entity::find()
+        .filter(product::Column::Id.is_in(items.iter().map(|item| item.product_id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|product| (product.id, product))
+        .collect();
+    let weight_grams = parcel_weight(items.iter().filter_map(|item| {
+        products
+            .get(&item.product_id)
+            .map(|product| (product, item.quantity))
+    }));
+
+    let request = CheckoutRequest {
+        cart_id: Some(cart_id),
+        ..Default::default()
+    };
+    let subtotal = match pricing_service::quote_cart(db, Some(user_id), cart_id, &request).await {
+        Ok(quote) => quote.subtotal - quote.discount_total,
+        Err(e @ (ApiError::DatabaseError(_) | ApiError::InternalServerError(_))) => return Err(e),
+        // A cart checkout would refuse (an expired coupon, a minimum spend not
+        // met, ...) is still shown the base rates, priced without discounts
+        Err(e) => {
+            tracing::debug!(
+                "Pricing shipping for cart {} without discounts: {}",
+                cart_id,
+                e
+            );
+            items
+                .iter()
+                .map(|item| item.unit_price * Decimal::from(item.quantity))
+                .sum()
+        }
+    };
+
+    Ok((subtotal, weight_grams))
+}
+
+/// Service function to list the shipping methods available for an address.
+///
+/// When a user with a cart is given, each method is priced for their cart and
+/// methods that cannot carry the cart are left out. Free-shipping thresholds are
+/// checked against the discounted subtotal, the same one checkout charges.
+pub async fn get_available_shipping_methods(
+    db: &DatabaseConnection,
+    user_id: Option<i32>,
+    query: AvailableShippingMethodsQuery,
+) -> Result<Vec<AvailableShippingMethod>, ApiError> {
+    let methods = shipping_method::Entity::find()
+        .filter(shipping_method::Column::Active.eq(true))
+        .order_by_asc(shipping_method::Column::Fee)
+        .order_by_asc(shipping_method::Column::Id)
+        .all(db)
+        .await?;
+
+    let cart_id = match (user_id, query.cart_id) {
+        // An explicit cart must belong to the user
+        (Some(user_id), Some(cart_id)) => Some(
+            OrderService::get_checkout_cart(db, user_id, Some(cart_id))
+                .await?
+                .id,
+        ),
+        // Users without a cart see the methods unpriced, like anonymous visitors
+        (Some(user_id), None) => find_active_cart(db, user_id).await?.map(|cart| cart.id),
+        (None, Some(_)) => {
+            return Err(ApiError::AuthenticationError(
+                "Sign in to price the shipping methods for a cart".to_string(),
+            ))
+        }
+        (None, None) => None,
+    };
+
+    let parcel = match (user_id, cart_id) {
+        (Some(user_id), Some(cart_id)) => Some(cart_parcel(db, user_id, cart_id).await?),
+        _ => None,
+    };
+
+    Ok(methods
+        .into_iter()
+        .filter(|method| {
+            is_available(
+                method,
+                Some(&query.country_code),
+                parcel.map_or(0, |(_, weight_grams)| weight_grams),
+            )
+        })
+        .map(|method| AvailableShippingMethod {
+            cost: parcel
+                .map(|(subtotal, weight_grams)| shipping_cost(&method, subtotal, weight_grams)),
+            id: method.id,
+            code: method.code,
+            name: method.name,
+            kind: method.kind,
+        })
+        .collect())
+}
+
+/// Service function to list every shipping method, including inactive ones
+pub async fn get_all_shipping_methods(
+    db: &DatabaseConnection,
+) -> Result<Vec<ShippingMethodResponse>, ApiError> {
+    let methods = shipping_method::Entity::find()
+        .order_by_asc(shipping_method::Column::Id)
+        .all(db)
+        .await?;
+
+    Ok(methods
+        .into_iter()
+        .map(ShippingMethodResponse::from)
+        .collect())
+}
+
+/// Fail with 409 if another shipping method already uses the code
+async fn ensure_code_unused(
+    db: &DatabaseConnection,
+    code: &str,
+    except_id: Option<i32>,
+) -> Result<(), ApiError> {
+    let existing = shipping_method::Entity::find()
+        .filter(shipping_method::Column::Code.eq(code))
+        .one(db)
+        .await?;
+
+    match existing {
+        Some(method) if Some(method.id) != except_id => Err(ApiError::Conflict(format!(
+            "A shipping method with code '{}' already exists",
+            code
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Service function to add a shipping method
+pub async fn create_shipping_method(
+    db: &DatabaseConnection,
+    request: ShippingMethodRequest,
+) -> Result<ShippingMethodResponse, ApiError> {
+    ensure_code_unused(db, &request.code, None).await?;
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let method = shipping_method::ActiveModel {
+        code: Set(request.code),
+        name: Set(request.name),
+        kind: Set(request.kind),
+        fee: Set(request.fee),
+        per_kg_fee: Set(request.per_kg_fee),
+        free_over: Set(request.free_over),
+        max_weight_grams: Set(request.max_weight_grams),
+        country_code: Set(request.country_code.map(|code| code.to_uppercase())),
+        active: Set(request.active.unwrap_or(true)),
+        created_at: Set(now_fixed),
+        updated_at: Set(now_fixed),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(ShippingMethodResponse::from(method))
+}
+
+/// Service function to change a shipping method; existing orders keep what they were charged
+pub async fn update_shipping_method(
+    db: &DatabaseConnection,
+    method_id: i32,
+    request: ShippingMethodRequest,
+) -> Result<ShippingMethodResponse, ApiError> {
+    let existing = shipping_method::Entity::find_by_id(method_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Shipping method with ID {} not found", method_id))
+        })?;
+    ensure_code_unused(db, &request.code, Some(method_id)).await?;
+
+    let active = request.active.unwrap_or(existing.active);
+    let mut active_model: shipping_method::ActiveModel = existing.into();
+    active_model.code = Set(request.code);
+    active_model.name = Set(request.name);
+    active_model.kind = Set(request.kind);
+    active_model.fee = Set(request.fee);
+    active_model.per_kg_fee = Set(request.per_kg_fee);
+    active_model.free_over = Set(request.free_over);
+    active_model.max_weight_grams = Set(request.max_weight_grams);
+    active_model.country_code = Set(request.country_code.map(|code| code.to_uppercase()));
+    active_model.active = Set(active);
+    active_model.updated_at = Set(Utc::now().into());
+    let method = active_model.update(db).await?;
+
+    Ok(ShippingMethodResponse::from(method))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::cart;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn flat_rate() -> shipping_method::Model {
+        let now: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+        shipping_method::Model {
+            id: 1,
+            code: "flat".to_string(),
+            name: "Flat rate".to_string(),
+            kind: ShippingMethodKind::FlatRate,
+            fee: Decimal::from(50),
+            per_kg_fee: None,
+            free_over: None,
+            max_weight_grams: None,
+            country_code: None,
+            active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn users_without_a_cart_get_unpriced_methods() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![flat_rate()]])
+            .append_query_results([Vec::<cart::Model>::new()])
+            .into_connection();
+        let query = AvailableShippingMethodsQuery {
+            country_code: "TH".to_string(),
+            cart_id: None,
+        };
+
+        let methods = get_available_shipping_methods(&db, Some(7), query)
+            .await
+            .unwrap();
+
+        assert_eq!(methods.len(), 1);
+        assert_eq!(methods[0].cost, None);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::entities::{product, tax_class};
+use crate::utils::actix_error::ApiError;
+use chrono::Utc;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::iter::Sum;
+use std::ops::Add;
+use validator::{Validate, ValidationError};
+
+/// Code of the tax class used by products (and shipping) without one
+pub const STANDARD_TAX_CLASS: &str = "standard";
+
+/// Thai VAT rate, used if the standard tax class is missing
+const DEFAULT_VAT_RATE_PERCENT: i64 = 7;
+
+/// Whether catalogue prices and shipping fees already include VAT
+/// (overridable with the `PRICES_INCLUDE_VAT` environment variable)
+pub fn prices_include_vat() -> bool {
+    env::var("PRICES_INCLUDE_VAT")
+        .ok()
+        .and_then(|value| value.parse::<bool>().ok())
+        .unwrap_or(true)
+}
+
+/// Round an amount to whole satang (0.01 THB), halves away from zero
+pub fn round_satang(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Net, tax and gross parts of an amount
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TaxBreakdown {
+    pub net: Decimal,
+    pub tax: Decimal,
+    pub gross: Decimal,
+}
+
+impl TaxBreakdown {
+    /// Split a price charged at `rate_percent` into its net, tax and gross parts.
+    ///
+    /// The tax is rounded to the satang once per amount; net and gross are
+    /// derived from it so the three always add up.
+    pub fn from_price(amount: Decimal, rate_percent: Decimal, includes_tax: bool) -> Self {
+        let rate = rate_percent / Decimal::ONE_HUNDRED;
+        let amount = round_satang(amount);
+
+        if includes_tax {
+            let tax = round_satang(amount * rate / (Decimal::ONE + rate));
+            TaxBreakdown {
+                net: amount - tax,
+                tax,
+                gross: amount,
+            }
+        } else {
+            let tax = round_satang(amount * rate);
+            TaxBreakdown {
+                net: amount,
+                tax,
+                gross: amount + tax,
+            }
+        }
+    }
+}
+
+impl Add for TaxBreakdown {
+    type Output = TaxBreakdown;
+
+    fn add(self, other: TaxBreakdown) -> TaxBreakdown {
+        TaxBreakdown {
+            net: self.net + other.net,
+            tax: self.tax + other.tax,
+            gross: self.gross + other.gross,
+        }
+    }
+}
+
+impl Sum for TaxBreakdown {
+    fn sum<I: Iterator<Item = TaxBreakdown>>(iter: I) -> Self {
+        iter.fold(TaxBreakdown::default(), Add::add)
+    }
+}
+
+/// VAT treatment applied to an amount
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxRate {
+    pub class_code: String,
+    /// Percentage, e.g. 7.00 for Thai VAT
+    pub rate_percent: Decimal,
+    pub exempt: bool,
+}
+
+impl From<&tax_class::Model> for TaxRate {
+    fn from(model: &tax_class::Model) -> Self {
+        TaxRate {
+            class_code: model.code.clone(),
+            // Exempt supplies carry no VAT whatever rate was configured
+            rate_percent: if model.exempt {
+                Decimal::ZERO
+            } else {
+                model.rate_percent
+            },
+            exempt: model.exempt,
+        }
+    }
+}
+
+/// The standard VAT rate, charged on products without a tax class and on shipping
+pub async fn standard_tax_rate<C: ConnectionTrait>(conn: &C) -> Result<TaxRate, ApiError> {
+    let standard = tax_class::Entity::find()
+        .filter(tax_class::Column::Code.eq(STANDARD_TAX_CLASS))
+        .one(conn)
+        .await?;
+
+    Ok(standard
+        .as_ref()
+        .map(TaxRate::from)
+        .unwrap_or_else(|| TaxRate {
+            class_code: STANDARD_TAX_CLASS.to_string(),
+            rate_percent: Decimal::from(DEFAULT_VAT_RATE_PERCENT),
+            exempt: false,
+        }))
+}
+
+/// The VAT treatment of each product, keyed by product ID
+pub async fn tax_rates_for_products<'a, C: ConnectionTrait>(
+    conn: &C,
+    products: impl IntoIterator<Item = &'a product::Model>,
+) -> Result<HashMap<i32, TaxRate>, ApiError> {
+    let standard = standard_tax_rate(conn).await?;
+    let classes: HashMap<i32, TaxRate> = tax_class::Entity::find()
+        .all(conn)
+        .await?
+        .iter()
+        .map(|class| (class.id, TaxRate::from(class)))
+        .collect();
+
+    Ok(products
+        .into_iter()
+        .map(|product| {
+            let rate = product
+                .tax_class_id
+                .and_then(|class_id| classes.get(&class_id))
+                .unwrap_or(&standard);
+            (product.id, rate.clone())
+        })
+        .collect())
+}
+
+/// Struct for creating or updating a tax class (staff only)
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_tax_class_request"))]
+pub struct TaxClassRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be 1-50 characters"))]
+    pub code: String,
+
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+
+    /// Percentage, e.g. 7.00 for Thai VAT
+    pub rate_percent: Decimal,
+
+    #[serde(default)]
+    pub exempt: bool,
+}
+
+/// Struct-level validator keeping rates within 0-100% and exempt classes at 0%
+fn validate_tax_class_request(request: &TaxClassRequest) -> Result<(), ValidationError> {
+    if request.rate_percent < Decimal::ZERO || request.rate_percent > Decimal::ONE_HUNDRED {
+        let mut error = ValidationError::new("rate_percent");
+        error.message = Some("Rate must be between 0 and 100 percent".into());
+        return Err(error);
+    }
+
+    if request.exempt && !request.rate_percent.is_zero() {
+        let mut error = ValidationError::new("exempt");
+        error.message = Some("Exempt tax classes must have a rate of 0".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Struct used to return a tax class
+#[derive(Debug, Serialize)]
+pub struct TaxClassResponse {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub rate_percent: Decimal,
+    pub exempt: bool,
+}
+
+impl From<tax_class::Model> for TaxClassResponse {
+    fn from(model: tax_class::Model) -> Self {
+        TaxClassResponse {
+            id: model.id,
+            code: model.code,
+            name: model.name,
+            rate_percent: model.rate_percent,
+            exempt: model.exempt,
+        }
+    }
+}
+
+/// Fail with 404 unless the tax class exists
+pub async fn ensure_tax_class_exists(
+    db: &DatabaseConnection,
+    tax_class_id: i32,
+) -> Result<(), ApiError> {
+    tax_class::Entity::find_by_id(tax_class_id)
+        .one(db)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| ApiError::NotFound(format!("Tax class with ID {} not found", tax_class_id)))
+}
+
+/// Fail with 409 if another tax class already uses the code
+async fn ensure_code_unused(
+    db: &DatabaseConnection,
+    code: &str,
+    except_id: Option<i32>,
+) -> Result<(), ApiError> {
+    let existing = tax_class::Entity::find()
+        .filter(tax_class::Column::Code.eq(code))
+        .one(db)
+        .await?;
+
+    match existing {
+        Some(class) if Some(class.id) != except_id => Err(ApiError::Conflict(format!(
+            "A tax class with code '{}' already exists",
+            code
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Service function to list the tax classes
+pub async fn get_all_tax_classes(
+    db: &DatabaseConnection,
+) -> Result<Vec<TaxClassResponse>, ApiError> {
+    let classes = tax_class::Entity::find()
+        .order_by_asc(tax_class::Column::Id)
+        .all(db)
+        .await?;
+
+    Ok(classes.into_iter().map(TaxClassResponse::from).collect())
+}
+
+/// Service function to add a tax class
+pub async fn create_tax_class(
+    db: &DatabaseConnection,
+    request: TaxClassRequest,
+) -> Result<TaxClassResponse, ApiError> {
+    ensure_code_unused(db, &request.code, None).await?;
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    let class = tax_class::ActiveModel {
+        code: Set(request.code),
+        name: Set(request.name),
+        rate_percent: Set(request.rate_percent),
+        exempt: Set(request.exempt),
+        created_at: Set(now_fixed),
+        updated_at: Set(now_fixed),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(TaxClassResponse::from(class))
+}
+
+/// Service function to change a tax class; placed orders keep the tax they were charged
+pub async fn update_tax_class(
+    db: &DatabaseConnection,
+    tax_class_id: i32,
+    request: TaxClassRequest,
+) -> Result<TaxClassResponse, ApiError> {
+    let existing = tax_class::Entity::find_by_id(tax_class_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Tax class with ID {} not found", tax_class_id))
+        })?;
+    ensure_code_unused(db, &request.code, Some(tax_class_id)).await?;
+
+    let mut active_model: tax_class::ActiveModel = existing.into();
+    active_model.code = Set(request.code);
+    active_model.name = Set(request.name);
+    active_model.rate_percent = Set(request.rate_percent);
+    active_model.exempt = Set(request.exempt);
+    active_model.updated_at = Set(Utc::now().into());
+    let class = active_model.update(db).await?;
+
+    Ok(TaxClassResponse::from(class))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn round_satang_rounds_midpoints_away_from_zero() {
+        assert_eq!(round_satang(dec("0.125")), dec("0.13"));
+        assert_eq!(round_satang(dec("-0.125")), dec("-0.13"));
+        assert_eq!(round_satang(dec("0.124")), dec("0.12"));
+        assert_eq!(round_satang(dec("10")), dec("10.00"));
+    }
+
+    #[test]
+    fn from_price_splits_vat_out_of_inclusive_price() {
+        assert_eq!(
+            TaxBreakdown::from_price(dec("107.00"), dec("7"), true),
+            TaxBreakdown {
+                net: dec("100.00"),
+                tax: dec("7.00"),
+                gross: dec("107.00"),
+            }
+        );
+    }
+
+    #[test]
+    fn from_price_adds_vat_to_exclusive_price() {
+        assert_eq!(
+            TaxBreakdown::from_price(dec("100"), dec("7"), false),
+            TaxBreakdown {
+                net: dec("100.00"),
+                tax: dec("7.00"),
+                gross: dec("107.00"),
+            }
+        );
+    }
+
+    #[test]
+    fn from_price_rounds_midpoint_tax_up() {
+        // 0.50 * 7% = 0.035
+        assert_eq!(
+            TaxBreakdown::from_price(dec("0.50"), dec("7"), false),
+            TaxBreakdown {
+                net: dec("0.50"),
+                tax: dec("0.04"),
+                gross: dec("0.54"),
+            }
+        );
+    }
+
+    #[test]
+    fn from_price_parts_always_add_up() {
+        // 99.99 / 1.07 * 0.07 = 6.5414..., the net takes the difference
+        let breakdown = TaxBreakdown::from_price(dec("99.99"), dec("7"), true);
+        assert_eq!(breakdown.tax, dec("6.54"));
+        assert_eq!(breakdown.net + breakdown.tax, breakdown.gross);
+    }
+
+    #[test]
+    fn from_price_charges_no_tax_at_zero_rate() {
+        assert_eq!(
+            TaxBreakdown::from_price(dec("250.00"), Decimal::ZERO, true),
+            TaxBreakdown {
+                net: dec("250.00"),
+                tax: dec("0.00"),
+                gross: dec("250.00"),
+            }
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use crate::entities::{product, product_translation};
+use crate::models::product::{
+    ProductResponse, ProductTranslationRequest, ProductTranslationResponse,
+};
+use crate::utils::actix_error::ApiError;
+use crate::utils::locale::{default_locale, normalize_locale};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use std::collections::HashMap;
+
+/// Validate a locale path segment against the supported locales
+fn parse_locale(locale: &str) -> Result<String, ApiError> {
+    normalize_locale(locale)
+        .filter(|normalized| normalized == locale)
+        .ok_or_else(|| ApiError::ValidationError(format!("Unsupported locale '{}'", locale)))
+}
+
+/// Find the best translation of each product for `locale`.
+///
+/// Falls back to the default locale's translation; products with neither
+/// are missing from the map and keep their untranslated content.
+pub async fn find_translations(
+    db: &DatabaseConnection,
+    product_ids: Vec<i32>,
+    locale: &str,
+) -> Result<HashMap<i32, product_translation::Model>, ApiError> {
+    if product_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let fallback_locale = default_locale();
+
+    let translations = product_translation::Entity::find()
+        .filter(product_translation::Column::ProductId.is_in(product_ids))
+        .filter(product_translation::Column::Locale.is_in([locale, fallback_locale.as_str()]))
+        .all(db)
+        .await?;
+
+    let mut by_product: HashMap<i32, product_translation::Model> = HashMap::new();
+    for translation in translations {
+        // The requested locale always wins over the fallback
+        let keep_existing = by_product
+            .get(&translation.product_id)
+            .is_some_and(|existing| existing.locale == locale);
+        if !keep_existing {
+            by_product.insert(translation.product_id, translation);
+        }
+    }
+
+    Ok(by_product)
+}
+
+/// Replace product names and descriptions with their translation for `locale`
+pub async fn localize_products<'a>(
+    db: &DatabaseConnection,
+    products: impl IntoIterator<Item = &'a mut ProductResponse>,
+    locale: &str,
+) -> Result<(), ApiError> {
+    let products: Vec<&mut ProductResponse> = products.into_iter().collect();
+    let product_ids: Vec<i32> = products.iter().map(|product| product.id).collect();
+
+    let mut translations = find_translations(db, product_ids, locale).await?;
+
+    for product in products {
+        if let Some(translation) = translations.remove(&product.id) {
+            product.name = translation.name;
+            product.description = translation.description;
+        }
+    }
+
+    Ok(())
+}
+
+/// List every translation of a product (staff only)
+pub async fn get_translations(
+    db: &DatabaseConnection,
+    product_id: i32,
+) -> Result<Vec<ProductTranslationResponse>, ApiError> {
+    product::Entity::find_by_id(product_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Product with ID {} not found", product_id)))?;
+
+    let translations = product_translation::Entity::find()
+        .filter(product_translation::Column::ProductId.eq(product_id))
+        .order_by_asc(product_translation::Column::Locale)
+        .all(db)
+        .await?;
+
+    Ok(translations
+        .into_iter()
+        .map(ProductTranslationResponse::from)
+        .collect())
+}
+
+/// Create or replace the translation of a product for one locale (staff only)
+pub async fn upsert_translation(
+    db: &DatabaseConnection,
+    product_id: i32,
+    locale: &str,
+    request: ProductTranslationRequest,
+) -> Result<ProductTranslationResponse, ApiError> {
+    let locale = parse_locale(locale)?;
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    product::Entity::find_by_id(product_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Product with ID {} not found", product_id)))?;
+
+    let existing = product_translation::Entity::find()
+        .filter(product_translation::Column::ProductId.eq(product_id))
+        .filter(product_translation::Column::Locale.eq(locale.clone()))
+        .one(db)
+        .await?;
+
+    let translation = match existing {
+        Some(existing) => {
+            let mut active_model: product_translation::ActiveModel = existing.into();
+            active_model.name = Set(request.name);
+            active_model.description = Set(request.description);
+            active_model.updated_at = Set(now_fixed);
+            active_model.update(db).await?
+        }
+        None => {
+            product_translation::ActiveModel {
+                product_id: Set(product_id),
+                locale: Set(locale),
+                name: Set(request.name),
+                description: Set(request.description),
+                created_at: Set(now_fixed),
+                updated_at: Set(now_fixed),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?
+        }
+    };
+
+    Ok(ProductTranslationResponse::from(translation))
+}
+
+/// Remove the translation of a product for one locale (staff only)
+pub async fn delete_translation(
+    db: &DatabaseConnection,
+    product_id: i32,
+    locale: &str,
+) -> Result<(), ApiError> {
+    let locale = parse_locale(locale)?;
+
+    let result = product_translation::Entity::delete_many()
+        .filter(product_translation::Column::ProductId.eq(product_id))
+        .filter(product_translation::Column::Locale.eq(locale.clone()))
+        .exec(db)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(ApiError::NotFound(format!(
+            "No '{}' translation for product with ID {}",
+            locale, product_id
+        )));
+    }
+
+    Ok(())
+}
@@ -39,6 +39,8 @@ pub async fn register_user(
     Ok(UserResponse {
         id: result.id,
         email: result.email,
+        role: result.role,
+        cart_id: None,
     })
 }
 pub async fn get_user_by_id(
@@ -52,6 +54,8 @@ pub async fn get_user_by_id(
         Some(user) => Ok(UserResponse {
             id: user.id,
             email: user.email,
+            role: user.role,
+            cart_id: None,
         }),
         None => Err(ApiError::NotFound(format!(
             "User with ID {} not found",
@@ -99,13 +103,15 @@ pub async fn authenticate_user(
     }
 
     // Generate JWT token for the user
-    let token = generate_jwt(user.id, user.email.clone())
+    let token = generate_jwt(user.id, user.email.clone(), user.role)
         .map_err(|_| ApiError::InternalServerError("Failed to generate JWT".to_string()))?;
 
     // Return the response with the token
     Ok(UserLoginResponse {
         id: user.id,
         email: user.email,
+        role: user.role,
         token,
+        cart_id: None,
     })
 }
@@ -0,0 +1,354 @@
+use super::cart_service::{self, CartItemRequest, CartItemResponse};
+use super::product_service::publicly_visible;
+use crate::entities::{product, wishlist, wishlist_item};
+use crate::utils::actix_error::ApiError;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Struct for creating a named wishlist
+#[derive(Debug, Deserialize, Validate)]
+pub struct WishlistRequest {
+    #[validate(length(min = 1, max = 100, message = "Wishlist name must be 1-100 characters"))]
+    pub name: String,
+}
+
+/// Struct for adding a product to a wishlist
+#[derive(Debug, Deserialize, Validate)]
+pub struct WishlistItemRequest {
+    #[validate(range(min = 1))]
+    pub product_id: i32,
+}
+
+/// Struct for moving a wishlist item into the active cart
+#[derive(Debug, Deserialize, Validate)]
+pub struct MoveToCartRequest {
+    #[validate(custom(function = "cart_service::validate_line_quantity"))]
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+/// Struct used to return wishlist details in the response
+#[derive(Debug, Serialize)]
+pub struct WishlistResponse {
+    pub id: i32,
+    pub name: String,
+    pub share_token: Option<String>,
+    pub items: Vec<WishlistItemResponse>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Struct used to return wishlist item details in the response
+#[derive(Debug, Serialize)]
+pub struct WishlistItemResponse {
+    pub id: i32,
+    pub product_id: i32,
+    pub product_name: String,
+    pub price: Decimal,
+    pub added_at: String,
+}
+
+/// Fetch a wishlist, making sure it belongs to the given user
+async fn find_owned_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<wishlist::Model, ApiError> {
+    wishlist::Entity::find_by_id(wishlist_id)
+        .filter(wishlist::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Wishlist with ID {} not found", wishlist_id)))
+}
+
+/// Load the items of a wishlist together with their products
+async fn build_wishlist_response(
+    db: &DatabaseConnection,
+    wishlist: wishlist::Model,
+) -> Result<WishlistResponse, ApiError> {
+    let items = wishlist
+        .find_related(wishlist_item::Entity)
+        .find_also_related(product::Entity)
+        .order_by_asc(wishlist_item::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let items = items
+        .into_iter()
+        .filter_map(|(item, product)| {
+            product.map(|product| WishlistItemResponse {
+                id: item.id,
+                product_id: item.product_id,
+                product_name: product.name,
+                price: product.price,
+                added_at: item.created_at.to_string(),
+            })
+        })
+        .collect();
+
+    Ok(WishlistResponse {
+        id: wishlist.id,
+        name: wishlist.name,
+        share_token: wishlist.share_token,
+        items,
+        created_at: wishlist.created_at.to_string(),
+        updated_at: wishlist.updated_at.to_string(),
+    })
+}
+
+/// Generate a random, URL-safe token for public wishlist links
+fn generate_share_token() -> String {
+    let mut bytes = [0u8; 24];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Service function to create a new wishlist for a user
+pub async fn create_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    request: WishlistRequest,
+) -> Result<WishlistResponse, ApiError> {
+    let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+    // Wishlist names are unique per user
+    let existing = wishlist::Entity::find()
+        .filter(wishlist::Column::UserId.eq(user_id))
+        .filter(wishlist::Column::Name.eq(request.name.clone()))
+        .one(db)
+        .await?;
+    if existing.is_some() {
+        return Err(ApiError::ValidationError(format!(
+            "A wishlist named '{}' already exists",
+            request.name
+        )));
+    }
+
+    let inserted_wishlist = wishlist::ActiveModel {
+        user_id: Set(user_id),
+        name: Set(request.name),
+        share_token: Set(None),
+        created_at: Set(now_fixed),
+        updated_at: Set(now_fixed),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    build_wishlist_response(db, inserted_wishlist).await
+}
+
+/// Service function to list all wishlists of a user
+pub async fn get_wishlists_for_user(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Vec<WishlistResponse>, ApiError> {
+    let wishlists = wishlist::Entity::find()
+        .filter(wishlist::Column::UserId.eq(user_id))
+        .order_by_asc(wishlist::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let mut responses = Vec::with_capacity(wishlists.len());
+    for wishlist in wishlists {
+        responses.push(build_wishlist_response(db, wishlist).await?);
+    }
+
+    Ok(responses)
+}
+
+/// Service function to fetch a single wishlist of a user
+pub async fn get_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<WishlistResponse, ApiError> {
+    let wishlist = find_owned_wishlist(db, user_id, wishlist_id).await?;
+    build_wishlist_response(db, wishlist).await
+}
+
+/// Service function to delete a wishlist and its items
+pub async fn delete_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<(), ApiError> {
+    let wishlist = find_owned_wishlist(db, user_id, wishlist_id).await?;
+
+    // Items are removed by the ON DELETE CASCADE foreign key
+    wishlist.delete(db).await?;
+
+    Ok(())
+}
+
+/// Service function to bookmark a product in a wishlist
+pub async fn add_item_to_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+    request: WishlistItemRequest,
+) -> Result<WishlistResponse, ApiError> {
+    let wishlist = find_owned_wishlist(db, user_id, wishlist_id).await?;
+
+    product::Entity::find_by_id(request.product_id)
+        .filter(publicly_visible(Utc::now().into()))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Product with ID {} not found", request.product_id))
+        })?;
+
+    // Adding a product that is already bookmarked is a no-op
+    let existing = wishlist_item::Entity::find()
+        .filter(wishlist_item::Column::WishlistId.eq(wishlist.id))
+        .filter(wishlist_item::Column::ProductId.eq(request.product_id))
+        .one(db)
+        .await?;
+
+    if existing.is_none() {
+        let now_fixed: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+        wishlist_item::ActiveModel {
+            wishlist_id: Set(wishlist.id),
+            product_id: Set(request.product_id),
+            created_at: Set(now_fixed),
+            updated_at: Set(now_fixed),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+
+    build_wishlist_response(db, wishlist).await
+}
+
+/// Service function to remove a product from a wishlist
+pub async fn remove_item_from_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+    product_id: i32,
+) -> Result<(), ApiError> {
+    let wishlist = find_owned_wishlist(db, user_id, wishlist_id).await?;
+
+    let result = wishlist_item::Entity::delete_many()
+        .filter(wishlist_item::Column::WishlistId.eq(wishlist.id))
+        .filter(wishlist_item::Column::ProductId.eq(product_id))
+        .exec(db)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(ApiError::NotFound(format!(
+            "Product with ID {} is not in wishlist {}",
+            product_id, wishlist_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Service function to move a wishlist item into the user's active cart
+pub async fn move_item_to_cart(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+    product_id: i32,
+    request: MoveToCartRequest,
+) -> Result<CartItemResponse, ApiError> {
+    let wishlist = find_owned_wishlist(db, user_id, wishlist_id).await?;
+
+    let item = wishlist_item::Entity::find()
+        .filter(wishlist_item::Column::WishlistId.eq(wishlist.id))
+        .filter(wishlist_item::Column::ProductId.eq(product_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Product with ID {} is not in wishlist {}",
+                product_id, wishlist_id
+            ))
+        })?;
+
+    // Adding to the cart and removing from the wishlist succeed or fail together
+    let txn = db.begin().await?;
+    let cart = cart_service::get_or_create_active_cart(&txn, user_id, Utc::now().into()).await?;
+    let cart_item_response = cart_service::add_line_to_cart(
+        &txn,
+        cart.id,
+        CartItemRequest {
+            product_id,
+            quantity: request.quantity,
+        },
+    )
+    .await?;
+
+    item.delete(&txn).await?;
+    txn.commit().await?;
+
+    Ok(cart_item_response)
+}
+
+/// Service function to create (or return the existing) public link for a wishlist
+pub async fn share_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<WishlistResponse, ApiError> {
+    let wishlist = find_owned_wishlist(db, user_id, wishlist_id).await?;
+
+    if wishlist.share_token.is_some() {
+        return build_wishlist_response(db, wishlist).await;
+    }
+
+    let mut active_model: wishlist::ActiveModel = wishlist.into();
+    active_model.share_token = Set(Some(generate_share_token()));
+    active_model.updated_at = Set(Utc::now().into());
+    let updated_wishlist = active_model.update(db).await?;
+
+    build_wishlist_response(db, updated_wishlist).await
+}
+
+/// Service function to revoke the public link of a wishlist
+pub async fn unshare_wishlist(
+    db: &DatabaseConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<WishlistResponse, ApiError> {
+    let wishlist = find_owned_wishlist(db, user_id, wishlist_id).await?;
+
+    let mut active_model: wishlist::ActiveModel = wishlist.into();
+    active_model.share_token = Set(None);
+    active_model.updated_at = Set(Utc::now().into());
+    let updated_wishlist = active_model.update(db).await?;
+
+    build_wishlist_response(db, updated_wishlist).await
+}
+
+/// Service function to fetch a wishlist through its public link
+pub async fn get_shared_wishlist(
+    db: &DatabaseConnection,
+    share_token: &str,
+) -> Result<WishlistResponse, ApiError> {
+    let wishlist = wishlist::Entity::find()
+        .filter(wishlist::Column::ShareToken.eq(share_token))
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Shared wishlist not found".to_string()))?;
+
+    let mut response = build_wishlist_response(db, wishlist).await?;
+    // Do not hand the token back out to anonymous viewers
+    response.share_token = None;
+
+    Ok(response)
+}
@@ -4,13 +4,16 @@ use sea_orm::DbErr;
 use serde::Serialize;
 use validator::ValidationErrors;
 
-use crate::services::service_error::ServiceError;
-
 #[derive(Debug, Display)]
 pub enum ApiError {
     DatabaseError(String),
     NotFound(String),
     AuthenticationError(String),
+    Forbidden(String),
+    // Request conflicting with the current state of a resource
+    Conflict(String),
+    // Well-formed request that cannot be applied to the current state
+    UnprocessableEntity(String),
     InternalServerError(String),
     // Manually handle ValidationError, do not derive From for String
     ValidationError(String),
@@ -26,16 +29,6 @@ impl From<DbErr> for ApiError {
     }
 }
 
-// Implement From<ServiceError> for ApiError
-impl From<ServiceError> for ApiError {
-    fn from(err: ServiceError) -> Self {
-        match err {
-            ServiceError::Database(e) => ApiError::DatabaseError(e.to_string()),
-            _ => ApiError::NotFound("".into()),
-        }
-    }
-}
-
 // Manually implement From<ValidationErrors> for ApiError
 impl From<ValidationErrors> for ApiError {
     fn from(err: ValidationErrors) -> Self {
@@ -86,6 +79,18 @@ impl error::ResponseError for ApiError {
                 error: "Authentication error".to_string(),
                 message: msg.clone(),
             },
+            ApiError::Forbidden(msg) => ErrorResponse {
+                error: "Forbidden".to_string(),
+                message: msg.clone(),
+            },
+            ApiError::Conflict(msg) => ErrorResponse {
+                error: "Conflict".to_string(),
+                message: msg.clone(),
+            },
+            ApiError::UnprocessableEntity(msg) => ErrorResponse {
+                error: "Unprocessable entity".to_string(),
+                message: msg.clone(),
+            },
             ApiError::InternalServerError(_) => ErrorResponse {
                 error: "Internal server error".to_string(),
                 message: "An unexpected error occurred".to_string(),
@@ -101,6 +106,9 @@ impl error::ResponseError for ApiError {
             ApiError::ValidationError(_) => StatusCode::BAD_REQUEST,
             ApiError::NotFound(_) => StatusCode::NOT_FOUND,
             ApiError::AuthenticationError(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
             ApiError::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
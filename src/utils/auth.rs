@@ -0,0 +1,159 @@
+use crate::entities::cart;
+use crate::entities::sea_orm_active_enums::UserRole;
+use crate::utils::actix_error::ApiError;
+use crate::utils::jwt::{decode_guest_cart_token, decode_jwt};
+use actix_web::{dev::Payload, http::header, FromRequest, HttpRequest};
+use std::future::{ready, Ready};
+
+/// The user resolved from the `Authorization: Bearer <token>` header
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub id: i32,
+    pub role: UserRole,
+}
+
+impl AuthenticatedUser {
+    /// Staff and admins may manage the catalogue and see unpublished products
+    pub fn is_staff(&self) -> bool {
+        matches!(self.role, UserRole::Staff | UserRole::Admin)
+    }
+
+    /// Fail with 403 unless the user is staff
+    pub fn require_staff(&self) -> Result<(), ApiError> {
+        if self.is_staff() {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(
+                "This endpoint is restricted to staff".to_string(),
+            ))
+        }
+    }
+
+    /// Fail with 403 unless the user is an admin
+    pub fn require_admin(&self) -> Result<(), ApiError> {
+        if self.role == UserRole::Admin {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(
+                "This endpoint is restricted to admins".to_string(),
+            ))
+        }
+    }
+
+    fn from_request_headers(req: &HttpRequest) -> Result<Self, ApiError> {
+        let header_value = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                ApiError::AuthenticationError("Missing Authorization header".to_string())
+            })?;
+
+        let token = header_value.strip_prefix("Bearer ").ok_or_else(|| {
+            ApiError::AuthenticationError("Authorization header must be a Bearer token".to_string())
+        })?;
+
+        let claims = decode_jwt(token.trim())
+            .map_err(|_| ApiError::AuthenticationError("Invalid or expired token".to_string()))?;
+
+        Ok(AuthenticatedUser {
+            id: claims.sub,
+            role: claims.role,
+        })
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Self::from_request_headers(req))
+    }
+}
+
+/// Header carrying the signed token of an anonymous visitor's cart
+pub const CART_TOKEN_HEADER: &str = "X-Cart-Token";
+
+/// The guest cart resolved from the `X-Cart-Token` header
+#[derive(Debug, Clone)]
+pub struct GuestCartToken {
+    pub cart_id: i32,
+}
+
+impl GuestCartToken {
+    fn from_request_headers(req: &HttpRequest) -> Result<Self, ApiError> {
+        let token = req
+            .headers()
+            .get(CART_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                ApiError::AuthenticationError(format!("Missing {} header", CART_TOKEN_HEADER))
+            })?;
+
+        let claims = decode_guest_cart_token(token.trim()).map_err(|_| {
+            ApiError::AuthenticationError("Invalid or expired cart token".to_string())
+        })?;
+
+        Ok(GuestCartToken {
+            cart_id: claims.cart_id,
+        })
+    }
+}
+
+impl FromRequest for GuestCartToken {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Self::from_request_headers(req))
+    }
+}
+
+/// Whoever is acting on a cart: a signed-in user, or an anonymous visitor
+/// holding a guest cart token.
+///
+/// The `Authorization` header wins when both headers are sent.
+#[derive(Debug, Clone)]
+pub enum CartCaller {
+    User(AuthenticatedUser),
+    Guest(GuestCartToken),
+}
+
+impl CartCaller {
+    /// Whether the cart belongs to the caller
+    pub fn owns(&self, cart: &cart::Model) -> bool {
+        match self {
+            CartCaller::User(user) => cart.user_id == Some(user.id),
+            CartCaller::Guest(token) => cart.user_id.is_none() && cart.id == token.cart_id,
+        }
+    }
+
+    /// Staff may read any cart, but only change their own
+    pub fn is_staff(&self) -> bool {
+        matches!(self, CartCaller::User(user) if user.is_staff())
+    }
+
+    fn from_request_headers(req: &HttpRequest) -> Result<Self, ApiError> {
+        if req.headers().contains_key(header::AUTHORIZATION) {
+            return AuthenticatedUser::from_request_headers(req).map(CartCaller::User);
+        }
+        if req.headers().contains_key(CART_TOKEN_HEADER) {
+            return GuestCartToken::from_request_headers(req).map(CartCaller::Guest);
+        }
+
+        Err(ApiError::AuthenticationError(format!(
+            "Missing Authorization or {} header",
+            CART_TOKEN_HEADER
+        )))
+    }
+}
+
+impl FromRequest for CartCaller {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Self::from_request_headers(req))
+    }
+}
@@ -0,0 +1,106 @@
+use crate::services::idempotency_service::{self, IdempotencyState, StoredResponse};
+use crate::utils::actix_error::ApiError;
+use actix_web::{dev::Payload, http::StatusCode, FromRequest, HttpRequest, HttpResponse};
+use sea_orm::DatabaseConnection;
+use std::future::{ready, Future, Ready};
+
+/// Header carrying the client-chosen key of a retryable request
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header set on responses replayed from an earlier request
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// Longest accepted idempotency key
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// The optional `Idempotency-Key` header of a request
+#[derive(Debug, Clone)]
+pub struct IdempotencyKey(pub Option<String>);
+
+impl IdempotencyKey {
+    fn from_request_headers(req: &HttpRequest) -> Result<Self, ApiError> {
+        let Some(value) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(IdempotencyKey(None));
+        };
+
+        let key = value
+            .to_str()
+            .map(str::trim)
+            .ok()
+            .filter(|key| !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH)
+            .ok_or_else(|| {
+                ApiError::ValidationError(format!(
+                    "{} must be 1-{} visible characters",
+                    IDEMPOTENCY_KEY_HEADER, MAX_IDEMPOTENCY_KEY_LENGTH
+                ))
+            })?;
+
+        Ok(IdempotencyKey(Some(key.to_owned())))
+    }
+}
+
+impl FromRequest for IdempotencyKey {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Self::from_request_headers(req))
+    }
+}
+
+/// Run `handler` at most once per idempotency key.
+///
+/// Keys are scoped to the operation and to the user sending the request, so
+/// two users picking the same key never see each other's responses.
+/// Successful responses are stored and replayed on retries; failed requests
+/// release the key so the client can retry with it. Without a key the
+/// handler simply runs.
+pub async fn run_idempotent<F, Fut>(
+    db: &DatabaseConnection,
+    key: IdempotencyKey,
+    operation: &str,
+    user_id: i32,
+    fingerprint: String,
+    handler: F,
+) -> Result<HttpResponse, ApiError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<(StatusCode, serde_json::Value), ApiError>>,
+{
+    let Some(key) = key.0 else {
+        let (status, body) = handler().await?;
+        return Ok(HttpResponse::build(status).json(body));
+    };
+    let scope = &idempotency_service::scope(operation, user_id);
+
+    if let IdempotencyState::Replay(stored) =
+        idempotency_service::begin(db, scope, &key, &fingerprint).await?
+    {
+        let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+        return Ok(HttpResponse::build(status)
+            .insert_header((IDEMPOTENT_REPLAYED_HEADER, "true"))
+            .json(stored.body));
+    }
+
+    match handler().await {
+        Ok((status, body)) if status.is_success() => {
+            let response = HttpResponse::build(status).json(&body);
+            idempotency_service::complete(
+                db,
+                scope,
+                &key,
+                StoredResponse {
+                    status: status.as_u16(),
+                    body,
+                },
+            )
+            .await?;
+            Ok(response)
+        }
+        outcome => {
+            idempotency_service::release(db, scope, &key).await?;
+            let (status, body) = outcome?;
+            Ok(HttpResponse::build(status).json(body))
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use printpdf::{IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Rect};
+use qrcode::{Color, EcLevel, QrCode};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::fs::File;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const LINE_HEIGHT: f32 = 5.5;
+const QR_SIZE: f32 = 40.0;
+
+/// Issuer of the invoice, printed in the header
+#[derive(Debug, Clone)]
+pub struct InvoiceSeller {
+    pub name: String,
+    pub tax_id: String,
+    pub branch: String,
+    pub address: String,
+}
+
+/// One row of the items table
+#[derive(Debug, Clone)]
+pub struct InvoiceLine {
+    pub description: String,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    pub discount: Decimal,
+    pub tax_rate: Decimal,
+    pub amount: Decimal,
+}
+
+/// Everything printed on an invoice or receipt
+#[derive(Debug, Clone)]
+pub struct InvoiceDocument {
+    pub title: String,
+    pub invoice_number: String,
+    pub issued_on: String,
+    pub order_id: i32,
+    pub seller: InvoiceSeller,
+    /// Name, address and contact lines of the buyer
+    pub buyer: Vec<String>,
+    pub lines: Vec<InvoiceLine>,
+    pub discount: Decimal,
+    pub shipping: Decimal,
+    pub net: Decimal,
+    pub tax: Decimal,
+    pub total: Decimal,
+    /// PromptPay payload to print as a QR code while the order is unpaid
+    pub promptpay_payload: Option<String>,
+    /// Lines printed under the totals, e.g. about refunds
+    pub notes: Vec<String>,
+}
+
+/// Render an invoice to PDF bytes.
+///
+/// `font_path` must point to a TrueType font with Thai glyphs.
+pub fn render_invoice(
+    invoice: &InvoiceDocument,
+    font_path: &str,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (doc, page, layer) = PdfDocument::new(
+        invoice.invoice_number.as_str(),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Invoice",
+    );
+    let font = doc.add_external_font(File::open(font_path)?)?;
+
+    let mut writer = PageWriter {
+        doc: &doc,
+        layer: doc.get_page(page).get_layer(layer),
+        font: &font,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    // Header: title, document number and seller
+    writer.text(&invoice.title, 16.0, MARGIN);
+    writer.y -= 3.0;
+    writer.text(&invoice.seller.name, 11.0, MARGIN);
+    writer.text_at(
+        &format!("เลขที่ / No.: {}", invoice.invoice_number),
+        10.0,
+        125.0,
+        writer.y + LINE_HEIGHT,
+    );
+    writer.text_at(
+        &format!("วันที่ / Date: {}", invoice.issued_on),
+        10.0,
+        125.0,
+        writer.y,
+    );
+    writer.text_at(
+        &format!("คำสั่งซื้อ / Order: #{}", invoice.order_id),
+        10.0,
+        125.0,
+        writer.y - LINE_HEIGHT,
+    );
+    for line in invoice.seller.address.lines() {
+        writer.text(line, 9.0, MARGIN);
+    }
+    writer.text(
+        &format!(
+            "เลขประจำตัวผู้เสียภาษี / Tax ID: {}  ({})",
+            invoice.seller.tax_id, invoice.seller.branch
+        ),
+        9.0,
+        MARGIN,
+    );
+
+    // Buyer
+    writer.y -= 4.0;
+    writer.text("ลูกค้า / Customer", 10.0, MARGIN);
+    for line in &invoice.buyer {
+        writer.text(line, 9.0, MARGIN + 4.0);
+    }
+
+    // Items table
+    writer.y -= 4.0;
+    writer.table_header();
+    for (index, line) in invoice.lines.iter().enumerate() {
+        writer.ensure_space(LINE_HEIGHT * 2.0);
+        writer.table_row(
+            &format!("{}. {}", index + 1, truncate(&line.description, 38)),
+            &[
+                line.quantity.to_string(),
+                format_amount(line.unit_price),
+                format_amount(line.discount),
+                format!("{}%", line.tax_rate.normalize()),
+                format_amount(line.amount),
+            ],
+        );
+    }
+
+    // Totals
+    writer.ensure_space(LINE_HEIGHT * 7.0 + QR_SIZE);
+    writer.y -= 3.0;
+    let totals_top = writer.y;
+    let totals = [
+        ("ส่วนลด / Discount", invoice.discount),
+        ("ค่าจัดส่ง / Shipping", invoice.shipping),
+        ("มูลค่าก่อนภาษี / Net amount", invoice.net),
+        ("ภาษีมูลค่าเพิ่ม / VAT", invoice.tax),
+        ("จำนวนเงินรวม / Grand total", invoice.total),
+    ];
+    for (label, amount) in totals {
+        writer.text_at(label, 9.0, 110.0, writer.y);
+        writer.text_at(&format_amount(amount), 9.0, 178.0, writer.y);
+        writer.y -= LINE_HEIGHT;
+    }
+
+    // PromptPay QR next to the totals for orders still awaiting payment
+    if let Some(payload) = &invoice.promptpay_payload {
+        writer.qr_code(payload, MARGIN, totals_top - QR_SIZE + LINE_HEIGHT)?;
+        writer.text_at(
+            "สแกนเพื่อชำระเงิน / Scan to pay with PromptPay",
+            8.0,
+            MARGIN,
+            totals_top - QR_SIZE,
+        );
+    }
+
+    if !invoice.notes.is_empty() {
+        writer.y -= 3.0;
+        for note in &invoice.notes {
+            writer.text(note, 9.0, MARGIN);
+        }
+    }
+
+    Ok(doc.save_to_bytes()?)
+}
+
+/// Draws text top-down, starting a new page when the current one is full
+struct PageWriter<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: &'a IndirectFontRef,
+    y: f32,
+}
+
+impl PageWriter<'_> {
+    /// Write a line at the cursor and move the cursor down
+    fn text(&mut self, text: &str, size: f32, x: f32) {
+        self.ensure_space(LINE_HEIGHT);
+        self.text_at(text, size, x, self.y);
+        self.y -= LINE_HEIGHT;
+    }
+
+    /// Write text at a fixed position without moving the cursor
+    fn text_at(&self, text: &str, size: f32, x: f32, y: f32) {
+        self.layer.use_text(text, size, Mm(x), Mm(y), self.font);
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+
+        let (page, layer) = self
+            .doc
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Invoice");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn table_header(&mut self) {
+        self.table_row(
+            "รายการ / Description",
+            &[
+                "จำนวน / Qty".to_string(),
+                "ราคา / Price".to_string(),
+                "ส่วนลด / Disc.".to_string(),
+                "VAT".to_string(),
+                "จำนวนเงิน / Amount".to_string(),
+            ],
+        );
+        self.rule();
+    }
+
+    fn table_row(&mut self, description: &str, columns: &[String]) {
+        const COLUMNS: [f32; 5] = [92.0, 106.0, 126.0, 146.0, 160.0];
+
+        self.ensure_space(LINE_HEIGHT);
+        self.text_at(description, 8.5, MARGIN, self.y);
+        for (x, column) in COLUMNS.iter().zip(columns) {
+            self.text_at(column, 8.5, *x, self.y);
+        }
+        self.y -= LINE_HEIGHT;
+    }
+
+    /// Thin horizontal line across the page
+    fn rule(&mut self) {
+        self.layer.add_rect(Rect::new(
+            Mm(MARGIN),
+            Mm(self.y + 3.5),
+            Mm(PAGE_WIDTH - MARGIN),
+            Mm(self.y + 3.7),
+        ));
+        self.y -= 1.0;
+    }
+
+    /// Draw a QR code as filled squares with its bottom-left corner at (x, y)
+    fn qr_code(&self, payload: &str, x: f32, y: f32) -> Result<(), Box<dyn Error>> {
+        let code = QrCode::with_error_correction_level(payload.as_bytes(), EcLevel::M)?;
+        let width = code.width();
+        let module = QR_SIZE / width as f32;
+
+        for (index, color) in code.to_colors().into_iter().enumerate() {
+            if color != Color::Dark {
+                continue;
+            }
+            let column = (index % width) as f32;
+            let row = (index / width) as f32;
+            let left = x + column * module;
+            let top = y + QR_SIZE - row * module;
+            self.layer.add_rect(Rect::new(
+                Mm(left),
+                Mm(top - module),
+                Mm(left + module),
+                Mm(top),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Format an amount with thousands separators and two decimals (1,234.50)
+fn format_amount(amount: Decimal) -> String {
+    let formatted = format!("{:.2}", amount.abs());
+    let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+
+    let mut grouped = String::new();
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if amount.is_sign_negative() && !amount.is_zero() {
+        "-"
+    } else {
+        ""
+    };
+    format!("{}{}.{}", sign, grouped, fraction)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn format_amount_groups_thousands_with_two_decimals() {
+        assert_eq!(format_amount(dec("0")), "0.00");
+        assert_eq!(format_amount(dec("999.5")), "999.50");
+        assert_eq!(format_amount(dec("1234.5")), "1,234.50");
+        assert_eq!(format_amount(dec("1234567.891")), "1,234,567.89");
+    }
+
+    #[test]
+    fn format_amount_keeps_the_sign_of_negative_amounts() {
+        assert_eq!(format_amount(dec("-1234.5")), "-1,234.50");
+        assert_eq!(format_amount(dec("-0.00")), "0.00");
+    }
+
+    #[test]
+    fn truncate_keeps_short_text() {
+        assert_eq!(truncate("Mug", 10), "Mug");
+        assert_eq!(truncate("ถ้วยกาแฟ", 8), "ถ้วยกาแฟ");
+    }
+
+    #[test]
+    fn truncate_cuts_on_characters_not_bytes() {
+        assert_eq!(truncate("Coffee mug", 5), "Coff…");
+        // Thai characters take three bytes each in UTF-8
+        assert_eq!(truncate("ถ้วยกาแฟเซรามิก", 5), "ถ้วย…");
+    }
+}
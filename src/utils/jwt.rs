@@ -1,16 +1,38 @@
+use crate::entities::sea_orm_active_enums::UserRole;
 use chrono::Utc;
-use jsonwebtoken::{encode, errors::Error, EncodingKey, Header};
+use jsonwebtoken::{decode, encode, errors::Error, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::env;
 
+/// Audience of the tokens signing users in
+const USER_TOKEN_AUDIENCE: &str = "user";
+
+/// Audience of the tokens granting access to a guest cart
+const GUEST_CART_TOKEN_AUDIENCE: &str = "guest-cart";
+
+/// Audience of the tokens carrying a shared cart snapshot
+const SHARED_CART_TOKEN_AUDIENCE: &str = "shared-cart";
+
+/// All tokens share the secret, so each kind is told apart by its audience
+/// and a token of one kind is never accepted as another
+fn validation_for(audience: &str) -> Validation {
+    // Signature and expiration are both checked by the default validation
+    let mut validation = Validation::default();
+    validation.set_audience(&[audience]);
+    validation
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i32, // User ID
     pub email: String,
     pub exp: usize, // Expiration time
+    pub aud: String,
+    #[serde(default)]
+    pub role: UserRole, // Tokens issued before roles existed are treated as customers
 }
 
-pub fn generate_jwt(user_id: i32, email: String) -> Result<String, Error> {
+pub fn generate_jwt(user_id: i32, email: String, role: UserRole) -> Result<String, Error> {
     let expiration = 3600; // Token expiration time in seconds (1 hour)
 
     // Set claims
@@ -18,6 +40,8 @@ pub fn generate_jwt(user_id: i32, email: String) -> Result<String, Error> {
         sub: user_id,
         email,
         exp: (Utc::now().timestamp() + expiration as i64) as usize,
+        aud: USER_TOKEN_AUDIENCE.to_string(),
+        role,
     };
 
     // Secret key (should be stored in env variables in production)
@@ -32,3 +56,166 @@ pub fn generate_jwt(user_id: i32, email: String) -> Result<String, Error> {
 
     Ok(token)
 }
+
+/// Verify a JWT token and return its claims
+pub fn decode_jwt(token: &str) -> Result<Claims, Error> {
+    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    let token_data = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_ref()),
+        &validation_for(USER_TOKEN_AUDIENCE),
+    )?;
+
+    Ok(token_data.claims)
+}
+
+/// Claims of the opaque token identifying an anonymous visitor's cart
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestCartClaims {
+    pub cart_id: i32,
+    pub exp: usize, // Expiration time
+    pub aud: String,
+}
+
+/// Sign a token granting access to a guest cart
+pub fn generate_guest_cart_token(cart_id: i32, valid_for_secs: i64) -> Result<String, Error> {
+    let claims = GuestCartClaims {
+        cart_id,
+        exp: (Utc::now().timestamp() + valid_for_secs) as usize,
+        aud: GUEST_CART_TOKEN_AUDIENCE.to_string(),
+    };
+
+    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_ref()),
+    )
+}
+
+/// Verify a guest cart token and return the cart it grants access to
+pub fn decode_guest_cart_token(token: &str) -> Result<GuestCartClaims, Error> {
+    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    let token_data = decode::<GuestCartClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_ref()),
+        &validation_for(GUEST_CART_TOKEN_AUDIENCE),
+    )?;
+
+    Ok(token_data.claims)
+}
+
+/// One line of a cart shared through a link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedCartLine {
+    pub product_id: i32,
+    pub quantity: i32,
+}
+
+/// Claims of a shareable cart link: a snapshot of the cart's lines
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedCartClaims {
+    pub shared_by: i32, // User ID of the sharer
+    pub items: Vec<SharedCartLine>,
+    pub exp: usize, // Expiration time
+    pub aud: String,
+}
+
+/// Sign a snapshot of cart lines into a shareable token
+pub fn generate_shared_cart_token(
+    shared_by: i32,
+    items: Vec<SharedCartLine>,
+    valid_for_secs: i64,
+) -> Result<String, Error> {
+    let claims = SharedCartClaims {
+        shared_by,
+        items,
+        exp: (Utc::now().timestamp() + valid_for_secs) as usize,
+        aud: SHARED_CART_TOKEN_AUDIENCE.to_string(),
+    };
+
+    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_ref()),
+    )
+}
+
+/// Verify a shareable cart token and return the snapshot it carries
+pub fn decode_shared_cart_token(token: &str) -> Result<SharedCartClaims, Error> {
+    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    let token_data = decode::<SharedCartClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_ref()),
+        &validation_for(SHARED_CART_TOKEN_AUDIENCE),
+    )?;
+
+    Ok(token_data.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_secret() {
+        env::set_var("JWT_SECRET", "test-secret");
+    }
+
+    #[test]
+    fn each_kind_of_token_decodes_as_itself() {
+        set_secret();
+
+        let user = generate_jwt(7, "a@example.com".to_string(), UserRole::Customer).unwrap();
+        assert_eq!(decode_jwt(&user).unwrap().sub, 7);
+
+        let guest = generate_guest_cart_token(3, 60).unwrap();
+        assert_eq!(decode_guest_cart_token(&guest).unwrap().cart_id, 3);
+
+        let shared = generate_shared_cart_token(7, Vec::new(), 60).unwrap();
+        assert_eq!(decode_shared_cart_token(&shared).unwrap().shared_by, 7);
+    }
+
+    #[test]
+    fn tokens_are_not_accepted_as_another_kind() {
+        set_secret();
+
+        let user = generate_jwt(7, "a@example.com".to_string(), UserRole::Customer).unwrap();
+        let guest = generate_guest_cart_token(3, 60).unwrap();
+        let shared = generate_shared_cart_token(7, Vec::new(), 60).unwrap();
+
+        assert!(decode_guest_cart_token(&user).is_err());
+        assert!(decode_shared_cart_token(&user).is_err());
+        assert!(decode_jwt(&guest).is_err());
+        assert!(decode_shared_cart_token(&guest).is_err());
+        assert!(decode_jwt(&shared).is_err());
+        assert!(decode_guest_cart_token(&shared).is_err());
+    }
+
+    #[test]
+    fn tokens_without_an_audience_are_rejected() {
+        set_secret();
+
+        #[derive(Serialize)]
+        struct LegacyGuestClaims {
+            cart_id: i32,
+            exp: usize,
+        }
+        let legacy = encode(
+            &Header::default(),
+            &LegacyGuestClaims {
+                cart_id: 3,
+                exp: (Utc::now().timestamp() + 60) as usize,
+            },
+            &EncodingKey::from_secret("test-secret".as_ref()),
+        )
+        .unwrap();
+
+        assert!(decode_guest_cart_token(&legacy).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpRequest};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::env;
+use std::future::{ready, Ready};
+
+/// Locales we hold product content for
+pub const SUPPORTED_LOCALES: [&str; 2] = ["th", "en"];
+
+/// Locale used when the client asks for nothing we support
+/// (overridable with the `DEFAULT_LOCALE` environment variable)
+pub fn default_locale() -> String {
+    env::var("DEFAULT_LOCALE")
+        .ok()
+        .and_then(|locale| normalize_locale(&locale))
+        .unwrap_or_else(|| "th".to_string())
+}
+
+/// Reduce a language tag such as `en-US` to a supported primary subtag
+pub fn normalize_locale(tag: &str) -> Option<String> {
+    let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    SUPPORTED_LOCALES
+        .contains(&primary.as_str())
+        .then_some(primary)
+}
+
+#[derive(Deserialize)]
+struct LangQuery {
+    lang: Option<String>,
+}
+
+/// The locale requested by the client.
+///
+/// Resolved from the `lang` query parameter first, then from the
+/// `Accept-Language` header, and finally from the default locale.
+#[derive(Debug, Clone)]
+pub struct RequestLocale(pub String);
+
+impl RequestLocale {
+    fn resolve(req: &HttpRequest) -> Self {
+        let from_query = web::Query::<LangQuery>::from_query(req.query_string())
+            .ok()
+            .and_then(|query| query.into_inner().lang)
+            .and_then(|lang| normalize_locale(&lang));
+
+        let locale = from_query
+            .or_else(|| {
+                req.headers()
+                    .get(header::ACCEPT_LANGUAGE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(Self::from_accept_language)
+            })
+            .unwrap_or_else(default_locale);
+
+        RequestLocale(locale)
+    }
+
+    /// Pick the best supported locale from an `Accept-Language` header value
+    fn from_accept_language(value: &str) -> Option<String> {
+        let mut candidates: Vec<(String, f32)> = value
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (quality > 0.0).then(|| (tag.to_string(), quality))
+            })
+            .collect();
+
+        // Stable sort keeps the header order for equal weights
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        candidates
+            .into_iter()
+            .find_map(|(tag, _)| normalize_locale(&tag))
+    }
+}
+
+impl FromRequest for RequestLocale {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(Self::resolve(req)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn normalize_locale_keeps_supported_primary_subtag() {
+        assert_eq!(normalize_locale("en-US"), Some("en".to_string()));
+        assert_eq!(normalize_locale(" TH_th "), Some("th".to_string()));
+        assert_eq!(normalize_locale("fr-FR"), None);
+        assert_eq!(normalize_locale(""), None);
+    }
+
+    #[test]
+    fn accept_language_prefers_highest_quality() {
+        assert_eq!(
+            RequestLocale::from_accept_language("th;q=0.5, en-GB;q=0.9"),
+            Some("en".to_string())
+        );
+    }
+
+    #[test]
+    fn accept_language_keeps_header_order_for_equal_weights() {
+        assert_eq!(
+            RequestLocale::from_accept_language("th-TH, en"),
+            Some("th".to_string())
+        );
+    }
+
+    #[test]
+    fn accept_language_skips_unsupported_and_zero_quality_tags() {
+        assert_eq!(
+            RequestLocale::from_accept_language("fr, en;q=0, th;q=0.1"),
+            Some("th".to_string())
+        );
+        assert_eq!(RequestLocale::from_accept_language("de, *;q=0.5"), None);
+    }
+
+    #[test]
+    fn query_parameter_overrides_accept_language() {
+        let req = TestRequest::default()
+            .uri("/products?lang=en")
+            .insert_header((header::ACCEPT_LANGUAGE, "th"))
+            .to_http_request();
+
+        assert_eq!(RequestLocale::resolve(&req).0, "en");
+    }
+
+    #[test]
+    fn unsupported_query_parameter_falls_back_to_header() {
+        let req = TestRequest::default()
+            .uri("/products?lang=fr")
+            .insert_header((header::ACCEPT_LANGUAGE, "en-US,en;q=0.9"))
+            .to_http_request();
+
+        assert_eq!(RequestLocale::resolve(&req).0, "en");
+    }
+}
@@ -1,3 +1,9 @@
 pub mod actix_error;
+pub mod auth;
+pub mod idempotency;
+pub mod invoice_pdf;
 pub mod jwt;
+pub mod locale;
+pub mod notifier;
+pub mod payment_provider;
 pub mod prompt_pay;
@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+
+/// Error returned by a notifier that could not deliver a message
+pub type NotifierError = Box<dyn Error + Send + Sync>;
+
+/// Content of a reminder about a cart the customer left behind
+#[derive(Debug, Clone, Serialize)]
+pub struct AbandonedCartReminder {
+    pub cart_id: i32,
+    pub user_id: i32,
+    pub email: String,
+    pub item_count: i32,
+    pub cart_total: Decimal,
+    pub abandoned_at: String,
+}
+
+/// Delivery channel for customer notifications (e-mail, LINE, push, ...)
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send_abandoned_cart_reminder(
+        &self,
+        reminder: &AbandonedCartReminder,
+    ) -> Result<(), NotifierError>;
+}
+
+/// Notifier that only writes the notification to the log
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn send_abandoned_cart_reminder(
+        &self,
+        reminder: &AbandonedCartReminder,
+    ) -> Result<(), NotifierError> {
+        tracing::info!(
+            "Abandoned cart reminder for {} (cart {}, {} items, total {})",
+            reminder.email,
+            reminder.cart_id,
+            reminder.item_count,
+            reminder.cart_total
+        );
+        Ok(())
+    }
+}
+
+/// Notifier that silently drops every notification
+pub struct NoopNotifier;
+
+#[async_trait]
+impl Notifier for NoopNotifier {
+    async fn send_abandoned_cart_reminder(
+        &self,
+        _reminder: &AbandonedCartReminder,
+    ) -> Result<(), NotifierError> {
+        Ok(())
+    }
+}
+
+/// Pick the notifier named by the `NOTIFIER` environment variable (`log` by default)
+pub fn notifier_from_env() -> Arc<dyn Notifier> {
+    match env::var("NOTIFIER").as_deref() {
+        Ok("none") => Arc::new(NoopNotifier),
+        Ok("log") | Err(_) => Arc::new(LogNotifier),
+        Ok(other) => {
+            tracing::warn!(
+                "Unknown notifier '{}', falling back to the log notifier",
+                other
+            );
+            Arc::new(LogNotifier)
+        }
+    }
+}
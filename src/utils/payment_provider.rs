@@ -0,0 +1,110 @@
+use crate::utils::prompt_pay::PromptPayUtils;
+use async_trait::async_trait;
+use chrono::Utc;
+use dotenvy::dotenv;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+
+/// Error returned by a payment provider that could not carry out an operation
+pub type PaymentError = Box<dyn Error + Send + Sync>;
+
+/// Gateway through which order payments are collected and returned
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    /// Ask the customer to pay an order
+    async fn request_payment(&self, order_id: i32, amount: Decimal) -> Result<(), PaymentError>;
+
+    /// Capture the payment of an order once the customer has paid
+    async fn capture(&self, order_id: i32, amount: Decimal) -> Result<(), PaymentError>;
+
+    /// Withdraw an outstanding payment request so it can no longer be paid
+    async fn void_payment(&self, order_id: i32) -> Result<(), PaymentError>;
+
+    /// Return money to the customer; yields the provider's reference for the refund
+    async fn refund(&self, order_id: i32, amount: Decimal) -> Result<String, PaymentError>;
+}
+
+/// PromptPay: customers pay by scanning a QR code, refunds are sent back by transfer
+pub struct PromptPayProvider;
+
+impl PromptPayProvider {
+    /// Path of the QR code image of an order
+    fn qr_code_path(order_id: i32) -> String {
+        format!("qrcodes/order_{}_qr.png", order_id)
+    }
+
+    /// Delete the QR code of an order so it can no longer be scanned
+    fn remove_qr_code(order_id: i32) -> Result<(), PaymentError> {
+        let qr_code_path = Self::qr_code_path(order_id);
+
+        match std::fs::remove_file(&qr_code_path) {
+            Ok(_) => {
+                tracing::info!("QR Code removed: {}", qr_code_path);
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for PromptPayProvider {
+    async fn request_payment(&self, order_id: i32, amount: Decimal) -> Result<(), PaymentError> {
+        dotenv().ok();
+        let phone_number = env::var("My_PHONE_NUMBER").map_err(|_| "My_PHONE_NUMBER not set")?;
+        let amount = amount
+            .to_f64()
+            .ok_or("Order total cannot be encoded in a QR code")?;
+        let qr_code_path = Self::qr_code_path(order_id);
+
+        PromptPayUtils::generate_qr(phone_number, amount, &qr_code_path)
+            .map_err(|e| e.to_string())?;
+
+        tracing::info!("QR Code saved to: {}", qr_code_path);
+        Ok(())
+    }
+
+    async fn capture(&self, order_id: i32, _amount: Decimal) -> Result<(), PaymentError> {
+        // The transfer has already arrived; make sure the QR code is not paid twice
+        Self::remove_qr_code(order_id)
+    }
+
+    async fn void_payment(&self, order_id: i32) -> Result<(), PaymentError> {
+        Self::remove_qr_code(order_id)
+    }
+
+    async fn refund(&self, order_id: i32, amount: Decimal) -> Result<String, PaymentError> {
+        // PromptPay transfers cannot be reversed; the money is sent back by hand
+        let reference = format!(
+            "promptpay-refund-{}-{}",
+            order_id,
+            Utc::now().timestamp_millis()
+        );
+        tracing::info!(
+            "Refund {} of {} THB for order {} to be transferred back to the customer",
+            reference,
+            amount,
+            order_id
+        );
+        Ok(reference)
+    }
+}
+
+/// Pick the payment provider named by the `PAYMENT_PROVIDER` environment variable
+/// (`promptpay` by default)
+pub fn payment_provider_from_env() -> Arc<dyn PaymentProvider> {
+    match env::var("PAYMENT_PROVIDER").as_deref() {
+        Ok("promptpay") | Err(_) => Arc::new(PromptPayProvider),
+        Ok(other) => {
+            tracing::warn!(
+                "Unknown payment provider '{}', falling back to PromptPay",
+                other
+            );
+            Arc::new(PromptPayProvider)
+        }
+    }
+}
@@ -32,7 +32,7 @@ impl PromptPayUtils {
     }
 
     /// Generate the PromptPay payload string
-    fn generate_payload(phone_number: String, amount: f64) -> Result<String, String> {
+    pub fn generate_payload(phone_number: String, amount: f64) -> Result<String, String> {
         // Sanitize the phone number to ensure it meets PromptPay's requirements
         let sanitized_phone = Self::sanitize_phone_number(phone_number)?;
 
@@ -48,14 +48,8 @@ impl PromptPayUtils {
 
         // Calculate the CRC and append it to the payload
         let crc = Self::calculate_precise_crc(&payload);
-        println!("This is payload: {}", payload);
-        println!("This is return CRC: {}", crc);
-        let final_payload = format!("{}{}", payload, crc);
 
-        // Debugging: print the final payload
-        println!("Generated Payload: {}", final_payload);
-
-        Ok(final_payload)
+        Ok(format!("{}{}", payload, crc))
     }
 
     /// Sanitize the phone number by ensuring it's valid for PromptPay
@@ -94,12 +88,6 @@ impl PromptPayUtils {
         let crc_value = digest.finalize();
 
         // Convert the CRC value to a 4-character hexadecimal string
-        let formatted_crc = format!("{:04X}", crc_value);
-
-        // Debugging outputs
-        println!("Raw CRC Value: {}", crc_value);
-        println!("Formatted CRC Value: {}", formatted_crc);
-
-        formatted_crc
+        format!("{:04X}", crc_value)
     }
 }